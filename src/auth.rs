@@ -0,0 +1,345 @@
+//! Who may call Tee, and what each caller may do.
+//!
+//! A principals file names every caller, the roles it holds, and the bearer tokens it
+//! may present. A caller authenticates with an `authorization: Bearer <token>` header
+//! or, failing that, with a client certificate whose identity is a principal's name.
+//! Roles decide which RPCs a principal may call; a principal may only write under its
+//! own name as `Provenance.source`.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tonic::metadata::MetadataMap;
+
+use crate::tls::ClientIdentity;
+
+#[derive(Debug, thiserror::Error)]
+pub enum PrincipalsError {
+    #[error("failed to read principals file {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid principals file: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("invalid principals file: {0:?} is listed twice")]
+    DuplicatePrincipal(String),
+    #[error("invalid principals file: token hash {0:?} is not 64 hex digits")]
+    InvalidTokenHash(String),
+    #[error("invalid principals file: a token of {0:?} is also a token of {1:?}")]
+    SharedToken(String, String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AuthError {
+    #[error("no credentials: send a bearer token or a client certificate")]
+    MissingCredentials,
+    #[error("malformed authorization header (expected \"Bearer <token>\")")]
+    MalformedAuthorization,
+    #[error("unknown bearer token")]
+    UnknownToken,
+    #[error("client certificate names {0:?}, which is not a known principal")]
+    UnknownIdentity(String),
+}
+
+/// What a principal is allowed to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Reads only.
+    Reader,
+    /// Reads, and proposes hypotheses with `MergeHypothesis`.
+    Hypothesis,
+    /// Reads, and runs the Meet Phase: incidents, confirmations and tombstones.
+    Cmbs,
+}
+
+/// The kinds of RPC roles grant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Read,
+    /// `MergeHypothesis`.
+    Hypothesize,
+    /// `CreateIncident`, `ConfirmNodes`, `MergeNodeTombstones` and `MergeEdgeTombstones`.
+    Meet,
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Action::Read => "read",
+            Action::Hypothesize => "merge hypotheses",
+            Action::Meet => "manage incidents and tombstones",
+        })
+    }
+}
+
+impl Role {
+    pub fn permits(self, action: Action) -> bool {
+        match action {
+            Action::Read => true,
+            Action::Hypothesize => self == Role::Hypothesis,
+            Action::Meet => self == Role::Cmbs,
+        }
+    }
+}
+
+/// An authenticated caller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    pub name: String,
+    pub roles: BTreeSet<Role>,
+}
+
+impl Principal {
+    pub fn permits(&self, action: Action) -> bool {
+        self.roles.iter().any(|role| role.permits(action))
+    }
+}
+
+/// On-disk form: one entry per principal. Tokens are stored as their SHA-256, in hex.
+///
+/// ```toml
+/// [[principals]]
+/// name = "cmbs"
+/// roles = ["cmbs"]
+/// token_sha256 = ["<64 hex digits>"]
+/// ```
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PrincipalsFile {
+    #[serde(default)]
+    principals: Vec<PrincipalEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PrincipalEntry {
+    name: String,
+    roles: BTreeSet<Role>,
+    #[serde(default)]
+    token_sha256: Vec<String>,
+}
+
+/// The callers allowed to use the server, by name and by token hash.
+#[derive(Debug, Clone, Default)]
+pub struct Principals {
+    by_name: HashMap<String, Principal>,
+    by_token: HashMap<[u8; 32], String>,
+}
+
+impl Principals {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PrincipalsError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|source| PrincipalsError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::from_toml(&text)
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, PrincipalsError> {
+        let file: PrincipalsFile = toml::from_str(text)?;
+        let mut principals = Self::default();
+        for entry in file.principals {
+            if principals.by_name.contains_key(&entry.name) {
+                return Err(PrincipalsError::DuplicatePrincipal(entry.name));
+            }
+            for hex in &entry.token_sha256 {
+                let hash = parse_hash(hex)
+                    .ok_or_else(|| PrincipalsError::InvalidTokenHash(hex.clone()))?;
+                if let Some(other) = principals.by_token.insert(hash, entry.name.clone()) {
+                    return Err(PrincipalsError::SharedToken(other, entry.name));
+                }
+            }
+            let principal = Principal {
+                name: entry.name.clone(),
+                roles: entry.roles,
+            };
+            principals.by_name.insert(entry.name, principal);
+        }
+        Ok(principals)
+    }
+
+    /// The principal a request's bearer token names or, without one, the principal its
+    /// client certificate names.
+    pub fn authenticate(
+        &self,
+        metadata: &MetadataMap,
+        identity: Option<&ClientIdentity>,
+    ) -> Result<&Principal, AuthError> {
+        if let Some(header) = metadata.get("authorization") {
+            let token = header
+                .to_str()
+                .ok()
+                .and_then(|value| value.strip_prefix("Bearer "))
+                .ok_or(AuthError::MalformedAuthorization)?;
+            return self
+                .by_token
+                .get(&sha256(token))
+                .map(|name| &self.by_name[name])
+                .ok_or(AuthError::UnknownToken);
+        }
+        let identity = identity.ok_or(AuthError::MissingCredentials)?;
+        self.by_name
+            .get(&identity.principal)
+            .ok_or_else(|| AuthError::UnknownIdentity(identity.principal.clone()))
+    }
+}
+
+fn sha256(token: &str) -> [u8; 32] {
+    let digest = ring::digest::digest(&ring::digest::SHA256, token.as_bytes());
+    digest
+        .as_ref()
+        .try_into()
+        .expect("SHA-256 digests are 32 bytes")
+}
+
+fn parse_hash(hex: &str) -> Option<[u8; 32]> {
+    if hex.len() != 64 || !hex.is_ascii() {
+        return None;
+    }
+    let mut hash = [0; 32];
+    for (i, byte) in hash.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).ok()?;
+    }
+    Some(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // SHA-256 of "cmbs-token" and "agent-token"
+    const PRINCIPALS: &str = r#"
+        [[principals]]
+        name = "cmbs"
+        roles = ["cmbs"]
+        token_sha256 = ["8f02bace520876461545beb8db937ec164aeed97cf63fbdbd4b397a478a21d43"]
+
+        [[principals]]
+        name = "agent-1"
+        roles = ["hypothesis"]
+        token_sha256 = ["eb47b9ce4840a5b3ea138b8691253b78035b9289d9014409067e9844c272ef99"]
+
+        [[principals]]
+        name = "dashboard"
+        roles = ["reader"]
+    "#;
+
+    fn principals() -> Principals {
+        Principals::from_toml(PRINCIPALS).unwrap()
+    }
+
+    fn bearer(token: &str) -> MetadataMap {
+        let mut metadata = MetadataMap::new();
+        metadata.insert("authorization", format!("Bearer {token}").parse().unwrap());
+        metadata
+    }
+
+    #[test]
+    fn authenticates_by_token_then_certificate() {
+        let principals = principals();
+        let agent = principals
+            .authenticate(&bearer("agent-token"), None)
+            .unwrap();
+        assert_eq!(agent.name, "agent-1");
+        let cmbs = principals
+            .authenticate(&bearer("cmbs-token"), None)
+            .unwrap();
+        assert_eq!(cmbs.roles, [Role::Cmbs].into());
+
+        let identity = ClientIdentity {
+            principal: "dashboard".into(),
+        };
+        let reader = principals
+            .authenticate(&MetadataMap::new(), Some(&identity))
+            .unwrap();
+        assert_eq!(reader.name, "dashboard");
+        // A token takes precedence over the certificate
+        let agent = principals
+            .authenticate(&bearer("agent-token"), Some(&identity))
+            .unwrap();
+        assert_eq!(agent.name, "agent-1");
+    }
+
+    #[test]
+    fn rejects_unknown_and_missing_credentials() {
+        let principals = principals();
+        let err = principals.authenticate(&bearer("guess"), None).unwrap_err();
+        assert_eq!(err, AuthError::UnknownToken);
+        let err = principals
+            .authenticate(&MetadataMap::new(), None)
+            .unwrap_err();
+        assert_eq!(err, AuthError::MissingCredentials);
+
+        let mut basic = MetadataMap::new();
+        basic.insert("authorization", "Basic YWdlbnQ=".parse().unwrap());
+        let err = principals.authenticate(&basic, None).unwrap_err();
+        assert_eq!(err, AuthError::MalformedAuthorization);
+
+        let stranger = ClientIdentity {
+            principal: "agent-9".into(),
+        };
+        let err = principals
+            .authenticate(&MetadataMap::new(), Some(&stranger))
+            .unwrap_err();
+        assert_eq!(err, AuthError::UnknownIdentity("agent-9".into()));
+    }
+
+    #[test]
+    fn roles_grant_actions() {
+        assert!(Role::Reader.permits(Action::Read));
+        assert!(!Role::Reader.permits(Action::Hypothesize));
+        assert!(Role::Hypothesis.permits(Action::Hypothesize));
+        assert!(!Role::Hypothesis.permits(Action::Meet));
+        assert!(Role::Cmbs.permits(Action::Meet));
+        assert!(!Role::Cmbs.permits(Action::Hypothesize));
+    }
+
+    #[test]
+    fn example_principals_load() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("config/principals.toml");
+        let principals = Principals::load(path).unwrap();
+        let identity = ClientIdentity {
+            principal: "cmbs".into(),
+        };
+        let cmbs = principals
+            .authenticate(&MetadataMap::new(), Some(&identity))
+            .unwrap();
+        assert!(cmbs.permits(Action::Meet));
+        // The example ships no token anyone could know
+        let err = principals
+            .authenticate(&bearer("cmbs-token"), None)
+            .unwrap_err();
+        assert_eq!(err, AuthError::UnknownToken);
+    }
+
+    #[test]
+    fn invalid_files_rejected() {
+        let result = Principals::from_toml("[[principals]]\nname = \"a\"\nroles = [\"admin\"]\n");
+        assert!(matches!(result, Err(PrincipalsError::Parse(_))));
+
+        let result = Principals::from_toml(
+            "[[principals]]\nname = \"a\"\nroles = []\ntoken_sha256 = [\"abc\"]\n",
+        );
+        assert!(matches!(result, Err(PrincipalsError::InvalidTokenHash(_))));
+
+        let twice = "[[principals]]\nname = \"a\"\nroles = []\n";
+        let result = Principals::from_toml(&twice.repeat(2));
+        assert!(matches!(
+            result,
+            Err(PrincipalsError::DuplicatePrincipal(_))
+        ));
+
+        // A repeated entry is a duplicate principal, even though its tokens repeat too
+        let twice = format!("{twice}token_sha256 = [\"{}\"]\n", "ab".repeat(32));
+        let result = Principals::from_toml(&twice.repeat(2));
+        assert!(matches!(
+            result,
+            Err(PrincipalsError::DuplicatePrincipal(name)) if name == "a"
+        ));
+    }
+}
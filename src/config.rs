@@ -1,14 +1,973 @@
+//! Server configuration, merged from a TOML file, `TEE_*` environment variables and
+//! command-line flags. Flags override the environment, which overrides the file.
+//!
+//! Every setting has one name in each source, listed in `SETTINGS`: a dotted key in
+//! the file (`store.neo4j.uri` is `uri` in the `[store.neo4j]` table), an environment
+//! variable and, for most, a flag. The file is named by `--config` or `TEE_CONFIG`.
+//! Values are validated once all sources are merged, and errors name the source the
+//! offending value came from.
+
+use std::collections::HashMap;
+use std::ffi::OsString;
 use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use clap::{Arg, ArgMatches, Command};
+
+use crate::proto::DanglingEdgePolicy;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error(transparent)]
+    Cli(#[from] clap::Error),
+    #[error("failed to read config file {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid config file {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("unknown setting {key} in {path}")]
+    UnknownSetting { key: String, path: PathBuf },
+    #[error("unknown store backend {0:?} (expected \"memory\" or \"neo4j\")")]
+    UnknownStoreBackend(String),
+    #[error("{} must be set {}", describe(.0), .1)]
+    MissingSetting(&'static str, &'static str),
+    #[error("invalid value {value:?} for {origin}")]
+    InvalidSetting {
+        key: &'static str,
+        value: String,
+        /// The setting as named where the value came from.
+        origin: String,
+    },
+    #[error("{origin} names {path}, which is not a file")]
+    MissingFile { origin: String, path: PathBuf },
+}
+
+/// One setting's names: its dotted key in the config file, its environment variable
+/// and its command-line flag. Settings are identified by their environment variable.
+struct Setting {
+    file: &'static str,
+    env: &'static str,
+    /// `None` for secrets, which would leak into process listings.
+    flag: Option<&'static str>,
+    help: &'static str,
+}
+
+/// Environment variable naming the config file; `--config` overrides it.
+const CONFIG_ENV: &str = "TEE_CONFIG";
+
+const SETTINGS: &[Setting] = &[
+    Setting {
+        file: "listen_addr",
+        env: "TEE_LISTEN_ADDR",
+        flag: Some("listen-addr"),
+        help: "Address the gRPC server listens on",
+    },
+    Setting {
+        file: "metrics_addr",
+        env: "TEE_METRICS_ADDR",
+        flag: Some("metrics-addr"),
+        help: "Address the HTTP /metrics endpoint listens on; unset serves no metrics",
+    },
+    Setting {
+        file: "store.backend",
+        env: "TEE_STORE",
+        flag: Some("store"),
+        help: "Store backend: memory or neo4j",
+    },
+    Setting {
+        file: "store.data_dir",
+        env: "TEE_DATA_DIR",
+        flag: Some("data-dir"),
+        help: "Directory that makes the memory backend durable",
+    },
+    Setting {
+        file: "store.snapshot_every",
+        env: "TEE_SNAPSHOT_EVERY",
+        flag: Some("snapshot-every"),
+        help: "Logged writes between snapshots of a durable memory backend (0 = never)",
+    },
+    Setting {
+        file: "store.neo4j.uri",
+        env: "TEE_NEO4J_URI",
+        flag: Some("neo4j-uri"),
+        help: "Neo4j Bolt address",
+    },
+    Setting {
+        file: "store.neo4j.user",
+        env: "TEE_NEO4J_USER",
+        flag: Some("neo4j-user"),
+        help: "Neo4j user",
+    },
+    Setting {
+        file: "store.neo4j.password",
+        env: "TEE_NEO4J_PASSWORD",
+        flag: None,
+        help: "Neo4j password",
+    },
+    Setting {
+        file: "store.neo4j.database",
+        env: "TEE_NEO4J_DATABASE",
+        flag: Some("neo4j-database"),
+        help: "Neo4j database (default: the server's default database)",
+    },
+    Setting {
+        file: "store.neo4j.max_connections",
+        env: "TEE_NEO4J_MAX_CONNECTIONS",
+        flag: Some("neo4j-max-connections"),
+        help: "Size of the Neo4j connection pool",
+    },
+    Setting {
+        file: "tls.cert_file",
+        env: "TEE_TLS_CERT_FILE",
+        flag: Some("tls-cert-file"),
+        help: "PEM certificate chain served over TLS",
+    },
+    Setting {
+        file: "tls.key_file",
+        env: "TEE_TLS_KEY_FILE",
+        flag: Some("tls-key-file"),
+        help: "PEM private key of the TLS certificate",
+    },
+    Setting {
+        file: "tls.client_ca_file",
+        env: "TEE_TLS_CLIENT_CA_FILE",
+        flag: Some("tls-client-ca-file"),
+        help: "PEM CA bundle that client certificates must chain to",
+    },
+    Setting {
+        file: "tls.client_auth_optional",
+        env: "TEE_TLS_CLIENT_AUTH_OPTIONAL",
+        flag: Some("tls-client-auth-optional"),
+        help: "Also accept clients without a certificate: true or false",
+    },
+    Setting {
+        file: "auth_file",
+        env: "TEE_AUTH_FILE",
+        flag: Some("auth-file"),
+        help: "Principals allowed to call the server; unset admits every caller",
+    },
+    Setting {
+        file: "limits.max_message_bytes",
+        env: "TEE_MAX_MESSAGE_BYTES",
+        flag: Some("max-message-bytes"),
+        help: "Largest gRPC message accepted or sent",
+    },
+    Setting {
+        file: "limits.request_timeout_secs",
+        env: "TEE_REQUEST_TIMEOUT_SECS",
+        flag: Some("request-timeout-secs"),
+        help: "Seconds a request may take before it is cancelled",
+    },
+    Setting {
+        file: "limits.concurrency_per_connection",
+        env: "TEE_CONCURRENCY_PER_CONNECTION",
+        flag: Some("concurrency-per-connection"),
+        help: "Requests served at once on one connection",
+    },
+    Setting {
+        file: "log.format",
+        env: "TEE_LOG_FORMAT",
+        flag: Some("log-format"),
+        help: "Log format: text or json",
+    },
+    Setting {
+        file: "log.filter",
+        env: "TEE_LOG_FILTER",
+        flag: Some("log-filter"),
+        help: "Log filter directives (default: RUST_LOG)",
+    },
+    Setting {
+        file: "dangling_edges",
+        env: "TEE_DANGLING_EDGES",
+        flag: Some("dangling-edges"),
+        help: "Default dangling-edge policy: report, reject or placeholder",
+    },
+    Setting {
+        file: "schema_file",
+        env: "TEE_SCHEMA_FILE",
+        flag: Some("schema-file"),
+        help: "Causal schema to enforce on hypothesis edges",
+    },
+    Setting {
+        file: "types_file",
+        env: "TEE_TYPES_FILE",
+        flag: Some("types-file"),
+        help: "Type registry of extra node and edge kinds",
+    },
+];
+
+fn setting(key: &str) -> &'static Setting {
+    SETTINGS
+        .iter()
+        .find(|s| s.env == key)
+        .unwrap_or_else(|| panic!("unknown setting {key}"))
+}
+
+/// Every name of the setting `key`, for errors that don't come from one source.
+fn describe(key: &str) -> String {
+    let setting = setting(key);
+    match setting.flag {
+        Some(flag) => format!("{key} (`{}`, --{flag})", setting.file),
+        None => format!("{key} (`{}`)", setting.file),
+    }
+}
+
+/// Settings for the in-memory backend. With a `data_dir` the store is durable:
+/// writes go to a write-ahead log there and are compacted into a snapshot every
+/// `snapshot_every` records.
+#[derive(Debug, Clone)]
+pub struct MemoryConfig {
+    pub data_dir: Option<PathBuf>,
+    pub snapshot_every: u64,
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        Self {
+            data_dir: None,
+            snapshot_every: 1000,
+        }
+    }
+}
+
+/// Connection settings for the Neo4j backend.
+#[derive(Clone)]
+pub struct Neo4jConfig {
+    pub uri: String,
+    pub user: String,
+    pub password: String,
+    /// Database to run in; `None` uses the server's default database.
+    pub database: Option<String>,
+    pub max_connections: usize,
+}
+
+impl Neo4jConfig {
+    pub const DEFAULT_MAX_CONNECTIONS: usize = 16;
+}
+
+impl std::fmt::Debug for Neo4jConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Neo4jConfig")
+            .field("uri", &self.uri)
+            .field("user", &self.user)
+            .field("password", &"<redacted>")
+            .field("database", &self.database)
+            .field("max_connections", &self.max_connections)
+            .finish()
+    }
+}
+
+/// Which [`Store`](crate::store::Store) implementation the server runs against.
+#[derive(Debug, Clone)]
+pub enum StoreBackend {
+    Memory(MemoryConfig),
+    Neo4j(Neo4jConfig),
+}
+
+/// Certificate and key the gRPC endpoint serves TLS with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlsConfig {
+    pub cert_file: PathBuf,
+    pub key_file: PathBuf,
+    /// When set, clients must present a certificate that chains to this CA bundle.
+    pub client_ca_file: Option<PathBuf>,
+    /// Whether clients may still connect without a certificate.
+    pub client_auth_optional: bool,
+}
+
+/// Bounds on the requests the server accepts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Limits {
+    /// Largest encoded message, in bytes, accepted or sent.
+    pub max_message_bytes: usize,
+    /// Time a request may take before it is cancelled; `None` waits indefinitely.
+    pub request_timeout: Option<Duration>,
+    /// Requests served at once on one connection; `None` is unbounded.
+    pub concurrency_per_connection: Option<usize>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_message_bytes: 4 * 1024 * 1024,
+            request_timeout: None,
+            concurrency_per_connection: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LogConfig {
+    pub format: LogFormat,
+    /// `tracing` filter directives; `None` reads `RUST_LOG`.
+    pub filter: Option<String>,
+}
 
 #[derive(Debug, Clone)]
 pub struct Config {
     pub listen_addr: SocketAddr,
+    /// Address of the Prometheus `/metrics` endpoint; none is served when unset.
+    pub metrics_addr: Option<SocketAddr>,
+    pub store: StoreBackend,
+    /// Serves plaintext when unset.
+    pub tls: Option<TlsConfig>,
+    /// Principals file; when set, every request must authenticate as one of them.
+    pub auth_file: Option<PathBuf>,
+    pub limits: Limits,
+    pub log: LogConfig,
+    /// Policy for hypothesis deltas that don't choose one themselves.
+    pub dangling_edge_policy: DanglingEdgePolicy,
+    /// Causal schema enforced on hypothesis edges; unset accepts any typed edge.
+    pub schema_file: Option<PathBuf>,
+    /// Type registry adding node and edge kinds to the built-in ones.
+    pub types_file: Option<PathBuf>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             listen_addr: "[::1]:50051".parse().unwrap(),
+            metrics_addr: None,
+            store: StoreBackend::Memory(MemoryConfig::default()),
+            tls: None,
+            auth_file: None,
+            limits: Limits::default(),
+            log: LogConfig::default(),
+            dangling_edge_policy: DanglingEdgePolicy::Report,
+            schema_file: None,
+            types_file: None,
+        }
+    }
+}
+
+/// Where a layer of settings was read from.
+#[derive(Debug, Clone)]
+enum Origin {
+    Flags,
+    Env,
+    File(PathBuf),
+}
+
+impl Origin {
+    /// How `setting` is named in this source.
+    fn name(&self, setting: &Setting) -> String {
+        match self {
+            Origin::Flags => format!("--{}", setting.flag.unwrap_or_default()),
+            Origin::Env => setting.env.to_string(),
+            Origin::File(path) => format!("{} in {}", setting.file, path.display()),
+        }
+    }
+}
+
+/// A setting's value as read, with the name it was read under.
+struct Raw {
+    key: &'static str,
+    value: String,
+    origin: String,
+}
+
+impl Raw {
+    fn invalid(self) -> ConfigError {
+        ConfigError::InvalidSetting {
+            key: self.key,
+            value: self.value,
+            origin: self.origin,
+        }
+    }
+
+    fn parse<T: FromStr>(self) -> Result<T, ConfigError> {
+        match self.value.parse() {
+            Ok(parsed) => Ok(parsed),
+            Err(_) => Err(self.invalid()),
         }
     }
+
+    /// Parses a count that must not be zero.
+    fn positive<T: FromStr + Default + PartialEq>(self) -> Result<T, ConfigError> {
+        match self.value.parse() {
+            Ok(parsed) if parsed != T::default() => Ok(parsed),
+            _ => Err(self.invalid()),
+        }
+    }
+
+    /// A path that must name an existing file.
+    fn file(self) -> Result<PathBuf, ConfigError> {
+        let path = PathBuf::from(self.value);
+        if path.is_file() {
+            Ok(path)
+        } else {
+            Err(ConfigError::MissingFile {
+                origin: self.origin,
+                path,
+            })
+        }
+    }
+}
+
+/// Layers of raw settings, highest precedence first.
+#[derive(Default)]
+struct Sources(Vec<(Origin, HashMap<&'static str, String>)>);
+
+impl Sources {
+    fn push(&mut self, origin: Origin, values: HashMap<&'static str, String>) {
+        self.0.push((origin, values));
+    }
+
+    /// The environment's values of every setting.
+    fn env(lookup: impl Fn(&str) -> Option<String>) -> HashMap<&'static str, String> {
+        SETTINGS
+            .iter()
+            .filter_map(|s| lookup(s.env).map(|value| (s.env, value)))
+            .collect()
+    }
+
+    fn get(&self, key: &'static str) -> Option<Raw> {
+        self.0.iter().find_map(|(origin, values)| {
+            values.get(key).map(|value| Raw {
+                key,
+                value: value.clone(),
+                origin: origin.name(setting(key)),
+            })
+        })
+    }
+}
+
+/// The command line: `--config` and a flag for every setting that has one.
+fn command() -> Command {
+    let flags = SETTINGS.iter().filter_map(|s| {
+        let help = format!("{} [env: {}] [file: {}]", s.help, s.env, s.file);
+        s.flag
+            .map(|flag| Arg::new(s.env).long(flag).value_name("VALUE").help(help))
+    });
+    Command::new("tee")
+        .about("Join-semilattice mediator between agents and the causal graph")
+        .version(env!("CARGO_PKG_VERSION"))
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("TOML config file [env: TEE_CONFIG]"),
+        )
+        .args(flags)
+}
+
+/// Reads a config file into the settings its keys name.
+fn read_file(path: &Path) -> Result<HashMap<&'static str, String>, ConfigError> {
+    let text = std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+        path: path.to_owned(),
+        source,
+    })?;
+    let table: toml::Table = text.parse().map_err(|source| ConfigError::Parse {
+        path: path.to_owned(),
+        source,
+    })?;
+    let mut values = HashMap::new();
+    flatten(path, "", &table, &mut values)?;
+    Ok(values)
+}
+
+fn flatten(
+    path: &Path,
+    prefix: &str,
+    table: &toml::Table,
+    values: &mut HashMap<&'static str, String>,
+) -> Result<(), ConfigError> {
+    for (name, value) in table {
+        let key = match prefix {
+            "" => name.clone(),
+            _ => format!("{prefix}.{name}"),
+        };
+        if let toml::Value::Table(inner) = value {
+            flatten(path, &key, inner, values)?;
+            continue;
+        }
+        let Some(setting) = SETTINGS.iter().find(|s| s.file == key) else {
+            return Err(ConfigError::UnknownSetting {
+                key,
+                path: path.to_owned(),
+            });
+        };
+        let text = match value {
+            toml::Value::String(text) => text.clone(),
+            toml::Value::Integer(_) | toml::Value::Float(_) | toml::Value::Boolean(_) => {
+                value.to_string()
+            }
+            _ => {
+                return Err(ConfigError::InvalidSetting {
+                    key: setting.env,
+                    value: value.to_string(),
+                    origin: Origin::File(path.to_owned()).name(setting),
+                })
+            }
+        };
+        values.insert(setting.env, text);
+    }
+    Ok(())
+}
+
+impl Config {
+    /// Reads the process's command line, environment and config file.
+    /// `--help`, `--version` and malformed flags come back as [`ConfigError::Cli`].
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::from_args(std::env::args_os(), |key| std::env::var(key).ok())
+    }
+
+    fn from_args<I, T>(args: I, env: impl Fn(&str) -> Option<String>) -> Result<Self, ConfigError>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let matches = command().try_get_matches_from(args)?;
+        Self::from_matches(&matches, env)
+    }
+
+    fn from_matches(
+        matches: &ArgMatches,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, ConfigError> {
+        let flags = SETTINGS
+            .iter()
+            .filter(|s| s.flag.is_some())
+            .filter_map(|s| matches.get_one::<String>(s.env).map(|v| (s.env, v.clone())))
+            .collect();
+        let mut sources = Sources::default();
+        sources.push(Origin::Flags, flags);
+        sources.push(Origin::Env, Sources::env(&env));
+        let file = matches
+            .get_one::<PathBuf>("config")
+            .cloned()
+            .or_else(|| env(CONFIG_ENV).map(PathBuf::from));
+        if let Some(path) = file {
+            let values = read_file(&path)?;
+            sources.push(Origin::File(path), values);
+        }
+        Self::from_sources(&sources)
+    }
+
+    #[cfg(test)]
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, ConfigError> {
+        let mut sources = Sources::default();
+        sources.push(Origin::Env, Sources::env(lookup));
+        Self::from_sources(&sources)
+    }
+
+    fn from_sources(sources: &Sources) -> Result<Self, ConfigError> {
+        let mut config = Self::default();
+        if let Some(raw) = sources.get("TEE_LISTEN_ADDR") {
+            config.listen_addr = raw.parse()?;
+        }
+        config.metrics_addr = sources
+            .get("TEE_METRICS_ADDR")
+            .map(Raw::parse)
+            .transpose()?;
+        match sources.get("TEE_STORE") {
+            None => config.store = StoreBackend::Memory(Self::memory(sources)?),
+            Some(raw) => match raw.value.as_str() {
+                "memory" => config.store = StoreBackend::Memory(Self::memory(sources)?),
+                "neo4j" => config.store = StoreBackend::Neo4j(Self::neo4j(sources)?),
+                _ => return Err(ConfigError::UnknownStoreBackend(raw.value)),
+            },
+        }
+        config.tls = Self::tls(sources)?;
+        config.auth_file = sources.get("TEE_AUTH_FILE").map(Raw::file).transpose()?;
+        if let Some(raw) = sources.get("TEE_MAX_MESSAGE_BYTES") {
+            config.limits.max_message_bytes = raw.positive()?;
+        }
+        if let Some(raw) = sources.get("TEE_REQUEST_TIMEOUT_SECS") {
+            config.limits.request_timeout = Some(Duration::from_secs(raw.positive()?));
+        }
+        if let Some(raw) = sources.get("TEE_CONCURRENCY_PER_CONNECTION") {
+            config.limits.concurrency_per_connection = Some(raw.positive()?);
+        }
+        if let Some(raw) = sources.get("TEE_LOG_FORMAT") {
+            config.log.format = match raw.value.as_str() {
+                "text" => LogFormat::Text,
+                "json" => LogFormat::Json,
+                _ => return Err(raw.invalid()),
+            };
+        }
+        if let Some(raw) = sources.get("TEE_LOG_FILTER") {
+            if tracing_subscriber::EnvFilter::try_new(&raw.value).is_err() {
+                return Err(raw.invalid());
+            }
+            config.log.filter = Some(raw.value);
+        }
+        config.schema_file = sources.get("TEE_SCHEMA_FILE").map(Raw::file).transpose()?;
+        config.types_file = sources.get("TEE_TYPES_FILE").map(Raw::file).transpose()?;
+        if let Some(raw) = sources.get("TEE_DANGLING_EDGES") {
+            config.dangling_edge_policy = match raw.value.as_str() {
+                "report" => DanglingEdgePolicy::Report,
+                "reject" => DanglingEdgePolicy::Reject,
+                "placeholder" => DanglingEdgePolicy::Placeholder,
+                _ => return Err(raw.invalid()),
+            };
+        }
+        Ok(config)
+    }
+
+    fn memory(sources: &Sources) -> Result<MemoryConfig, ConfigError> {
+        let mut memory = MemoryConfig {
+            data_dir: sources
+                .get("TEE_DATA_DIR")
+                .map(|raw| PathBuf::from(raw.value)),
+            ..MemoryConfig::default()
+        };
+        if let Some(raw) = sources.get("TEE_SNAPSHOT_EVERY") {
+            memory.snapshot_every = raw.parse()?;
+        }
+        Ok(memory)
+    }
+
+    fn neo4j(sources: &Sources) -> Result<Neo4jConfig, ConfigError> {
+        let require = |key: &'static str| {
+            sources
+                .get(key)
+                .map(|raw| raw.value)
+                .ok_or(ConfigError::MissingSetting(
+                    key,
+                    "when the neo4j backend is selected",
+                ))
+        };
+        Ok(Neo4jConfig {
+            uri: require("TEE_NEO4J_URI")?,
+            user: require("TEE_NEO4J_USER")?,
+            password: require("TEE_NEO4J_PASSWORD")?,
+            database: sources.get("TEE_NEO4J_DATABASE").map(|raw| raw.value),
+            max_connections: match sources.get("TEE_NEO4J_MAX_CONNECTIONS") {
+                Some(raw) => raw.positive()?,
+                None => Neo4jConfig::DEFAULT_MAX_CONNECTIONS,
+            },
+        })
+    }
+
+    fn tls(sources: &Sources) -> Result<Option<TlsConfig>, ConfigError> {
+        let client_ca = sources.get("TEE_TLS_CLIENT_CA_FILE");
+        let client_auth_optional = sources.get("TEE_TLS_CLIENT_AUTH_OPTIONAL");
+        let (cert, key) = match (
+            sources.get("TEE_TLS_CERT_FILE"),
+            sources.get("TEE_TLS_KEY_FILE"),
+        ) {
+            (Some(cert), Some(key)) => (cert, key),
+            (Some(_), None) => {
+                return Err(ConfigError::MissingSetting(
+                    "TEE_TLS_KEY_FILE",
+                    "along with TEE_TLS_CERT_FILE",
+                ))
+            }
+            (None, Some(_)) => {
+                return Err(ConfigError::MissingSetting(
+                    "TEE_TLS_CERT_FILE",
+                    "along with TEE_TLS_KEY_FILE",
+                ))
+            }
+            (None, None) if client_ca.is_some() => {
+                return Err(ConfigError::MissingSetting(
+                    "TEE_TLS_CERT_FILE",
+                    "to verify client certificates",
+                ))
+            }
+            (None, None) => return Ok(None),
+        };
+        if client_auth_optional.is_some() && client_ca.is_none() {
+            return Err(ConfigError::MissingSetting(
+                "TEE_TLS_CLIENT_CA_FILE",
+                "to make client certificates optional",
+            ));
+        }
+        Ok(Some(TlsConfig {
+            cert_file: cert.file()?,
+            key_file: key.file()?,
+            client_ca_file: client_ca.map(Raw::file).transpose()?,
+            client_auth_optional: match client_auth_optional {
+                Some(raw) => raw.parse()?,
+                None => false,
+            },
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |key| vars.get(key).cloned()
+    }
+
+    #[test]
+    fn defaults_to_memory() {
+        let config = Config::from_lookup(lookup(&[])).unwrap();
+        match config.store {
+            StoreBackend::Memory(memory) => assert!(memory.data_dir.is_none()),
+            other => panic!("expected memory, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn memory_durable_mode() {
+        let config = Config::from_lookup(lookup(&[
+            ("TEE_DATA_DIR", "/var/lib/tee"),
+            ("TEE_SNAPSHOT_EVERY", "50"),
+        ]))
+        .unwrap();
+        match config.store {
+            StoreBackend::Memory(memory) => {
+                assert_eq!(memory.data_dir, Some(PathBuf::from("/var/lib/tee")));
+                assert_eq!(memory.snapshot_every, 50);
+            }
+            other => panic!("expected memory, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn invalid_snapshot_interval_rejected() {
+        let result = Config::from_lookup(lookup(&[("TEE_SNAPSHOT_EVERY", "often")]));
+        assert!(matches!(
+            result,
+            Err(ConfigError::InvalidSetting {
+                key: "TEE_SNAPSHOT_EVERY",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn dangling_edge_policy() {
+        let config = Config::from_lookup(lookup(&[])).unwrap();
+        assert_eq!(config.dangling_edge_policy, DanglingEdgePolicy::Report);
+
+        let config = Config::from_lookup(lookup(&[("TEE_DANGLING_EDGES", "placeholder")])).unwrap();
+        assert_eq!(config.dangling_edge_policy, DanglingEdgePolicy::Placeholder);
+
+        let result = Config::from_lookup(lookup(&[("TEE_DANGLING_EDGES", "ignore")]));
+        assert!(matches!(
+            result,
+            Err(ConfigError::InvalidSetting {
+                key: "TEE_DANGLING_EDGES",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn schema_and_types_files() {
+        let config = Config::from_lookup(lookup(&[
+            ("TEE_SCHEMA_FILE", "config/causal_schema.toml"),
+            ("TEE_TYPES_FILE", "config/types.toml"),
+            ("TEE_AUTH_FILE", "config/principals.toml"),
+        ]))
+        .unwrap();
+        assert_eq!(
+            config.schema_file,
+            Some(PathBuf::from("config/causal_schema.toml"))
+        );
+        assert_eq!(config.types_file, Some(PathBuf::from("config/types.toml")));
+        assert_eq!(
+            config.auth_file,
+            Some(PathBuf::from("config/principals.toml"))
+        );
+    }
+
+    #[test]
+    fn selects_neo4j() {
+        let config = Config::from_lookup(lookup(&[
+            ("TEE_STORE", "neo4j"),
+            ("TEE_NEO4J_URI", "127.0.0.1:7687"),
+            ("TEE_NEO4J_USER", "neo4j"),
+            ("TEE_NEO4J_PASSWORD", "secret"),
+        ]))
+        .unwrap();
+        match config.store {
+            StoreBackend::Neo4j(neo4j) => assert_eq!(neo4j.uri, "127.0.0.1:7687"),
+            other => panic!("expected neo4j, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn neo4j_requires_settings() {
+        let result = Config::from_lookup(lookup(&[("TEE_STORE", "neo4j")]));
+        assert!(matches!(
+            result,
+            Err(ConfigError::MissingSetting("TEE_NEO4J_URI", _))
+        ));
+    }
+
+    #[test]
+    fn unknown_backend_rejected() {
+        let result = Config::from_lookup(lookup(&[("TEE_STORE", "postgres")]));
+        assert!(matches!(result, Err(ConfigError::UnknownStoreBackend(_))));
+    }
+
+    fn write_file(dir: &tempfile::TempDir, name: &str, text: &str) -> String {
+        let path = dir.path().join(name);
+        std::fs::write(&path, text).unwrap();
+        path.display().to_string()
+    }
+
+    fn from_args(args: &[&str], vars: &[(&str, &str)]) -> Result<Config, ConfigError> {
+        Config::from_args(["tee"].iter().chain(args), lookup(vars))
+    }
+
+    #[test]
+    fn flags_override_env_which_overrides_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = write_file(
+            &dir,
+            "tee.toml",
+            r#"
+            listen_addr = "127.0.0.1:1"
+            metrics_addr = "127.0.0.1:9100"
+
+            [store]
+            snapshot_every = 10
+
+            [store.neo4j]
+            uri = "bolt://db:7687"
+
+            [log]
+            format = "json"
+            "#,
+        );
+        let config = from_args(
+            &["--config", &file, "--listen-addr", "0.0.0.0:50051"],
+            &[("TEE_SNAPSHOT_EVERY", "20")],
+        )
+        .unwrap();
+        assert_eq!(config.listen_addr, "0.0.0.0:50051".parse().unwrap());
+        assert_eq!(config.metrics_addr, Some("127.0.0.1:9100".parse().unwrap()));
+        assert_eq!(config.log.format, LogFormat::Json);
+        match config.store {
+            StoreBackend::Memory(memory) => assert_eq!(memory.snapshot_every, 20),
+            other => panic!("expected memory, got {other:?}"),
+        }
+
+        // TEE_CONFIG names the file when --config doesn't
+        let config = from_args(
+            &["--store", "neo4j"],
+            &[
+                ("TEE_CONFIG", &file),
+                ("TEE_NEO4J_USER", "neo4j"),
+                ("TEE_NEO4J_PASSWORD", "secret"),
+            ],
+        )
+        .unwrap();
+        match config.store {
+            StoreBackend::Neo4j(neo4j) => {
+                assert_eq!(neo4j.uri, "bolt://db:7687");
+                assert_eq!(neo4j.max_connections, Neo4jConfig::DEFAULT_MAX_CONNECTIONS);
+            }
+            other => panic!("expected neo4j, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn unknown_file_settings_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = write_file(
+            &dir,
+            "tee.toml",
+            "[store]\nbackend = \"memory\"\ndir = \"x\"\n",
+        );
+        let result = from_args(&["--config", &file], &[]);
+        assert!(matches!(
+            result,
+            Err(ConfigError::UnknownSetting { key, .. }) if key == "store.dir"
+        ));
+    }
+
+    #[test]
+    fn invalid_values_name_their_source() {
+        let err = from_args(&["--max-message-bytes", "0"], &[]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid value \"0\" for --max-message-bytes"
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = write_file(&dir, "tee.toml", "[limits]\nrequest_timeout_secs = -1\n");
+        let err = from_args(&["--config", &file], &[]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("invalid value \"-1\" for limits.request_timeout_secs in {file}")
+        );
+
+        let err = from_args(&[], &[("TEE_LOG_FORMAT", "xml")]).unwrap_err();
+        assert_eq!(err.to_string(), "invalid value \"xml\" for TEE_LOG_FORMAT");
+
+        // Secrets have no flag
+        assert!(matches!(
+            from_args(&["--neo4j-password", "secret"], &[]),
+            Err(ConfigError::Cli(_))
+        ));
+    }
+
+    #[test]
+    fn tls_needs_an_existing_cert_and_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let cert = write_file(&dir, "tls.crt", "cert");
+        let key = write_file(&dir, "tls.key", "key");
+
+        let config = from_args(&["--tls-cert-file", &cert, "--tls-key-file", &key], &[]).unwrap();
+        let tls = config.tls.unwrap();
+        assert_eq!(tls.cert_file, PathBuf::from(&cert));
+        assert_eq!(tls.key_file, PathBuf::from(&key));
+
+        assert_eq!(tls.client_ca_file, None);
+
+        let result = from_args(&["--tls-cert-file", &cert], &[]);
+        assert!(matches!(
+            result,
+            Err(ConfigError::MissingSetting("TEE_TLS_KEY_FILE", _))
+        ));
+
+        let ca = write_file(&dir, "ca.pem", "ca");
+        let result = from_args(&["--tls-client-ca-file", &ca], &[]);
+        assert!(matches!(
+            result,
+            Err(ConfigError::MissingSetting("TEE_TLS_CERT_FILE", _))
+        ));
+        let config = from_args(
+            &[
+                "--tls-client-ca-file",
+                &ca,
+                "--tls-client-auth-optional",
+                "true",
+            ],
+            &[("TEE_TLS_CERT_FILE", &cert), ("TEE_TLS_KEY_FILE", &key)],
+        )
+        .unwrap();
+        let tls = config.tls.unwrap();
+        assert_eq!(tls.client_ca_file, Some(PathBuf::from(&ca)));
+        assert!(tls.client_auth_optional);
+
+        let result = from_args(
+            &[],
+            &[
+                ("TEE_TLS_CERT_FILE", &cert),
+                ("TEE_TLS_KEY_FILE", "gone.key"),
+            ],
+        );
+        assert!(matches!(result, Err(ConfigError::MissingFile { .. })));
+    }
 }
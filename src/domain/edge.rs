@@ -1,7 +1,7 @@
 use std::collections::BTreeSet;
 
 use lattices::set_union::SetUnionBTreeSet;
-use lattices::{IsBot, LatticeFrom, Merge};
+use lattices::{IsBot, LatticeFrom, Merge, Min};
 use serde::{Deserialize, Serialize};
 
 use super::edge_type::EdgeType;
@@ -26,23 +26,61 @@ impl EdgeKey {
 }
 
 /// Lattice-backed representation of a hypothesis edge's mutable properties.
-/// Identity is in `EdgeKey` (the map key). Only provenance is a lattice field.
-#[derive(Debug, Clone)]
+/// Identity is in `EdgeKey` (the map key). Provenance grows as a set union;
+/// `created_version` keeps the main-graph version of the first write (`Min<u64>`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EdgeLattice {
     pub provenance: SetUnionBTreeSet<Provenance>,
+    pub created_version: Min<u64>,
 }
 
 impl EdgeLattice {
     pub fn new(provenance: BTreeSet<Provenance>) -> Self {
         Self {
             provenance: SetUnionBTreeSet::new(provenance),
+            created_version: Min::new(0),
         }
     }
+
+    /// Stamps the main-graph version this lattice is being written at onto the
+    /// created version and each provenance entry.
+    pub fn at_version(mut self, version: u64) -> Self {
+        self.created_version = Min::new(version);
+        self.provenance = SetUnionBTreeSet::new(
+            self.provenance
+                .into_reveal()
+                .into_iter()
+                .map(|p| p.with_version(version))
+                .collect(),
+        );
+        self
+    }
+
+    /// The edge as it stood at main-graph `version`, or `None` if it did not exist yet.
+    pub fn as_of(&self, version: u64) -> Option<Self> {
+        if *self.created_version.as_reveal_ref() > version {
+            return None;
+        }
+        Some(Self {
+            provenance: SetUnionBTreeSet::new(
+                self.provenance
+                    .as_reveal_ref()
+                    .iter()
+                    .filter(|p| p.version <= version)
+                    .cloned()
+                    .collect(),
+            ),
+            created_version: self.created_version,
+        })
+    }
 }
 
 impl Merge<EdgeLattice> for EdgeLattice {
     fn merge(&mut self, other: EdgeLattice) -> bool {
-        self.provenance.merge(other.provenance)
+        let mut changed = false;
+        changed |= self.provenance.merge(other.provenance);
+        changed |= self.created_version.merge(other.created_version);
+        changed
     }
 }
 
@@ -116,4 +154,15 @@ mod tests {
         let e = EdgeLattice::new(BTreeSet::from([prov("a", "t")]));
         assert!(!e.is_bot());
     }
+
+    #[test]
+    fn as_of_drops_later_provenance() {
+        let mut e = EdgeLattice::new(BTreeSet::from([prov("a", "t1")])).at_version(2);
+        e.merge(EdgeLattice::new(BTreeSet::from([prov("b", "t2")])).at_version(5));
+
+        assert!(e.as_of(1).is_none());
+        let past = e.as_of(3).unwrap();
+        assert_eq!(past.provenance.as_reveal_ref().len(), 1);
+        assert_eq!(e.as_of(5).unwrap().provenance.as_reveal_ref().len(), 2);
+    }
 }
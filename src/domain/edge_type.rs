@@ -1,10 +1,29 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum EdgeType {
     DependsOn,
     PropagatesTo,
     ManifestsAs,
+    /// A kind defined by name in the type registry rather than built in.
+    Registered(String),
+}
+
+impl EdgeType {
+    /// The kinds every type registry starts with.
+    pub const BUILTIN: [EdgeType; 3] = [Self::DependsOn, Self::PropagatesTo, Self::ManifestsAs];
+
+    /// Resolves a kind by its `Display` name (e.g. `"DEPENDS_ON"`). Names that aren't
+    /// built in resolve to `Registered`; whether they are actually registered is checked
+    /// by the type registry at the API boundary.
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "DEPENDS_ON" => Self::DependsOn,
+            "PROPAGATES_TO" => Self::PropagatesTo,
+            "MANIFESTS_AS" => Self::ManifestsAs,
+            other => Self::Registered(other.to_string()),
+        }
+    }
 }
 
 impl std::fmt::Display for EdgeType {
@@ -13,6 +32,11 @@ impl std::fmt::Display for EdgeType {
             Self::DependsOn => write!(f, "DEPENDS_ON"),
             Self::PropagatesTo => write!(f, "PROPAGATES_TO"),
             Self::ManifestsAs => write!(f, "MANIFESTS_AS"),
+            Self::Registered(name) => f.write_str(name),
         }
     }
 }
+
+#[derive(Debug, thiserror::Error)]
+#[error("unknown edge type: {0:?}")]
+pub struct UnknownEdgeType(pub String);
@@ -4,3 +4,4 @@ pub mod graph;
 pub mod node;
 pub mod node_type;
 pub mod provenance;
+pub mod rationale;
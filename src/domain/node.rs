@@ -2,6 +2,7 @@ use std::collections::BTreeSet;
 
 use lattices::set_union::SetUnionBTreeSet;
 use lattices::{Conflict, IsBot, IsTop, LatticeFrom, Merge, Min};
+use serde::{Deserialize, Serialize};
 
 use super::node_type::NodeType;
 use super::provenance::Provenance;
@@ -13,18 +14,40 @@ use super::provenance::Provenance;
 /// Field merge semantics:
 /// - `node_type`: `Conflict<NodeType>` — first-write-wins, `is_top()` = conflict detected
 /// - `label`: `Conflict<String>` — first-write-wins, `is_top()` = conflict detected
+///
+///   A `Placeholder` node sits below every real write: the first non-placeholder
+///   merge replaces both its type and label instead of conflicting with them.
 /// - `hypothetical`: `Min<bool>` — once false (confirmed), stays false
 /// - `provenance`: `SetUnion<BTreeSet<Provenance>>` — append-only, dedup by `(source, trigger)`
+/// - `created_version`: `Min<u64>` — main-graph version of the first write; versions only
+///   increase, so the minimum is the version at which the node entered the graph
+/// - `confirmed_version`: `Min<u64>` — main-graph version at which `hypothetical` became
+///   false; `u64::MAX` while the node is still hypothetical
+/// - `refined`: the placeholder a real write replaced, and that write's version; `None`
+///   unless the node started as a placeholder and has since been written for real
 ///
 /// Note: The README describes `hypothetical` as `Max<bool>`, but the intended semantics
 /// ("once false, stays false") are AND/Min. `Min<bool>::default()` = `true` (new nodes
 /// start hypothetical). `Min::merge` keeps the minimum: `merge(true, false) → false`.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NodeLattice {
     pub node_type: Conflict<NodeType>,
     pub label: Conflict<String>,
     pub hypothetical: Min<bool>,
     pub provenance: SetUnionBTreeSet<Provenance>,
+    pub created_version: Min<u64>,
+    pub confirmed_version: Min<u64>,
+    pub refined: Option<Refinement>,
+}
+
+/// A placeholder node's first real write, kept so reads at earlier versions still see
+/// the placeholder.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Refinement {
+    /// Main-graph version of the real write.
+    pub version: u64,
+    /// The placeholder's label (its id).
+    pub placeholder_label: String,
 }
 
 impl NodeLattice {
@@ -39,7 +62,80 @@ impl NodeLattice {
             label: Conflict::new_from(label),
             hypothetical: Min::new(hypothetical),
             provenance: SetUnionBTreeSet::new(provenance),
+            created_version: Min::new(0),
+            confirmed_version: Min::new(if hypothetical { u64::MAX } else { 0 }),
+            refined: None,
+        }
+    }
+
+    /// A hypothetical stand-in for the endpoint `id` of a dangling edge, labelled with
+    /// the id and carrying the provenance of the edge that referenced it.
+    pub fn placeholder(id: &str, provenance: BTreeSet<Provenance>) -> Self {
+        Self::new(NodeType::Placeholder, id.to_string(), true, provenance)
+    }
+
+    pub fn is_placeholder(&self) -> bool {
+        self.node_type.as_reveal_ref() == Some(&NodeType::Placeholder)
+    }
+
+    /// Stamps the main-graph version this lattice is being written at onto the
+    /// created version, each provenance entry and, if not hypothetical, the confirmation.
+    pub fn at_version(mut self, version: u64) -> Self {
+        self.created_version = Min::new(version);
+        if !*self.hypothetical.as_reveal_ref() {
+            self.confirmed_version = Min::new(version);
         }
+        self.provenance = SetUnionBTreeSet::new(
+            self.provenance
+                .into_reveal()
+                .into_iter()
+                .map(|p| p.with_version(version))
+                .collect(),
+        );
+        self
+    }
+
+    /// Confirms the node at main-graph `version` on behalf of `prov`: `hypothetical`
+    /// merges to false and `prov` is appended. Returns whether anything changed.
+    pub fn confirm(&mut self, prov: Provenance, version: u64) -> bool {
+        let mut changed = self.hypothetical.merge(Min::new(false));
+        changed |= self.confirmed_version.merge(Min::new(version));
+        changed |= self
+            .provenance
+            .merge(SetUnionBTreeSet::new(BTreeSet::from([prov.with_version(version)])));
+        changed
+    }
+
+    /// The node as it stood at main-graph `version`, or `None` if it did not exist yet.
+    pub fn as_of(&self, version: u64) -> Option<Self> {
+        if *self.created_version.as_reveal_ref() > version {
+            return None;
+        }
+        let confirmed = *self.confirmed_version.as_reveal_ref() <= version;
+        let (node_type, label, refined) = match &self.refined {
+            Some(refined) if refined.version > version => (
+                Conflict::new_from(NodeType::Placeholder),
+                Conflict::new_from(refined.placeholder_label.clone()),
+                None,
+            ),
+            refined => (self.node_type.clone(), self.label.clone(), refined.clone()),
+        };
+        Some(Self {
+            node_type,
+            label,
+            hypothetical: Min::new(!confirmed),
+            provenance: SetUnionBTreeSet::new(
+                self.provenance
+                    .as_reveal_ref()
+                    .iter()
+                    .filter(|p| p.version <= version)
+                    .cloned()
+                    .collect(),
+            ),
+            created_version: self.created_version,
+            confirmed_version: self.confirmed_version,
+            refined,
+        })
     }
 
     /// Returns true if either structural field (type or label) is in conflict state.
@@ -48,26 +144,65 @@ impl NodeLattice {
         self.node_type.is_top() || self.label.is_top()
     }
 
-    /// Returns the conflict details if any structural field is conflicted.
+    /// Returns every structural field that is conflicted, `"type"` before `"label"`.
     /// Used to build `MergeConflict` proto responses.
-    pub fn conflict_field(&self) -> Option<&'static str> {
+    pub fn conflict_fields(&self) -> Vec<&'static str> {
+        let mut fields = Vec::new();
         if self.node_type.is_top() {
-            Some("type")
-        } else if self.label.is_top() {
-            Some("label")
-        } else {
-            None
+            fields.push("type");
+        }
+        if self.label.is_top() {
+            fields.push("label");
+        }
+        fields
+    }
+
+    /// The value of a structural field as a string, or empty if it is conflicted.
+    pub fn field_value(&self, field: &str) -> String {
+        match field {
+            "type" => self
+                .node_type
+                .as_reveal_ref()
+                .map(|t| t.to_string())
+                .unwrap_or_default(),
+            "label" => self.label.as_reveal_ref().cloned().unwrap_or_default(),
+            _ => String::new(),
         }
     }
+
+    /// Provenance entries recorded by the write that created the node.
+    pub fn first_writer_provenance(&self) -> impl Iterator<Item = &Provenance> {
+        let created = *self.created_version.as_reveal_ref();
+        self.provenance
+            .as_reveal_ref()
+            .iter()
+            .filter(move |p| p.version <= created)
+    }
 }
 
 impl Merge<NodeLattice> for NodeLattice {
     fn merge(&mut self, other: NodeLattice) -> bool {
         let mut changed = false;
-        changed |= self.node_type.merge(other.node_type);
-        changed |= self.label.merge(other.label);
+        match (self.is_placeholder(), other.is_placeholder()) {
+            (true, false) => {
+                self.refined = Some(Refinement {
+                    version: *other.created_version.as_reveal_ref(),
+                    placeholder_label: self.label.as_reveal_ref().cloned().unwrap_or_default(),
+                });
+                self.node_type = other.node_type;
+                self.label = other.label;
+                changed = true;
+            }
+            (false, true) => {}
+            _ => {
+                changed |= self.node_type.merge(other.node_type);
+                changed |= self.label.merge(other.label);
+            }
+        }
         changed |= self.hypothetical.merge(other.hypothetical);
         changed |= self.provenance.merge(other.provenance);
+        changed |= self.created_version.merge(other.created_version);
+        changed |= self.confirmed_version.merge(other.confirmed_version);
         changed
     }
 }
@@ -160,7 +295,7 @@ mod tests {
         a.merge(b);
         assert!(a.has_conflict());
         assert!(a.node_type.is_top());
-        assert_eq!(a.conflict_field(), Some("type"));
+        assert_eq!(a.conflict_fields(), vec!["type"]);
     }
 
     #[test]
@@ -180,7 +315,7 @@ mod tests {
         a.merge(b);
         assert!(a.has_conflict());
         assert!(a.label.is_top());
-        assert_eq!(a.conflict_field(), Some("label"));
+        assert_eq!(a.conflict_fields(), vec!["label"]);
     }
 
     #[test]
@@ -202,6 +337,103 @@ mod tests {
         assert_eq!(a.provenance.as_reveal_ref().len(), 2);
     }
 
+    #[test]
+    fn type_and_label_conflicts_both_reported() {
+        let mut a = NodeLattice::new(
+            NodeType::Service,
+            "api-gw".into(),
+            true,
+            prov_set(&[("a", "t")]),
+        );
+        let b = NodeLattice::new(
+            NodeType::Infrastructure,
+            "api-gateway".into(),
+            true,
+            prov_set(&[("a", "t")]),
+        );
+        a.merge(b);
+        assert_eq!(a.conflict_fields(), vec!["type", "label"]);
+        assert_eq!(a.field_value("type"), "");
+    }
+
+    #[test]
+    fn first_writer_provenance_excludes_later_writes() {
+        let mut a = NodeLattice::new(
+            NodeType::Service,
+            "svc".into(),
+            true,
+            prov_set(&[("agent-1", "alert")]),
+        )
+        .at_version(1);
+        let b = NodeLattice::new(
+            NodeType::Service,
+            "svc".into(),
+            true,
+            prov_set(&[("agent-2", "log-scan")]),
+        )
+        .at_version(3);
+        a.merge(b);
+        let first: Vec<_> = a.first_writer_provenance().map(|p| p.source.as_str()).collect();
+        assert_eq!(first, vec!["agent-1"]);
+        assert_eq!(a.field_value("type"), "SERVICE");
+        assert_eq!(a.field_value("label"), "svc");
+    }
+
+    // --- Placeholders ---
+
+    #[test]
+    fn real_write_replaces_placeholder() {
+        let mut a = NodeLattice::placeholder("db", prov_set(&[("a", "edge")]));
+        let b = NodeLattice::new(
+            NodeType::Infrastructure,
+            "postgres".into(),
+            true,
+            prov_set(&[("b", "t")]),
+        );
+        assert!(a.merge(b));
+        assert!(!a.has_conflict());
+        assert!(!a.is_placeholder());
+        assert_eq!(a.field_value("type"), "INFRASTRUCTURE");
+        assert_eq!(a.field_value("label"), "postgres");
+        assert_eq!(a.provenance.as_reveal_ref().len(), 2);
+    }
+
+    #[test]
+    fn reads_before_the_real_write_still_see_the_placeholder() {
+        let mut a = NodeLattice::placeholder("db", prov_set(&[("a", "edge")])).at_version(1);
+        let b = NodeLattice::new(
+            NodeType::Infrastructure,
+            "postgres".into(),
+            true,
+            prov_set(&[("b", "t")]),
+        )
+        .at_version(3);
+        a.merge(b);
+
+        let before = a.as_of(2).unwrap();
+        assert!(before.is_placeholder());
+        assert_eq!(before.field_value("label"), "db");
+        assert_eq!(before.provenance.as_reveal_ref().len(), 1);
+        let after = a.as_of(3).unwrap();
+        assert_eq!(after.field_value("type"), "INFRASTRUCTURE");
+        assert_eq!(after.field_value("label"), "postgres");
+        assert_eq!(*a.created_version.as_reveal_ref(), 1);
+    }
+
+    #[test]
+    fn placeholder_does_not_overwrite_real_node() {
+        let mut a = NodeLattice::new(
+            NodeType::Service,
+            "api".into(),
+            true,
+            prov_set(&[("a", "t")]),
+        );
+        let b = NodeLattice::placeholder("api-id", prov_set(&[("a", "t")]));
+        assert!(!a.merge(b));
+        assert!(!a.has_conflict());
+        assert_eq!(a.field_value("label"), "api");
+    }
+
     // --- Hypothetical monotonicity (Min<bool>) ---
 
     #[test]
@@ -240,6 +472,27 @@ mod tests {
         assert!(!*a.hypothetical.as_reveal_ref());
     }
 
+    #[test]
+    fn confirm_is_monotone_and_keeps_the_first_version() {
+        let mut n = NodeLattice::new(
+            NodeType::Service,
+            "svc".into(),
+            true,
+            prov_set(&[("a", "t")]),
+        )
+        .at_version(1);
+        assert!(n.confirm(prov("b", "review"), 3));
+        assert!(!*n.hypothetical.as_reveal_ref());
+        assert_eq!(*n.confirmed_version.as_reveal_ref(), 3);
+        assert!(*n.as_of(2).unwrap().hypothetical.as_reveal_ref());
+
+        // Same writer again: no-op; a new writer only appends provenance
+        assert!(!n.confirm(prov("b", "review"), 4));
+        assert!(n.confirm(prov("c", "review"), 5));
+        assert_eq!(*n.confirmed_version.as_reveal_ref(), 3);
+        assert_eq!(n.provenance.as_reveal_ref().len(), 3);
+    }
+
     #[test]
     fn hypothetical_default_is_true() {
         let h = Min::<bool>::default();
@@ -284,6 +537,74 @@ mod tests {
         assert_eq!(a.provenance.as_reveal_ref().len(), 1);
     }
 
+    // --- Created version (Min<u64>) ---
+
+    #[test]
+    fn created_version_keeps_first_write() {
+        let mut a = NodeLattice::new(
+            NodeType::Service,
+            "svc".into(),
+            true,
+            prov_set(&[("a", "t")]),
+        )
+        .at_version(3);
+        let b = NodeLattice::new(
+            NodeType::Service,
+            "svc".into(),
+            true,
+            prov_set(&[("a", "t")]),
+        )
+        .at_version(7);
+        assert!(
+            !a.merge(b),
+            "a later version must not move the created version"
+        );
+        assert_eq!(*a.created_version.as_reveal_ref(), 3);
+    }
+
+    #[test]
+    fn at_version_stamps_provenance() {
+        let n = NodeLattice::new(
+            NodeType::Service,
+            "svc".into(),
+            true,
+            prov_set(&[("a", "t1"), ("b", "t2")]),
+        )
+        .at_version(4);
+        assert!(n.provenance.as_reveal_ref().iter().all(|p| p.version == 4));
+        assert_eq!(*n.confirmed_version.as_reveal_ref(), u64::MAX);
+    }
+
+    #[test]
+    fn as_of_reconstructs_past_state() {
+        let mut n = NodeLattice::new(
+            NodeType::Service,
+            "svc".into(),
+            true,
+            prov_set(&[("a", "t1")]),
+        )
+        .at_version(2);
+        n.merge(
+            NodeLattice::new(
+                NodeType::Service,
+                "svc".into(),
+                false,
+                prov_set(&[("b", "t2")]),
+            )
+            .at_version(5),
+        );
+
+        assert!(n.as_of(1).is_none());
+
+        let past = n.as_of(3).unwrap();
+        assert!(*past.hypothetical.as_reveal_ref());
+        assert_eq!(past.provenance.as_reveal_ref().len(), 1);
+
+        let head = n.as_of(5).unwrap();
+        assert!(!*head.hypothetical.as_reveal_ref());
+        assert_eq!(head.provenance.as_reveal_ref().len(), 2);
+    }
+
     // --- IsBot ---
 
     #[test]
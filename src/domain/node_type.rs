@@ -1,11 +1,40 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum NodeType {
     Service,
     Dependency,
     Infrastructure,
     Mechanism,
+    /// Stand-in created for an edge endpoint that no node has described yet.
+    /// The first real write of the node replaces it.
+    Placeholder,
+    /// A kind defined by name in the type registry rather than built in.
+    Registered(String),
+}
+
+impl NodeType {
+    /// The kinds every type registry starts with.
+    pub const BUILTIN: [NodeType; 4] = [
+        Self::Service,
+        Self::Dependency,
+        Self::Infrastructure,
+        Self::Mechanism,
+    ];
+
+    /// Resolves a kind by its `Display` name (e.g. `"SERVICE"`). Names that aren't built
+    /// in resolve to `Registered`; whether they are actually registered is checked by
+    /// the type registry at the API boundary.
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "SERVICE" => Self::Service,
+            "DEPENDENCY" => Self::Dependency,
+            "INFRASTRUCTURE" => Self::Infrastructure,
+            "MECHANISM" => Self::Mechanism,
+            "PLACEHOLDER" => Self::Placeholder,
+            other => Self::Registered(other.to_string()),
+        }
+    }
 }
 
 impl std::fmt::Display for NodeType {
@@ -15,6 +44,12 @@ impl std::fmt::Display for NodeType {
             Self::Dependency => write!(f, "DEPENDENCY"),
             Self::Infrastructure => write!(f, "INFRASTRUCTURE"),
             Self::Mechanism => write!(f, "MECHANISM"),
+            Self::Placeholder => write!(f, "PLACEHOLDER"),
+            Self::Registered(name) => f.write_str(name),
         }
     }
 }
+
+#[derive(Debug, thiserror::Error)]
+#[error("unknown node type: {0:?}")]
+pub struct UnknownNodeType(pub String);
@@ -4,8 +4,8 @@ use serde::{Deserialize, Serialize};
 
 /// A provenance record tracking who triggered an operation and when.
 ///
-/// Identity is `(source, trigger)` only — the timestamp is informational metadata
-/// excluded from equality, ordering, and hashing. This means:
+/// Identity is `(source, trigger)` only — the timestamp and version are informational
+/// metadata excluded from equality, ordering, and hashing. This means:
 /// - A `BTreeSet<Provenance>` deduplicates by `(source, trigger)`
 /// - `BTreeSet::insert` keeps the existing entry on collision (first-write-wins on timestamp)
 /// - `SetUnion::merge` uses `Extend`, which calls `insert`, preserving first-write-wins
@@ -17,6 +17,9 @@ pub struct Provenance {
     /// Stored as (seconds, nanos) from epoch to avoid prost_types dependency in domain layer.
     pub timestamp_seconds: i64,
     pub timestamp_nanos: i32,
+    /// Main-graph version at which this entry was recorded. Informational only.
+    #[serde(default)]
+    pub version: u64,
 }
 
 impl Provenance {
@@ -26,6 +29,7 @@ impl Provenance {
             trigger: trigger.into(),
             timestamp_seconds: 0,
             timestamp_nanos: 0,
+            version: 0,
         }
     }
 
@@ -34,9 +38,14 @@ impl Provenance {
         self.timestamp_nanos = nanos;
         self
     }
+
+    pub fn with_version(mut self, version: u64) -> Self {
+        self.version = version;
+        self
+    }
 }
 
-// Identity is (source, trigger) only — timestamp excluded.
+// Identity is (source, trigger) only — timestamp and version excluded.
 
 impl PartialEq for Provenance {
     fn eq(&self, other: &Self) -> bool {
@@ -76,6 +85,13 @@ mod tests {
         assert_eq!(a, b);
     }
 
+    #[test]
+    fn equality_ignores_version() {
+        let a = Provenance::new("agent-1", "alert").with_version(1);
+        let b = Provenance::new("agent-1", "alert").with_version(2);
+        assert_eq!(a, b);
+    }
+
     #[test]
     fn different_source_not_equal() {
         let a = Provenance::new("agent-1", "alert");
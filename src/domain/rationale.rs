@@ -0,0 +1,88 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::provenance::Provenance;
+
+/// Why a writer eliminated a hypothesis: free text, evidence URIs and attributes.
+///
+/// Identity is the writer's `(source, trigger)`, as for [`Provenance`] — the content
+/// is excluded from equality, ordering, and hashing. A `BTreeSet<Rationale>` therefore
+/// keeps the first rationale each writer attached.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Rationale {
+    pub source: String,
+    pub trigger: String,
+    pub text: String,
+    pub evidence_uris: Vec<String>,
+    pub attributes: BTreeMap<String, String>,
+}
+
+impl Rationale {
+    /// Attributes this rationale to the writer `prov` names.
+    pub fn by(mut self, prov: &Provenance) -> Self {
+        self.source = prov.source.clone();
+        self.trigger = prov.trigger.clone();
+        self
+    }
+}
+
+// Identity is (source, trigger) only — content excluded.
+
+impl PartialEq for Rationale {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source && self.trigger == other.trigger
+    }
+}
+impl Eq for Rationale {}
+
+impl PartialOrd for Rationale {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Rationale {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.source, &self.trigger).cmp(&(&other.source, &other.trigger))
+    }
+}
+
+impl std::hash::Hash for Rationale {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.source.hash(state);
+        self.trigger.hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    fn rationale(source: &str, text: &str) -> Rationale {
+        Rationale {
+            text: text.into(),
+            ..Default::default()
+        }
+        .by(&Provenance::new(source, "elim"))
+    }
+
+    #[test]
+    fn equality_ignores_content() {
+        assert_eq!(rationale("agent-1", "logs"), rationale("agent-1", "metrics"));
+        assert_ne!(rationale("agent-1", "logs"), rationale("agent-2", "logs"));
+    }
+
+    #[test]
+    fn btreeset_keeps_first_rationale_per_writer() {
+        let mut set = BTreeSet::new();
+        set.insert(rationale("agent-1", "logs"));
+        set.insert(rationale("agent-1", "metrics"));
+        set.insert(rationale("agent-2", "traces"));
+
+        let texts: Vec<_> = set.iter().map(|r| r.text.as_str()).collect();
+        assert_eq!(texts, ["logs", "traces"]);
+    }
+}
@@ -0,0 +1,218 @@
+//! gRPC health checking (`grpc.health.v1`) for the Tee service.
+//!
+//! The server answers health checks before its store is open: while a durable store
+//! replays its log or the Neo4j connection is made, `tee.Tee` reports NOT_SERVING and
+//! its RPCs fail with UNAVAILABLE behind a [`Deferred`] slot. Once the store is open,
+//! [`monitor`] probes it periodically and reports NOT_SERVING whenever it fails.
+
+use std::convert::Infallible;
+use std::future::Future;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use tonic::body::Body;
+use tonic::codegen::{http, BoxFuture, Context, Poll, Service};
+use tonic::server::NamedService;
+use tonic::Status;
+use tonic_health::server::HealthReporter;
+use tonic_health::ServingStatus;
+
+use crate::store::StoreError;
+
+/// A service that is added to the server before it exists, answering UNAVAILABLE
+/// until [`Deferred::set`] supplies it.
+pub struct Deferred<S> {
+    inner: Arc<OnceLock<S>>,
+}
+
+impl<S> Deferred<S> {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(OnceLock::new()),
+        }
+    }
+
+    /// Starts routing requests to `service`. Panics if a service was already set.
+    pub fn set(&self, service: S) {
+        if self.inner.set(service).is_err() {
+            panic!("deferred service set twice");
+        }
+    }
+}
+
+impl<S> Default for Deferred<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Clone for Deferred<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<S: NamedService> NamedService for Deferred<S> {
+    const NAME: &'static str = S::NAME;
+}
+
+impl<S> Service<http::Request<Body>> for Deferred<S>
+where
+    S: Service<http::Request<Body>, Response = http::Response<Body>, Error = Infallible>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = http::Response<Body>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+        let Some(service) = self.inner.get() else {
+            return Box::pin(async {
+                Ok(Status::unavailable("the store is not open yet").into_http())
+            });
+        };
+        let mut service = service.clone();
+        Box::pin(async move {
+            std::future::poll_fn(|cx| service.poll_ready(cx)).await?;
+            service.call(request).await
+        })
+    }
+}
+
+/// Sets the status of `service` and of the server as a whole (the empty name).
+pub async fn report(reporter: &HealthReporter, service: &str, status: ServingStatus) {
+    reporter.set_service_status("", status).await;
+    reporter.set_service_status(service, status).await;
+}
+
+/// Runs `check` every `interval`, reporting `service` SERVING while it succeeds and
+/// NOT_SERVING while it fails. Changes of status are logged.
+pub async fn monitor<F, Fut>(
+    reporter: HealthReporter,
+    service: &'static str,
+    interval: Duration,
+    mut check: F,
+) where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<(), StoreError>>,
+{
+    let mut serving = true;
+    report(&reporter, service, ServingStatus::Serving).await;
+    let mut ticks = tokio::time::interval(interval);
+    ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticks.tick().await;
+        match check().await {
+            Ok(()) if !serving => {
+                tracing::info!("store is reachable again, serving {service}");
+                report(&reporter, service, ServingStatus::Serving).await;
+                serving = true;
+            }
+            Err(err) if serving => {
+                tracing::warn!("store health check failed, not serving {service}: {err}");
+                report(&reporter, service, ServingStatus::NotServing).await;
+                serving = false;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Server;
+    use tonic::Request;
+    use tonic_health::pb::health_check_response::ServingStatus as Reported;
+    use tonic_health::pb::health_server::Health;
+    use tonic_health::pb::HealthCheckRequest;
+    use tonic_health::server::HealthService;
+
+    use super::*;
+    use crate::proto::tee_client::TeeClient;
+    use crate::proto::tee_server::TeeServer;
+    use crate::proto::TombstoneRequest;
+    use crate::service::TeeService;
+    use crate::store::memory::InMemoryStore;
+
+    async fn status(reporter: &HealthReporter, service: &str) -> Reported {
+        let response = HealthService::from_health_reporter(reporter.clone())
+            .check(Request::new(HealthCheckRequest {
+                service: service.into(),
+            }))
+            .await
+            .unwrap();
+        response.into_inner().status()
+    }
+
+    #[tokio::test]
+    async fn deferred_service_is_unavailable_until_set() {
+        let tee = Deferred::new();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            Server::builder()
+                .add_service(tee.clone())
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+        let mut client = TeeClient::connect(format!("http://{addr}")).await.unwrap();
+        let request = || TombstoneRequest {
+            incident_id: "inc-1".into(),
+        };
+
+        let status = client.get_tombstones(request()).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unavailable);
+
+        tee.set(TeeServer::new(TeeService::new(Arc::new(
+            InMemoryStore::new(),
+        ))));
+        let status = client.get_tombstones(request()).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn monitor_follows_the_store() {
+        let reporter = HealthReporter::new();
+        report(&reporter, "tee.Tee", ServingStatus::NotServing).await;
+        assert_eq!(status(&reporter, "").await, Reported::NotServing);
+
+        let healthy = Arc::new(AtomicBool::new(true));
+        let probe = healthy.clone();
+        tokio::spawn(monitor(
+            reporter.clone(),
+            "tee.Tee",
+            Duration::from_secs(5),
+            move || {
+                let healthy = probe.load(Ordering::SeqCst);
+                async move {
+                    match healthy {
+                        true => Ok(()),
+                        false => Err(StoreError::Backend("connection refused".into())),
+                    }
+                }
+            },
+        ));
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(status(&reporter, "tee.Tee").await, Reported::Serving);
+
+        healthy.store(false, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        assert_eq!(status(&reporter, "tee.Tee").await, Reported::NotServing);
+        assert_eq!(status(&reporter, "").await, Reported::NotServing);
+
+        healthy.store(true, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        assert_eq!(status(&reporter, "tee.Tee").await, Reported::Serving);
+    }
+}
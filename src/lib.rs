@@ -1,10 +1,20 @@
+pub mod auth;
 pub mod config;
 pub mod domain;
+pub mod health;
+pub mod metrics;
+pub mod paths;
 pub mod proto_convert;
+pub mod root_cause;
 pub mod schema;
 pub mod service;
 pub mod store;
+pub mod tls;
+pub mod watch;
 
 pub mod proto {
     tonic::include_proto!("tee");
+
+    /// Encoded descriptors of `tee.proto`, for server reflection.
+    pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("tee_descriptor");
 }
@@ -1,28 +1,193 @@
+use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 
+use tokio::net::TcpListener;
+use tonic::service::interceptor::InterceptedService;
 use tonic::transport::Server;
+use tonic_health::ServingStatus;
 use tracing_subscriber::EnvFilter;
 
-use tee::config::Config;
-use tee::proto::tee_server::TeeServer;
+use tee::auth::Principals;
+use tee::config::{Config, ConfigError, LogFormat, StoreBackend};
+use tee::health::{self, Deferred};
+use tee::metrics::{self, Metrics, Observed, OpenedStore};
+use tee::proto::tee_server::{self, TeeServer};
+use tee::schema::causal::CausalSchema;
+use tee::schema::registry::TypeRegistry;
 use tee::service::TeeService;
 use tee::store::memory::InMemoryStore;
+use tee::store::neo4j::Neo4jStore;
+use tee::store::{Store, StoreError};
+use tee::tls;
+
+/// How often an open store is probed for the health service.
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
-        .init();
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(ConfigError::Cli(err)) => err.exit(),
+        Err(err) => {
+            eprintln!("tee: {err}");
+            std::process::exit(2);
+        }
+    };
+
+    let filter = match &config.log.filter {
+        Some(directives) => EnvFilter::new(directives),
+        None => EnvFilter::from_default_env(),
+    };
+    let logs = tracing_subscriber::fmt().with_env_filter(filter);
+    match config.log.format {
+        LogFormat::Text => logs.init(),
+        LogFormat::Json => logs.json().init(),
+    }
+
+    match &config.store {
+        StoreBackend::Memory(memory) => match &memory.data_dir {
+            None => {
+                tracing::info!("using in-memory store");
+                serve(&config, async { Ok(InMemoryStore::new()) }).await
+            }
+            Some(dir) => {
+                tracing::info!("using durable in-memory store at {}", dir.display());
+                let (dir, snapshot_every) = (dir.clone(), memory.snapshot_every);
+                // Recovery replays the log synchronously; keep it off the serving task
+                let recover =
+                    tokio::task::spawn_blocking(move || InMemoryStore::open(dir, snapshot_every));
+                serve(&config, async {
+                    recover.await.expect("store recovery panicked")
+                })
+                .await
+            }
+        },
+        StoreBackend::Neo4j(neo4j) => {
+            tracing::info!("using neo4j store at {}", neo4j.uri);
+            serve(&config, Neo4jStore::connect(neo4j)).await
+        }
+    }
+}
 
-    let config = Config::default();
-    let store = Arc::new(InMemoryStore::new());
+/// Serves health checks and reflection at once, and the Tee service as soon as `open`
+/// yields the store. Until then `tee.Tee` reports NOT_SERVING.
+async fn serve<S: Store + 'static>(
+    config: &Config,
+    open: impl Future<Output = Result<S, StoreError>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let registry = match &config.types_file {
+        Some(path) => {
+            tracing::info!("loading type registry from {}", path.display());
+            TypeRegistry::load(path)?
+        }
+        None => TypeRegistry::default(),
+    };
+    let schema = match &config.schema_file {
+        Some(path) => {
+            tracing::info!("enforcing causal schema from {}", path.display());
+            Some(CausalSchema::load(path, &registry)?)
+        }
+        None => None,
+    };
+    let principals = match &config.auth_file {
+        Some(path) => {
+            tracing::info!("authenticating callers against {}", path.display());
+            Some(Principals::load(path)?)
+        }
+        None => None,
+    };
+
+    let limits = &config.limits;
+    let mut server = Server::builder();
+    if let Some(tls) = &config.tls {
+        tracing::info!("serving TLS with {}", tls.cert_file.display());
+        if let Some(path) = &tls.client_ca_file {
+            tracing::info!("verifying client certificates against {}", path.display());
+        }
+        server = server.tls_config(tls::server_config(tls)?)?;
+    }
+    if let Some(limit) = limits.concurrency_per_connection {
+        server = server.concurrency_limit_per_connection(limit);
+    }
+    if let Some(timeout) = limits.request_timeout {
+        server = server.timeout(timeout);
+    }
+
+    let metrics = Metrics::new();
+    let metrics_listener = match config.metrics_addr {
+        Some(addr) => Some(TcpListener::bind(addr).await?),
+        None => None,
+    };
+
+    let (reporter, health_service) = tonic_health::server::health_reporter();
+    health::report(
+        &reporter,
+        tee_server::SERVICE_NAME,
+        ServingStatus::NotServing,
+    )
+    .await;
+    let reflection = || {
+        tonic_reflection::server::Builder::configure()
+            .register_encoded_file_descriptor_set(tee::proto::FILE_DESCRIPTOR_SET)
+    };
+    let tee = Deferred::new();
 
     tracing::info!("Tee server listening on {}", config.listen_addr);
+    let server = server
+        .add_service(health_service)
+        .add_service(reflection().build_v1()?)
+        .add_service(reflection().build_v1alpha()?)
+        .add_service(Observed::new(
+            InterceptedService::new(tee.clone(), tls::attach_client_identity),
+            metrics.clone(),
+        ))
+        .serve(config.listen_addr);
+
+    let opened = OpenedStore::default();
+    let serve_metrics = async {
+        if let Some(listener) = metrics_listener {
+            tracing::info!(
+                "serving metrics on http://{}/metrics",
+                listener.local_addr()?
+            );
+            metrics::serve(listener, metrics.clone(), opened.clone()).await?;
+        }
+        Ok::<_, Box<dyn std::error::Error>>(())
+    };
 
-    Server::builder()
-        .add_service(TeeServer::new(TeeService::new(store)))
-        .serve(config.listen_addr)
-        .await?;
+    let start = async {
+        let store = Arc::new(open.await?);
+        opened.get_or_init(|| store.clone());
+        let mut service = TeeService::new(store.clone())
+            .with_dangling_edge_policy(config.dangling_edge_policy)
+            .with_registry(registry)
+            .with_metrics(metrics.clone());
+        if let Some(schema) = schema {
+            service = service.with_schema(schema);
+        }
+        if let Some(principals) = principals {
+            service = service.with_principals(principals);
+        }
+        tee.set(
+            TeeServer::new(service)
+                .max_decoding_message_size(limits.max_message_bytes)
+                .max_encoding_message_size(limits.max_message_bytes),
+        );
+        tracing::info!("store is open, serving {}", tee_server::SERVICE_NAME);
+        let probe = store.clone();
+        tokio::spawn(health::monitor(
+            reporter,
+            tee_server::SERVICE_NAME,
+            HEALTH_CHECK_INTERVAL,
+            move || {
+                let store = probe.clone();
+                async move { store.check_health().await }
+            },
+        ));
+        Ok::<_, Box<dyn std::error::Error>>(())
+    };
+    tokio::try_join!(async { Ok(server.await?) }, start, serve_metrics)?;
 
     Ok(())
 }
@@ -0,0 +1,348 @@
+//! Prometheus metrics, served over HTTP at `/metrics`.
+//!
+//! [`Observed`] wraps the Tee service to time every RPC and count its failures by gRPC
+//! code. [`TeeService`](crate::service::TeeService) records what each write did, and
+//! the graph-size gauges are read from the store on every scrape once it is open.
+
+use std::convert::Infallible;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use prometheus::{
+    HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use tokio::net::TcpListener;
+use tonic::body::Body;
+use tonic::codegen::{http, BoxFuture, Context, Poll, Service};
+use tonic::server::NamedService;
+use tonic::Code;
+
+use crate::proto::{HypothesisMergeResult, TombstoneMergeResult};
+use crate::store::{GraphSize, Store};
+
+/// The server's metrics. Clones share the same counters.
+#[derive(Debug, Clone)]
+pub struct Metrics {
+    registry: Registry,
+    rpc_duration: HistogramVec,
+    rpc_errors: IntCounterVec,
+    merged_ids: IntCounterVec,
+    conflicts: IntCounterVec,
+    tombstoned_ids: IntCounterVec,
+    version: IntGauge,
+    nodes: IntGauge,
+    edges: IntGauge,
+    incidents: IntGauge,
+    tombstones: IntGaugeVec,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+        let register = |metric: Box<dyn prometheus::core::Collector>| {
+            registry
+                .register(metric)
+                .expect("metric names are distinct and valid");
+        };
+        let counter = |name: &str, help: &str, labels: &[&str]| {
+            let counter = IntCounterVec::new(Opts::new(name, help), labels).unwrap();
+            register(Box::new(counter.clone()));
+            counter
+        };
+        let gauge = |name: &str, help: &str| {
+            let gauge = IntGauge::new(name, help).unwrap();
+            register(Box::new(gauge.clone()));
+            gauge
+        };
+
+        let rpc_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "tee_rpc_duration_seconds",
+                "Time to answer a Tee RPC; for a stream, until it opens",
+            ),
+            &["method"],
+        )
+        .unwrap();
+        register(Box::new(rpc_duration.clone()));
+        let tombstones = IntGaugeVec::new(
+            Opts::new("tee_graph_tombstones", "Tombstones across all incidents"),
+            &["kind"],
+        )
+        .unwrap();
+        register(Box::new(tombstones.clone()));
+
+        Self {
+            rpc_duration,
+            rpc_errors: counter(
+                "tee_rpc_errors_total",
+                "Tee RPCs that failed, by gRPC status code",
+                &["method", "code"],
+            ),
+            merged_ids: counter(
+                "tee_merged_ids_total",
+                "Ids written by MergeHypothesis, by whether the write created or merged them",
+                &["outcome"],
+            ),
+            conflicts: counter(
+                "tee_merge_conflicts_total",
+                "Hypothesis writes rejected for disagreeing with the first writer, by field",
+                &["field"],
+            ),
+            tombstoned_ids: counter(
+                "tee_tombstoned_ids_total",
+                "Ids named by tombstone writes: applied, already_tombstoned or unmatched",
+                &["kind", "outcome"],
+            ),
+            version: gauge("tee_graph_version", "Main-graph version"),
+            nodes: gauge("tee_graph_nodes", "Nodes in the main graph"),
+            edges: gauge("tee_graph_edges", "Edges in the main graph"),
+            incidents: gauge("tee_graph_incidents", "Incidents"),
+            tombstones,
+            registry,
+        }
+    }
+
+    /// Records one RPC `method` that took `elapsed` and ended with `code`.
+    pub fn observe_rpc(&self, method: &str, elapsed: Duration, code: Code) {
+        self.rpc_duration
+            .with_label_values(&[method])
+            .observe(elapsed.as_secs_f64());
+        if code != Code::Ok {
+            self.rpc_errors
+                .with_label_values(&[method, &format!("{code:?}")])
+                .inc();
+        }
+    }
+
+    pub fn merged(&self, result: &HypothesisMergeResult) {
+        let ids = |outcome: &str, ids: &[String]| {
+            self.merged_ids
+                .with_label_values(&[outcome])
+                .inc_by(ids.len() as u64);
+        };
+        ids("created", &result.created_ids);
+        ids("merged", &result.merged_ids);
+        for conflict in &result.conflicts {
+            self.conflicts.with_label_values(&[&conflict.field]).inc();
+        }
+    }
+
+    /// Records a tombstone write; `kind` is `node` or `edge`.
+    pub fn tombstoned(&self, kind: &str, result: &TombstoneMergeResult) {
+        let ids = |outcome: &str, ids: &[String]| {
+            self.tombstoned_ids
+                .with_label_values(&[kind, outcome])
+                .inc_by(ids.len() as u64);
+        };
+        ids("applied", &result.applied_ids);
+        ids("already_tombstoned", &result.already_tombstoned_ids);
+        ids("unmatched", &result.unmatched_ids);
+    }
+
+    pub fn set_graph_size(&self, size: &GraphSize) {
+        self.version.set(size.version as i64);
+        self.nodes.set(size.nodes as i64);
+        self.edges.set(size.edges as i64);
+        self.incidents.set(size.incidents as i64);
+        let tombstones = |kind: &str, count: u64| {
+            self.tombstones.with_label_values(&[kind]).set(count as i64);
+        };
+        tombstones("node", size.node_tombstones);
+        tombstones("edge", size.edge_tombstones);
+    }
+
+    /// Every metric, in the Prometheus text format.
+    pub fn encode(&self) -> String {
+        TextEncoder::new()
+            .encode_to_string(&self.registry.gather())
+            .expect("metrics encode as text")
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A gRPC service whose RPCs are recorded in [`Metrics`]: their latency, and the
+/// status code of those that fail before a response is sent.
+#[derive(Debug, Clone)]
+pub struct Observed<S> {
+    inner: S,
+    metrics: Metrics,
+}
+
+impl<S> Observed<S> {
+    pub fn new(inner: S, metrics: Metrics) -> Self {
+        Self { inner, metrics }
+    }
+}
+
+impl<S: NamedService> NamedService for Observed<S> {
+    const NAME: &'static str = S::NAME;
+}
+
+impl<S, B> Service<http::Request<Body>> for Observed<S>
+where
+    S: Service<http::Request<Body>, Response = http::Response<B>, Error = Infallible>,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+        let path = request.uri().path();
+        let method = path.rsplit('/').next().unwrap_or(path).to_owned();
+        let metrics = self.metrics.clone();
+        let start = Instant::now();
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            let response = response.await?;
+            // Failed calls are answered with the status in the headers
+            let code = response
+                .headers()
+                .get("grpc-status")
+                .map_or(Code::Ok, |status| Code::from_bytes(status.as_bytes()));
+            // Paths the service doesn't route would each add a label value
+            let method = match code {
+                Code::Unimplemented => "unknown",
+                _ => &method,
+            };
+            metrics.observe_rpc(method, start.elapsed(), code);
+            Ok(response)
+        })
+    }
+}
+
+/// The store the graph-size gauges are read from, set once it is open.
+pub type OpenedStore<S> = Arc<OnceLock<Arc<S>>>;
+
+/// Serves `GET /metrics` on `listener`, refreshing the graph-size gauges from `store`
+/// on every scrape. Until the store is set, scrapes answer with the gauges left as they
+/// are, so metrics are available while a store recovers.
+pub async fn serve<S: Store + 'static>(
+    listener: TcpListener,
+    metrics: Metrics,
+    store: OpenedStore<S>,
+) -> std::io::Result<()> {
+    let app = Router::new()
+        .route("/metrics", get(scrape::<S>))
+        .with_state((metrics, store));
+    axum::serve(listener, app).await
+}
+
+async fn scrape<S: Store>(
+    State((metrics, store)): State<(Metrics, OpenedStore<S>)>,
+) -> impl IntoResponse {
+    if let Some(store) = store.get() {
+        match store.graph_size().await {
+            Ok(size) => metrics.set_graph_size(&size),
+            // The gauges keep their last values
+            Err(err) => tracing::warn!("failed to read the graph size for metrics: {err}"),
+        }
+    }
+    (
+        [(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)],
+        metrics.encode(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Server;
+
+    use super::*;
+    use crate::proto::tee_client::TeeClient;
+    use crate::proto::tee_server::TeeServer;
+    use crate::proto::{CreateIncidentRequest, TombstoneRequest};
+    use crate::service::TeeService;
+    use crate::store::memory::InMemoryStore;
+
+    #[tokio::test]
+    async fn rpcs_are_timed_and_failures_counted_by_code() {
+        let metrics = Metrics::new();
+        let service = TeeServer::new(TeeService::new(Arc::new(InMemoryStore::new())));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            Server::builder()
+                .add_service(Observed::new(service, metrics.clone()))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+        let mut client = TeeClient::connect(format!("http://{addr}")).await.unwrap();
+        let request = || TombstoneRequest {
+            incident_id: "inc-1".into(),
+        };
+
+        client.get_tombstones(request()).await.unwrap_err();
+        client
+            .create_incident(CreateIncidentRequest {
+                incident_id: "inc-1".into(),
+                follow_main_graph: false,
+            })
+            .await
+            .unwrap();
+        client.get_tombstones(request()).await.unwrap();
+
+        let text = metrics.encode();
+        assert!(text.contains("tee_rpc_duration_seconds_count{method=\"GetTombstones\"} 2"));
+        assert!(text.contains("tee_rpc_duration_seconds_count{method=\"CreateIncident\"} 1"));
+        assert!(text.contains("tee_rpc_errors_total{code=\"NotFound\",method=\"GetTombstones\"} 1"));
+        let errors = text
+            .lines()
+            .filter(|l| l.starts_with("tee_rpc_errors_total{"));
+        assert_eq!(errors.count(), 1);
+    }
+
+    async fn scrape(addr: std::net::SocketAddr) -> String {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: tee\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        response
+    }
+
+    #[tokio::test]
+    async fn scrapes_read_the_graph_size_once_the_store_is_open() {
+        let metrics = Metrics::new();
+        let opened = OpenedStore::default();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, metrics, opened.clone()));
+
+        // Answered while the store is still opening
+        let response = scrape(addr).await;
+        assert!(response.contains("tee_graph_incidents 0"));
+
+        let store = Arc::new(InMemoryStore::new());
+        store
+            .create_incident(CreateIncidentRequest {
+                incident_id: "inc-1".into(),
+                follow_main_graph: false,
+            })
+            .await
+            .unwrap();
+        opened.get_or_init(|| store);
+        let response = scrape(addr).await;
+        assert!(response.contains("tee_graph_incidents 1"));
+        assert!(response.contains("tee_graph_tombstones{kind=\"node\"} 0"));
+    }
+}
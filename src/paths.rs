@@ -0,0 +1,401 @@
+//! Simple-path enumeration between two node sets of an incident's live view, for
+//! `FindPaths`.
+//!
+//! Every path of at most `max_depth` edges from a start node lies within the start
+//! nodes' neighborhood of that many hops, so paths are searched in that neighborhood
+//! and only ever cross the live nodes and edges `GetNeighborhood` returns. They are
+//! enumerated by iterative deepening, all paths of one edge before any of two, so the
+//! first `max_paths` found are the shortest. Paths of one length come in order of
+//! their start node, then of the edges taken. A path may pass through other end nodes.
+//!
+//! The number of simple paths grows exponentially with depth, so a search takes at
+//! most [`STEP_BUDGET`] steps and reports the result truncated if it runs out.
+
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+use crate::domain::edge_type::EdgeType;
+use crate::proto;
+use crate::store::neighborhood::{Direction, NeighborhoodQuery};
+
+/// Most paths one `FindPaths` call returns.
+pub const MAX_PATHS: usize = 1000;
+
+/// Most edges one `FindPaths` search follows, across all depths. Past it, the search
+/// stops and the paths found so far are returned as truncated.
+pub const STEP_BUDGET: usize = 100_000;
+
+/// Parameters of a path query.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PathQuery {
+    pub from: BTreeSet<String>,
+    pub to: BTreeSet<String>,
+    /// Maximum number of edges in a path.
+    pub max_depth: u32,
+    /// Which way each edge may be crossed.
+    pub direction: Direction,
+    /// Edge kinds a path may use; empty allows every kind.
+    pub edge_types: BTreeSet<EdgeType>,
+    /// Number of shortest paths to return; `0` returns up to [`MAX_PATHS`].
+    pub max_paths: usize,
+    pub as_of_version: Option<u64>,
+}
+
+impl From<proto::PathRequest> for PathQuery {
+    fn from(request: proto::PathRequest) -> Self {
+        Self {
+            direction: request.direction().into(),
+            from: request.from_node_ids.into_iter().collect(),
+            to: request.to_node_ids.into_iter().collect(),
+            max_depth: request.max_depth,
+            edge_types: request
+                .edge_types
+                .iter()
+                .map(|name| EdgeType::from_name(name))
+                .collect(),
+            max_paths: request.max_paths as usize,
+            as_of_version: request.as_of_version,
+        }
+    }
+}
+
+impl PathQuery {
+    /// The neighborhood read that holds every path this query can return.
+    pub fn neighborhood(&self) -> NeighborhoodQuery {
+        NeighborhoodQuery {
+            node_ids: self.from.clone(),
+            hops: self.max_depth,
+            direction: self.direction,
+            edge_types: self.edge_types.clone(),
+            max_nodes: 0,
+            as_of_version: self.as_of_version,
+        }
+    }
+
+    fn limit(&self) -> usize {
+        match self.max_paths {
+            0 => MAX_PATHS,
+            n => n.min(MAX_PATHS),
+        }
+    }
+}
+
+/// Enumerates the paths `query` asks for in `graph`, the result of reading
+/// [`PathQuery::neighborhood`].
+pub fn find_paths(query: &PathQuery, graph: &proto::CausalGraph) -> proto::PathResult {
+    let mut finder = PathFinder::new(query, graph);
+    let limit = query.limit();
+    // A simple path never has more edges than the graph has nodes less one
+    let longest = query
+        .max_depth
+        .min(graph.nodes.len().saturating_sub(1) as u32);
+    for depth in 1..=longest {
+        for start in &query.from {
+            if finder.nodes.contains_key(start.as_str()) {
+                finder.search(start, depth, limit + 1);
+            }
+        }
+        if finder.found.len() > limit || finder.capped {
+            break;
+        }
+    }
+
+    let truncated = finder.found.len() > limit || finder.capped;
+    let paths = finder
+        .found
+        .iter()
+        .take(limit)
+        .map(|(nodes, edges)| proto::CausalPath {
+            nodes: nodes.iter().map(|&i| graph.nodes[i].clone()).collect(),
+            edges: edges.iter().map(|&i| graph.edges[i].clone()).collect(),
+        })
+        .collect();
+    proto::PathResult {
+        paths,
+        version: graph.version,
+        truncated,
+    }
+}
+
+/// Depth-first search state over one neighborhood, by index into its nodes and edges.
+struct PathFinder<'g> {
+    nodes: HashMap<&'g str, usize>,
+    /// For each node, the edges a path may leave it by and the node each leads to.
+    steps: HashMap<&'g str, Vec<(usize, &'g str)>>,
+    /// Fewest edges from each node to an end node; nodes that reach none are absent.
+    distance: HashMap<&'g str, u32>,
+    to: &'g BTreeSet<String>,
+    path_nodes: Vec<&'g str>,
+    path_edges: Vec<usize>,
+    found: Vec<(Vec<usize>, Vec<usize>)>,
+    /// Nodes with no path of the given length to an end node, whatever path leads
+    /// there.
+    dead_ends: HashSet<(&'g str, u32)>,
+    /// Steps left before the search stops.
+    budget: usize,
+    /// Whether a step was skipped for lack of budget.
+    capped: bool,
+}
+
+/// How a search from one node went.
+struct Explored {
+    found: bool,
+    /// Position in the path of the earliest node a step was refused for revisiting.
+    blocked_at: usize,
+    /// Whether the budget or the path limit stopped the search early.
+    cut: bool,
+}
+
+impl<'g> PathFinder<'g> {
+    fn new(query: &'g PathQuery, graph: &'g proto::CausalGraph) -> Self {
+        let nodes: HashMap<_, _> = graph
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.id.as_str(), i))
+            .collect();
+        let mut steps: HashMap<_, Vec<_>> = HashMap::new();
+        let mut back_steps: HashMap<_, Vec<_>> = HashMap::new();
+        for (i, edge) in graph.edges.iter().enumerate() {
+            let (source, target) = (edge.source.as_str(), edge.target.as_str());
+            if query.direction.follows_outgoing() {
+                steps.entry(source).or_default().push((i, target));
+                back_steps.entry(target).or_default().push(source);
+            }
+            if query.direction.follows_incoming() {
+                steps.entry(target).or_default().push((i, source));
+                back_steps.entry(source).or_default().push(target);
+            }
+        }
+
+        let mut distance = HashMap::new();
+        let mut queue = VecDeque::new();
+        for end in &query.to {
+            if nodes.contains_key(end.as_str()) {
+                distance.insert(end.as_str(), 0);
+                queue.push_back(end.as_str());
+            }
+        }
+        while let Some(node) = queue.pop_front() {
+            let next = distance[node] + 1;
+            for &prev in back_steps.get(node).into_iter().flatten() {
+                if !distance.contains_key(prev) {
+                    distance.insert(prev, next);
+                    queue.push_back(prev);
+                }
+            }
+        }
+
+        Self {
+            nodes,
+            steps,
+            distance,
+            to: &query.to,
+            path_nodes: Vec::new(),
+            path_edges: Vec::new(),
+            found: Vec::new(),
+            dead_ends: HashSet::new(),
+            budget: STEP_BUDGET,
+            capped: false,
+        }
+    }
+
+    /// Records every simple path of exactly `depth` edges from `start` to an end node,
+    /// until `limit` paths have been found in total.
+    fn search(&mut self, start: &'g str, depth: u32, limit: usize) {
+        self.path_nodes.push(start);
+        if !self.dead_ends.contains(&(start, depth)) {
+            self.extend(start, depth, limit);
+        }
+        self.path_nodes.pop();
+    }
+
+    fn extend(&mut self, node: &'g str, remaining: u32, limit: usize) -> Explored {
+        let mut explored = Explored {
+            found: false,
+            blocked_at: usize::MAX,
+            cut: false,
+        };
+        if self.found.len() >= limit {
+            explored.cut = true;
+            return explored;
+        }
+        if remaining == 0 {
+            explored.found = self.to.contains(node);
+            if explored.found {
+                let nodes = self.path_nodes.iter().map(|id| self.nodes[id]).collect();
+                self.found.push((nodes, self.path_edges.clone()));
+            }
+            return explored;
+        }
+        let position = self.path_nodes.len() - 1;
+        let steps = self.steps.get(node).cloned().unwrap_or_default();
+        for (edge, next) in steps {
+            let reaches_end = self.distance.get(next).is_some_and(|&d| d < remaining);
+            if !reaches_end || self.dead_ends.contains(&(next, remaining - 1)) {
+                continue;
+            }
+            if let Some(at) = self.path_nodes.iter().position(|&id| id == next) {
+                explored.blocked_at = explored.blocked_at.min(at);
+                continue;
+            }
+            if self.budget == 0 {
+                self.capped = true;
+                explored.cut = true;
+                break;
+            }
+            self.budget -= 1;
+            self.path_nodes.push(next);
+            self.path_edges.push(edge);
+            let step = self.extend(next, remaining - 1, limit);
+            self.path_nodes.pop();
+            self.path_edges.pop();
+            explored.found |= step.found;
+            explored.blocked_at = explored.blocked_at.min(step.blocked_at);
+            explored.cut |= step.cut;
+        }
+        // Finding nothing only makes a dead end if no node before this one on the path
+        // was in the way
+        if !explored.found && !explored.cut && explored.blocked_at >= position {
+            self.dead_ends.insert((node, remaining));
+        }
+        explored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::neighborhood::MAX_HOPS;
+
+    fn node(id: &str) -> proto::Node {
+        proto::Node {
+            id: id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn edge(source: &str, target: &str) -> proto::Edge {
+        proto::Edge {
+            source: source.to_string(),
+            target: target.to_string(),
+            r#type: proto::EdgeType::PropagatesTo as i32,
+            ..Default::default()
+        }
+    }
+
+    /// Two routes from a to d: a -> b -> d and a -> c -> e -> d, plus d -> f.
+    fn diamond() -> proto::CausalGraph {
+        proto::CausalGraph {
+            nodes: ["a", "b", "c", "d", "e", "f"].map(node).to_vec(),
+            edges: vec![
+                edge("a", "b"),
+                edge("a", "c"),
+                edge("b", "d"),
+                edge("c", "e"),
+                edge("d", "f"),
+                edge("e", "d"),
+            ],
+            version: 3,
+            ..Default::default()
+        }
+    }
+
+    fn query(from: &str, to: &[&str], max_depth: u32) -> PathQuery {
+        PathQuery {
+            from: [from.to_string()].into(),
+            to: to.iter().map(|id| id.to_string()).collect(),
+            max_depth,
+            direction: Direction::Downstream,
+            ..Default::default()
+        }
+    }
+
+    fn routes(result: &proto::PathResult) -> Vec<String> {
+        result
+            .paths
+            .iter()
+            .map(|p| p.nodes.iter().map(|n| n.id.as_str()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn enumerates_simple_paths_shortest_first() {
+        let result = find_paths(&query("a", &["d"], 3), &diamond());
+        assert_eq!(routes(&result), ["abd", "aced"]);
+        assert_eq!(result.version, 3);
+        assert!(!result.truncated);
+
+        let path = &result.paths[0];
+        assert_eq!(path.edges.len(), 2);
+        assert_eq!(
+            (path.edges[1].source.as_str(), path.edges[1].target.as_str()),
+            ("b", "d")
+        );
+    }
+
+    #[test]
+    fn max_depth_bounds_path_length() {
+        let result = find_paths(&query("a", &["d"], 2), &diamond());
+        assert_eq!(routes(&result), ["abd"]);
+    }
+
+    #[test]
+    fn max_paths_keeps_the_shortest() {
+        let query = PathQuery {
+            max_paths: 1,
+            ..query("a", &["d", "f"], 4)
+        };
+        let result = find_paths(&query, &diamond());
+        assert_eq!(routes(&result), ["abd"]);
+        assert!(result.truncated);
+    }
+
+    #[test]
+    fn paths_may_pass_through_other_end_nodes() {
+        let result = find_paths(&query("a", &["d", "f"], 4), &diamond());
+        assert_eq!(routes(&result), ["abd", "abdf", "aced", "acedf"]);
+    }
+
+    #[test]
+    fn direction_decides_which_way_edges_are_crossed() {
+        assert!(find_paths(&query("d", &["a"], 3), &diamond())
+            .paths
+            .is_empty());
+
+        let upstream = PathQuery {
+            direction: Direction::Upstream,
+            ..query("d", &["a"], 3)
+        };
+        assert_eq!(routes(&find_paths(&upstream, &diamond())), ["dba", "deca"]);
+
+        // Crossing edges both ways still never revisits a node
+        let both = PathQuery {
+            direction: Direction::Both,
+            ..query("b", &["c"], 5)
+        };
+        assert_eq!(routes(&find_paths(&both, &diamond())), ["bac", "bdec"]);
+    }
+
+    #[test]
+    fn search_stops_at_the_step_budget() {
+        // s -> t directly, and every node of a clique leads back to s. Each walk around
+        // the clique looks one step from t, but could only reach it through s again.
+        let clique: Vec<String> = (0..10).map(|i| format!("c{i}")).collect();
+        let mut graph = proto::CausalGraph {
+            nodes: vec![node("s"), node("t")],
+            edges: vec![edge("s", "t"), edge("s", "c0")],
+            ..Default::default()
+        };
+        for a in &clique {
+            graph.nodes.push(node(a));
+            graph.edges.push(edge(a, "s"));
+            for b in clique.iter().filter(|&b| b != a) {
+                graph.edges.push(edge(a, b));
+            }
+        }
+
+        let result = find_paths(&query("s", &["t"], MAX_HOPS), &graph);
+        assert_eq!(routes(&result), ["st"]);
+        assert!(result.truncated);
+    }
+}
@@ -5,6 +5,7 @@ use crate::domain::edge_type::EdgeType;
 use crate::domain::node::NodeLattice;
 use crate::domain::node_type::NodeType;
 use crate::domain::provenance::Provenance;
+use crate::domain::rationale::Rationale;
 use crate::proto;
 
 #[derive(Debug, thiserror::Error)]
@@ -26,22 +27,36 @@ impl TryFrom<i32> for NodeType {
             x if x == proto::NodeType::Dependency as i32 => Ok(NodeType::Dependency),
             x if x == proto::NodeType::Infrastructure as i32 => Ok(NodeType::Infrastructure),
             x if x == proto::NodeType::Mechanism as i32 => Ok(NodeType::Mechanism),
+            x if x == proto::NodeType::Placeholder as i32 => Ok(NodeType::Placeholder),
             other => Err(ConversionError::InvalidNodeType(other)),
         }
     }
 }
 
-impl From<NodeType> for i32 {
-    fn from(value: NodeType) -> Self {
+/// Registered kinds have no enum value and map to `UNSPECIFIED`; their name travels
+/// in the `kind` field instead.
+impl From<&NodeType> for i32 {
+    fn from(value: &NodeType) -> Self {
         match value {
             NodeType::Service => proto::NodeType::Service as i32,
             NodeType::Dependency => proto::NodeType::Dependency as i32,
             NodeType::Infrastructure => proto::NodeType::Infrastructure as i32,
             NodeType::Mechanism => proto::NodeType::Mechanism as i32,
+            NodeType::Placeholder => proto::NodeType::Placeholder as i32,
+            NodeType::Registered(_) => proto::NodeType::Unspecified as i32,
         }
     }
 }
 
+/// Resolves a proto node's type: the `kind` name when set, otherwise the enum value.
+pub fn node_type_of(r#type: i32, kind: &str) -> Result<NodeType, ConversionError> {
+    if kind.is_empty() {
+        NodeType::try_from(r#type)
+    } else {
+        Ok(NodeType::from_name(kind))
+    }
+}
+
 // --- EdgeType conversions ---
 
 impl TryFrom<i32> for EdgeType {
@@ -57,16 +72,37 @@ impl TryFrom<i32> for EdgeType {
     }
 }
 
-impl From<EdgeType> for i32 {
-    fn from(value: EdgeType) -> Self {
+/// Registered kinds have no enum value and map to `UNSPECIFIED`; their name travels
+/// in the `kind` field instead.
+impl From<&EdgeType> for i32 {
+    fn from(value: &EdgeType) -> Self {
         match value {
             EdgeType::DependsOn => proto::EdgeType::DependsOn as i32,
             EdgeType::PropagatesTo => proto::EdgeType::PropagatesTo as i32,
             EdgeType::ManifestsAs => proto::EdgeType::ManifestsAs as i32,
+            EdgeType::Registered(_) => proto::EdgeType::Unspecified as i32,
         }
     }
 }
 
+/// Resolves a proto edge's type: the `kind` name when set, otherwise the enum value.
+pub fn edge_type_of(r#type: i32, kind: &str) -> Result<EdgeType, ConversionError> {
+    if kind.is_empty() {
+        EdgeType::try_from(r#type)
+    } else {
+        Ok(EdgeType::from_name(kind))
+    }
+}
+
+/// Identifier reported for an edge in merge and tombstone results: `source->target:T`,
+/// where `T` is the proto enum value for built-in kinds and the name for registered ones.
+pub fn edge_id(source: &str, target: &str, edge_type: &EdgeType) -> String {
+    match edge_type {
+        EdgeType::Registered(name) => format!("{source}->{target}:{name}"),
+        builtin => format!("{source}->{target}:{}", i32::from(builtin)),
+    }
+}
+
 // --- Provenance conversions ---
 
 impl From<proto::Provenance> for Provenance {
@@ -75,7 +111,9 @@ impl From<proto::Provenance> for Provenance {
             .timestamp
             .map(|t| (t.seconds, t.nanos))
             .unwrap_or((0, 0));
-        Provenance::new(p.source, p.trigger).with_timestamp(secs, nanos)
+        Provenance::new(p.source, p.trigger)
+            .with_timestamp(secs, nanos)
+            .with_version(p.version)
     }
 }
 
@@ -88,6 +126,35 @@ impl From<&Provenance> for proto::Provenance {
                 seconds: p.timestamp_seconds,
                 nanos: p.timestamp_nanos,
             }),
+            version: p.version,
+        }
+    }
+}
+
+// --- Rationale conversions ---
+
+/// Source and trigger are kept as sent; stores attribute a request's rationale to its
+/// writer with [`Rationale::by`].
+impl From<proto::EliminationRationale> for Rationale {
+    fn from(r: proto::EliminationRationale) -> Self {
+        Rationale {
+            source: r.source,
+            trigger: r.trigger,
+            text: r.text,
+            evidence_uris: r.evidence_uris,
+            attributes: r.attributes.into_iter().collect(),
+        }
+    }
+}
+
+impl From<&Rationale> for proto::EliminationRationale {
+    fn from(r: &Rationale) -> Self {
+        proto::EliminationRationale {
+            source: r.source.clone(),
+            trigger: r.trigger.clone(),
+            text: r.text.clone(),
+            evidence_uris: r.evidence_uris.clone(),
+            attributes: r.attributes.clone().into_iter().collect(),
         }
     }
 }
@@ -99,7 +166,7 @@ impl From<&Provenance> for proto::Provenance {
 pub fn proto_node_to_domain(
     node: proto::Node,
 ) -> Result<(String, NodeLattice), ConversionError> {
-    let node_type = NodeType::try_from(node.r#type)?;
+    let node_type = node_type_of(node.r#type, &node.kind)?;
     let provenance: BTreeSet<Provenance> = node.provenance.into_iter().map(Into::into).collect();
     let lattice = NodeLattice::new(node_type, node.label, node.hypothetical, provenance);
     Ok((node.id, lattice))
@@ -112,8 +179,13 @@ pub fn domain_node_to_proto(id: String, lattice: &NodeLattice) -> proto::Node {
         r#type: lattice
             .node_type
             .as_reveal_ref()
-            .map(|t| i32::from(*t))
+            .map(i32::from)
             .unwrap_or(proto::NodeType::Unspecified as i32),
+        kind: lattice
+            .node_type
+            .as_reveal_ref()
+            .map(|t| t.to_string())
+            .unwrap_or_default(),
         label: lattice
             .label
             .as_reveal_ref()
@@ -136,7 +208,7 @@ pub fn domain_node_to_proto(id: String, lattice: &NodeLattice) -> proto::Node {
 pub fn proto_edge_to_domain(
     edge: proto::Edge,
 ) -> Result<(EdgeKey, EdgeLattice), ConversionError> {
-    let edge_type = EdgeType::try_from(edge.r#type)?;
+    let edge_type = edge_type_of(edge.r#type, &edge.kind)?;
     let key = EdgeKey::new(edge.source, edge.target, edge_type);
     let provenance: BTreeSet<Provenance> = edge.provenance.into_iter().map(Into::into).collect();
     let lattice = EdgeLattice::new(provenance);
@@ -148,7 +220,8 @@ pub fn domain_edge_to_proto(key: &EdgeKey, lattice: &EdgeLattice) -> proto::Edge
     proto::Edge {
         source: key.source.clone(),
         target: key.target.clone(),
-        r#type: i32::from(key.edge_type),
+        r#type: i32::from(&key.edge_type),
+        kind: key.edge_type.to_string(),
         provenance: lattice
             .provenance
             .as_reveal_ref()
@@ -158,6 +231,18 @@ pub fn domain_edge_to_proto(key: &EdgeKey, lattice: &EdgeLattice) -> proto::Edge
     }
 }
 
+/// Convert an `EdgeKey` to the tombstone entry that names it.
+impl From<&EdgeKey> for proto::EdgeTombstoneEntry {
+    fn from(key: &EdgeKey) -> Self {
+        proto::EdgeTombstoneEntry {
+            source: key.source.clone(),
+            target: key.target.clone(),
+            r#type: i32::from(&key.edge_type),
+            kind: key.edge_type.to_string(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -170,7 +255,7 @@ mod tests {
             NodeType::Infrastructure,
             NodeType::Mechanism,
         ] {
-            let i: i32 = nt.into();
+            let i = i32::from(&nt);
             let back = NodeType::try_from(i).unwrap();
             assert_eq!(nt, back);
         }
@@ -183,7 +268,7 @@ mod tests {
             EdgeType::PropagatesTo,
             EdgeType::ManifestsAs,
         ] {
-            let i: i32 = et.into();
+            let i = i32::from(&et);
             let back = EdgeType::try_from(i).unwrap();
             assert_eq!(et, back);
         }
@@ -208,17 +293,20 @@ mod tests {
                 seconds: 1000,
                 nanos: 500,
             }),
+            version: 7,
         };
         let domain: Provenance = proto_prov.into();
         assert_eq!(domain.source, "agent-1");
         assert_eq!(domain.trigger, "alert");
         assert_eq!(domain.timestamp_seconds, 1000);
         assert_eq!(domain.timestamp_nanos, 500);
+        assert_eq!(domain.version, 7);
 
         let back: proto::Provenance = (&domain).into();
         assert_eq!(back.source, "agent-1");
         assert_eq!(back.trigger, "alert");
         assert_eq!(back.timestamp.unwrap().seconds, 1000);
+        assert_eq!(back.version, 7);
     }
 
     #[test]
@@ -232,7 +320,9 @@ mod tests {
                 source: "a".into(),
                 trigger: "t".into(),
                 timestamp: None,
+                version: 0,
             }],
+            kind: String::new(),
         };
         let (id, lattice) = proto_node_to_domain(proto_node).unwrap();
         assert_eq!(id, "n1");
@@ -240,6 +330,7 @@ mod tests {
         let back = domain_node_to_proto(id, &lattice);
         assert_eq!(back.id, "n1");
         assert_eq!(back.r#type, proto::NodeType::Service as i32);
+        assert_eq!(back.kind, "SERVICE");
         assert_eq!(back.label, "api-gw");
         assert!(back.hypothetical);
         assert_eq!(back.provenance.len(), 1);
@@ -255,7 +346,9 @@ mod tests {
                 source: "agent".into(),
                 trigger: "scan".into(),
                 timestamp: None,
+                version: 0,
             }],
+            kind: String::new(),
         };
         let (key, lattice) = proto_edge_to_domain(proto_edge).unwrap();
         assert_eq!(key.source, "a");
@@ -268,4 +361,30 @@ mod tests {
         assert_eq!(back.r#type, proto::EdgeType::DependsOn as i32);
         assert_eq!(back.provenance.len(), 1);
     }
+
+    #[test]
+    fn registered_kind_roundtrip() {
+        let proto_edge = proto::Edge {
+            source: "a".into(),
+            target: "b".into(),
+            r#type: proto::EdgeType::Unspecified as i32,
+            provenance: vec![],
+            kind: "CORRELATES_WITH".into(),
+        };
+        let (key, lattice) = proto_edge_to_domain(proto_edge).unwrap();
+        assert_eq!(key.edge_type, EdgeType::Registered("CORRELATES_WITH".into()));
+        assert_eq!(edge_id("a", "b", &key.edge_type), "a->b:CORRELATES_WITH");
+
+        let back = domain_edge_to_proto(&key, &lattice);
+        assert_eq!(back.r#type, proto::EdgeType::Unspecified as i32);
+        assert_eq!(back.kind, "CORRELATES_WITH");
+    }
+
+    #[test]
+    fn kind_takes_precedence_over_type() {
+        let node_type = node_type_of(proto::NodeType::Service as i32, "DEPLOYMENT").unwrap();
+        assert_eq!(node_type, NodeType::Registered("DEPLOYMENT".into()));
+        let builtin = node_type_of(proto::NodeType::Unspecified as i32, "MECHANISM").unwrap();
+        assert_eq!(builtin, NodeType::Mechanism);
+    }
 }
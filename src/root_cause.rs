@@ -0,0 +1,316 @@
+//! Root-cause candidates of an incident, for `RankRootCauses`.
+//!
+//! A candidate is a live node that reaches every symptom node within `max_depth`
+//! edges, following `PROPAGATES_TO` and `MANIFESTS_AS` edges from source to target.
+//! Placeholders carry paths but are never candidates: nothing is known about them.
+//! Candidates are searched in the symptoms' upstream neighborhood of that many hops,
+//! so tombstones apply exactly as in the live view. They are ranked by the number of
+//! simple paths from the candidate to the symptoms, then by the number of distinct
+//! provenance sources that asserted the candidate, then by id.
+
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+use crate::domain::edge_type::EdgeType;
+use crate::proto;
+use crate::proto_convert::edge_type_of;
+use crate::store::neighborhood::{Direction, NeighborhoodQuery};
+
+/// Most path steps one ranking walks while counting paths. Past it, path counts are
+/// lower bounds.
+pub const PATH_BUDGET: usize = 100_000;
+
+/// Edge kinds a cause reaches its symptoms along.
+const CAUSAL_EDGES: [EdgeType; 2] = [EdgeType::PropagatesTo, EdgeType::ManifestsAs];
+
+/// Parameters of a root-cause ranking.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RootCauseQuery {
+    pub symptoms: BTreeSet<String>,
+    /// Maximum number of edges from a candidate to a symptom.
+    pub max_depth: u32,
+    /// Number of candidates to return; `0` returns all.
+    pub max_candidates: usize,
+    pub as_of_version: Option<u64>,
+}
+
+impl From<proto::RootCauseRequest> for RootCauseQuery {
+    fn from(request: proto::RootCauseRequest) -> Self {
+        Self {
+            symptoms: request.symptom_node_ids.into_iter().collect(),
+            max_depth: request.max_depth,
+            max_candidates: request.max_candidates as usize,
+            as_of_version: request.as_of_version,
+        }
+    }
+}
+
+impl RootCauseQuery {
+    /// The neighborhood read that holds every candidate and its paths.
+    pub fn neighborhood(&self) -> NeighborhoodQuery {
+        NeighborhoodQuery {
+            node_ids: self.symptoms.clone(),
+            hops: self.max_depth,
+            direction: Direction::Upstream,
+            edge_types: CAUSAL_EDGES.into(),
+            max_nodes: 0,
+            as_of_version: self.as_of_version,
+        }
+    }
+}
+
+/// Ranks the candidates `query` asks for in `graph`, the result of reading
+/// [`RootCauseQuery::neighborhood`].
+pub fn rank_candidates(
+    query: &RootCauseQuery,
+    graph: &proto::CausalGraph,
+) -> proto::RootCauseResult {
+    let nodes: HashMap<&str, &proto::Node> =
+        graph.nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+    let missing_symptom_ids: Vec<String> = query
+        .symptoms
+        .iter()
+        .filter(|id| !nodes.contains_key(id.as_str()))
+        .cloned()
+        .collect();
+    let mut result = proto::RootCauseResult {
+        missing_symptom_ids,
+        version: graph.version,
+        ..Default::default()
+    };
+    if !result.missing_symptom_ids.is_empty() {
+        return result;
+    }
+
+    let mut causes: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in &graph.edges {
+        let causal = edge_type_of(edge.r#type, &edge.kind).is_ok_and(|t| CAUSAL_EDGES.contains(&t));
+        if causal {
+            causes.entry(&edge.target).or_default().push(&edge.source);
+        }
+    }
+
+    // Nodes within reach of every symptom
+    let mut candidates: Option<HashSet<&str>> = None;
+    for symptom in &query.symptoms {
+        let reach = upstream_of(symptom, &causes, query.max_depth);
+        candidates = Some(match candidates {
+            None => reach,
+            Some(so_far) => so_far.intersection(&reach).copied().collect(),
+        });
+    }
+    let candidates = candidates.unwrap_or_default();
+
+    let mut counter = PathCounter {
+        causes: &causes,
+        path: Vec::new(),
+        counts: HashMap::new(),
+        budget: PATH_BUDGET,
+        capped: false,
+    };
+    for symptom in &query.symptoms {
+        counter.path.push(symptom);
+        counter.count(symptom, query.max_depth);
+        counter.path.pop();
+    }
+    result.path_counts_capped = counter.capped;
+
+    let placeholder = proto::NodeType::Placeholder as i32;
+    let mut ranked: Vec<_> = candidates
+        .into_iter()
+        .filter(|id| !query.symptoms.contains(*id) && nodes[id].r#type != placeholder)
+        .map(|id| {
+            let node = nodes[id];
+            let sources: BTreeSet<_> = node.provenance.iter().map(|p| p.source.clone()).collect();
+            proto::RootCauseCandidate {
+                node: Some(node.clone()),
+                path_count: counter.counts.get(id).copied().unwrap_or_default(),
+                supporting_sources: sources.into_iter().collect(),
+            }
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.path_count
+            .cmp(&a.path_count)
+            .then(b.supporting_sources.len().cmp(&a.supporting_sources.len()))
+            .then_with(|| node_id(a).cmp(node_id(b)))
+    });
+    if query.max_candidates != 0 {
+        ranked.truncate(query.max_candidates);
+    }
+    result.candidates = ranked;
+    result
+}
+
+fn node_id(candidate: &proto::RootCauseCandidate) -> &str {
+    candidate.node.as_ref().map_or("", |n| n.id.as_str())
+}
+
+/// The nodes with a path of at most `max_depth` edges to `symptom`.
+fn upstream_of<'g>(
+    symptom: &'g str,
+    causes: &HashMap<&'g str, Vec<&'g str>>,
+    max_depth: u32,
+) -> HashSet<&'g str> {
+    let mut depth = HashMap::from([(symptom, 0)]);
+    let mut queue = VecDeque::from([symptom]);
+    while let Some(node) = queue.pop_front() {
+        let next = depth[node] + 1;
+        if next > max_depth {
+            continue;
+        }
+        for &cause in causes.get(node).into_iter().flatten() {
+            if !depth.contains_key(cause) {
+                depth.insert(cause, next);
+                queue.push_back(cause);
+            }
+        }
+    }
+    depth.into_keys().filter(|&id| id != symptom).collect()
+}
+
+/// Counts simple paths by walking them backwards from each symptom: every node a
+/// walk arrives at has one more path to that symptom.
+struct PathCounter<'a, 'g> {
+    causes: &'a HashMap<&'g str, Vec<&'g str>>,
+    path: Vec<&'g str>,
+    counts: HashMap<&'g str, u64>,
+    /// Steps left before counting stops.
+    budget: usize,
+    /// Whether a step was skipped for lack of budget.
+    capped: bool,
+}
+
+impl<'g> PathCounter<'_, 'g> {
+    fn count(&mut self, node: &'g str, remaining: u32) {
+        if remaining == 0 {
+            return;
+        }
+        for &cause in self.causes.get(node).into_iter().flatten() {
+            if self.path.contains(&cause) {
+                continue;
+            }
+            if self.budget == 0 {
+                self.capped = true;
+                return;
+            }
+            self.budget -= 1;
+            *self.counts.entry(cause).or_default() += 1;
+            self.path.push(cause);
+            self.count(cause, remaining - 1);
+            self.path.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str, sources: &[&str]) -> proto::Node {
+        proto::Node {
+            id: id.to_string(),
+            provenance: sources
+                .iter()
+                .map(|source| proto::Provenance {
+                    source: source.to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn edge(source: &str, target: &str, edge_type: proto::EdgeType) -> proto::Edge {
+        proto::Edge {
+            source: source.to_string(),
+            target: target.to_string(),
+            r#type: edge_type as i32,
+            ..Default::default()
+        }
+    }
+
+    /// Two symptoms, latency and errors. oom reaches both, directly and through pool;
+    /// deploy reaches both through pool only; disk reaches errors only; cache depends
+    /// on latency, which is not causal.
+    fn incident() -> proto::CausalGraph {
+        use proto::EdgeType::{DependsOn, ManifestsAs, PropagatesTo};
+        proto::CausalGraph {
+            nodes: vec![
+                node("latency", &[]),
+                node("errors", &[]),
+                node("pool", &["agent-1"]),
+                node("oom", &["agent-1"]),
+                node("deploy", &["agent-1", "agent-2"]),
+                node("disk", &["agent-1"]),
+                node("cache", &["agent-1"]),
+            ],
+            edges: vec![
+                edge("pool", "latency", PropagatesTo),
+                edge("pool", "errors", PropagatesTo),
+                edge("oom", "pool", PropagatesTo),
+                edge("oom", "latency", ManifestsAs),
+                edge("deploy", "pool", PropagatesTo),
+                edge("disk", "errors", ManifestsAs),
+                edge("cache", "latency", DependsOn),
+            ],
+            version: 7,
+            ..Default::default()
+        }
+    }
+
+    fn query(max_depth: u32) -> RootCauseQuery {
+        RootCauseQuery {
+            symptoms: ["latency".to_string(), "errors".to_string()].into(),
+            max_depth,
+            ..Default::default()
+        }
+    }
+
+    fn ranking(result: &proto::RootCauseResult) -> Vec<(&str, u64)> {
+        result
+            .candidates
+            .iter()
+            .map(|c| (node_id(c), c.path_count))
+            .collect()
+    }
+
+    #[test]
+    fn ranks_nodes_reaching_every_symptom() {
+        let result = rank_candidates(&query(3), &incident());
+        // deploy ties with pool on paths but has wider support
+        assert_eq!(ranking(&result), [("oom", 3), ("deploy", 2), ("pool", 2)]);
+        assert_eq!(result.version, 7);
+        assert!(!result.path_counts_capped);
+        assert_eq!(
+            result.candidates[1].supporting_sources,
+            ["agent-1", "agent-2"]
+        );
+    }
+
+    #[test]
+    fn max_depth_bounds_reach() {
+        let result = rank_candidates(&query(1), &incident());
+        assert_eq!(ranking(&result), [("pool", 2)]);
+    }
+
+    #[test]
+    fn missing_symptom_leaves_no_candidates() {
+        let query = RootCauseQuery {
+            symptoms: ["latency".to_string(), "gone".to_string()].into(),
+            ..query(3)
+        };
+        let result = rank_candidates(&query, &incident());
+        assert!(result.candidates.is_empty());
+        assert_eq!(result.missing_symptom_ids, ["gone"]);
+    }
+
+    #[test]
+    fn placeholders_are_not_candidates() {
+        let mut graph = incident();
+        let pool = graph.nodes.iter_mut().find(|n| n.id == "pool").unwrap();
+        pool.r#type = proto::NodeType::Placeholder as i32;
+        // Paths through the placeholder still count for the nodes behind it
+        let result = rank_candidates(&query(3), &graph);
+        assert_eq!(ranking(&result), [("oom", 3), ("deploy", 2)]);
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::domain::edge_type::{EdgeType, UnknownEdgeType};
+use crate::domain::node_type::{NodeType, UnknownNodeType};
+
+use super::registry::TypeRegistry;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SchemaError {
+    #[error("failed to read schema file {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid schema: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("invalid schema: {0}")]
+    UnknownNodeType(#[from] UnknownNodeType),
+    #[error("invalid schema: {0}")]
+    UnknownEdgeType(#[from] UnknownEdgeType),
+}
+
+/// On-disk form: a list of permitted triples, naming kinds known to the type registry.
+///
+/// ```toml
+/// [[allow]]
+/// source = "MECHANISM"
+/// edge = "MANIFESTS_AS"
+/// target = "SERVICE"
+/// ```
+#[derive(Deserialize)]
+struct SchemaFile {
+    #[serde(default)]
+    allow: Vec<AllowEntry>,
+}
+
+#[derive(Deserialize)]
+struct AllowEntry {
+    source: String,
+    edge: String,
+    target: String,
+}
+
+/// The permitted `(source type, edge type, target type)` triples. An edge whose
+/// endpoint types don't form a permitted triple is rejected by validation.
+#[derive(Debug, Clone, Default)]
+pub struct CausalSchema {
+    allowed: BTreeSet<(NodeType, EdgeType, NodeType)>,
+}
+
+impl CausalSchema {
+    pub fn new(allowed: impl IntoIterator<Item = (NodeType, EdgeType, NodeType)>) -> Self {
+        Self {
+            allowed: allowed.into_iter().collect(),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>, registry: &TypeRegistry) -> Result<Self, SchemaError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|source| SchemaError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::from_toml(&text, registry)
+    }
+
+    pub fn from_toml(text: &str, registry: &TypeRegistry) -> Result<Self, SchemaError> {
+        let file: SchemaFile = toml::from_str(text)?;
+        let allowed = file
+            .allow
+            .into_iter()
+            .map(|entry| {
+                Ok((
+                    registry.node_type(&entry.source)?,
+                    registry.edge_type(&entry.edge)?,
+                    registry.node_type(&entry.target)?,
+                ))
+            })
+            .collect::<Result<_, SchemaError>>()?;
+        Ok(Self { allowed })
+    }
+
+    pub fn allows(&self, source: &NodeType, edge: &EdgeType, target: &NodeType) -> bool {
+        self.allowed
+            .contains(&(source.clone(), edge.clone(), target.clone()))
+    }
+
+    /// Whether any triple permits `edge` at all.
+    pub fn allows_edge_type(&self, edge: &EdgeType) -> bool {
+        self.allowed.iter().any(|(_, e, _)| e == edge)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"
+        [[allow]]
+        source = "MECHANISM"
+        edge = "MANIFESTS_AS"
+        target = "SERVICE"
+
+        [[allow]]
+        source = "SERVICE"
+        edge = "DEPENDS_ON"
+        target = "DEPENDENCY"
+    "#;
+
+    #[test]
+    fn parses_permitted_triples() {
+        let schema = CausalSchema::from_toml(SCHEMA, &TypeRegistry::default()).unwrap();
+        assert!(schema.allows(
+            &NodeType::Mechanism,
+            &EdgeType::ManifestsAs,
+            &NodeType::Service
+        ));
+        assert!(!schema.allows(
+            &NodeType::Service,
+            &EdgeType::ManifestsAs,
+            &NodeType::Mechanism
+        ));
+        assert!(schema.allows_edge_type(&EdgeType::DependsOn));
+        assert!(!schema.allows_edge_type(&EdgeType::PropagatesTo));
+    }
+
+    #[test]
+    fn unknown_type_name_rejected() {
+        let result = CausalSchema::from_toml(
+            r#"
+            [[allow]]
+            source = "DEPLOYMENT"
+            edge = "DEPENDS_ON"
+            target = "SERVICE"
+            "#,
+            &TypeRegistry::default(),
+        );
+        assert!(matches!(result, Err(SchemaError::UnknownNodeType(_))));
+    }
+
+    #[test]
+    fn registered_kinds_usable() {
+        let registry = TypeRegistry::from_toml(r#"node_types = ["DEPLOYMENT"]"#).unwrap();
+        let schema = CausalSchema::from_toml(
+            r#"
+            [[allow]]
+            source = "DEPLOYMENT"
+            edge = "PROPAGATES_TO"
+            target = "SERVICE"
+            "#,
+            &registry,
+        )
+        .unwrap();
+        assert!(schema.allows(
+            &NodeType::Registered("DEPLOYMENT".into()),
+            &EdgeType::PropagatesTo,
+            &NodeType::Service
+        ));
+    }
+
+    #[test]
+    fn example_schema_loads() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("config/causal_schema.toml");
+        let schema = CausalSchema::load(path, &TypeRegistry::default()).unwrap();
+        assert!(schema.allows(
+            &NodeType::Mechanism,
+            &EdgeType::ManifestsAs,
+            &NodeType::Service
+        ));
+    }
+}
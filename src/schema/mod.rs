@@ -1 +1,3 @@
+pub mod causal;
+pub mod registry;
 pub mod validation;
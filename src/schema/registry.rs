@@ -0,0 +1,170 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::domain::edge_type::{EdgeType, UnknownEdgeType};
+use crate::domain::node_type::{NodeType, UnknownNodeType};
+
+#[derive(Debug, thiserror::Error)]
+pub enum RegistryError {
+    #[error("failed to read type registry {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid type registry: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("invalid type name {0:?} (expected UPPER_SNAKE_CASE, and not PLACEHOLDER)")]
+    InvalidName(String),
+}
+
+/// On-disk form: kind names added on top of the built-in ones.
+///
+/// ```toml
+/// node_types = ["DEPLOYMENT"]
+/// edge_types = ["CORRELATES_WITH"]
+/// ```
+#[derive(Deserialize)]
+struct RegistryFile {
+    #[serde(default)]
+    node_types: Vec<String>,
+    #[serde(default)]
+    edge_types: Vec<String>,
+}
+
+/// The node and edge kinds the server accepts, by name. Always contains the built-in
+/// kinds; a registry file can add more without a proto or code change.
+#[derive(Debug, Clone)]
+pub struct TypeRegistry {
+    node_types: BTreeSet<NodeType>,
+    edge_types: BTreeSet<EdgeType>,
+}
+
+impl Default for TypeRegistry {
+    fn default() -> Self {
+        Self {
+            node_types: NodeType::BUILTIN.into_iter().collect(),
+            edge_types: EdgeType::BUILTIN.into_iter().collect(),
+        }
+    }
+}
+
+impl TypeRegistry {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RegistryError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|source| RegistryError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::from_toml(&text)
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, RegistryError> {
+        let file: RegistryFile = toml::from_str(text)?;
+        let mut registry = Self::default();
+        for name in file.node_types {
+            check_name(&name)?;
+            registry.node_types.insert(NodeType::from_name(&name));
+        }
+        for name in file.edge_types {
+            check_name(&name)?;
+            registry.edge_types.insert(EdgeType::from_name(&name));
+        }
+        Ok(registry)
+    }
+
+    /// Resolves a registered node kind by name.
+    pub fn node_type(&self, name: &str) -> Result<NodeType, UnknownNodeType> {
+        let node_type = NodeType::from_name(name);
+        if self.node_types.contains(&node_type) {
+            Ok(node_type)
+        } else {
+            Err(UnknownNodeType(name.to_string()))
+        }
+    }
+
+    /// Resolves a registered edge kind by name.
+    pub fn edge_type(&self, name: &str) -> Result<EdgeType, UnknownEdgeType> {
+        let edge_type = EdgeType::from_name(name);
+        if self.edge_types.contains(&edge_type) {
+            Ok(edge_type)
+        } else {
+            Err(UnknownEdgeType(name.to_string()))
+        }
+    }
+}
+
+fn check_name(name: &str) -> Result<(), RegistryError> {
+    let well_formed = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+    if well_formed && name != "PLACEHOLDER" {
+        Ok(())
+    } else {
+        Err(RegistryError::InvalidName(name.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_are_builtin() {
+        let registry = TypeRegistry::default();
+        assert_eq!(
+            registry.node_type("MECHANISM").unwrap(),
+            NodeType::Mechanism
+        );
+        assert_eq!(
+            registry.edge_type("MANIFESTS_AS").unwrap(),
+            EdgeType::ManifestsAs
+        );
+        assert!(registry.node_type("DEPLOYMENT").is_err());
+        assert!(registry.node_type("PLACEHOLDER").is_err());
+    }
+
+    #[test]
+    fn file_adds_kinds() {
+        let registry = TypeRegistry::from_toml(
+            r#"
+            node_types = ["DEPLOYMENT"]
+            edge_types = ["CORRELATES_WITH"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            registry.node_type("DEPLOYMENT").unwrap(),
+            NodeType::Registered("DEPLOYMENT".into())
+        );
+        assert_eq!(
+            registry.edge_type("CORRELATES_WITH").unwrap(),
+            EdgeType::Registered("CORRELATES_WITH".into())
+        );
+        assert!(registry.node_type("SERVICE").is_ok());
+    }
+
+    #[test]
+    fn example_registry_loads() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("config/types.toml");
+        let registry = TypeRegistry::load(path).unwrap();
+        assert!(registry.node_type("DEPLOYMENT").is_ok());
+        assert!(registry.edge_type("CORRELATES_WITH").is_ok());
+    }
+
+    #[test]
+    fn malformed_or_reserved_names_rejected() {
+        for text in [
+            r#"node_types = ["deployment"]"#,
+            r#"node_types = [""]"#,
+            r#"node_types = ["PLACEHOLDER"]"#,
+        ] {
+            assert!(matches!(
+                TypeRegistry::from_toml(text),
+                Err(RegistryError::InvalidName(_))
+            ));
+        }
+    }
+}
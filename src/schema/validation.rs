@@ -1,4 +1,14 @@
+use std::collections::BTreeMap;
+
+use crate::domain::edge::EdgeKey;
+use crate::domain::edge_type::EdgeType;
+use crate::domain::node_type::NodeType;
 use crate::proto;
+use crate::proto_convert::{edge_type_of, node_type_of};
+use crate::store::neighborhood::MAX_HOPS;
+
+use super::causal::CausalSchema;
+use super::registry::TypeRegistry;
 
 #[derive(Debug, thiserror::Error)]
 pub enum ValidationError {
@@ -6,6 +16,12 @@ pub enum ValidationError {
     EmptyNodeId,
     #[error("node type must be specified (got UNSPECIFIED)")]
     UnspecifiedNodeType,
+    #[error("node kind {0:?} is not in the type registry")]
+    UnregisteredNodeType(String),
+    #[error("edge kind {0:?} is not in the type registry")]
+    UnregisteredEdgeType(String),
+    #[error("placeholder nodes are created by Tee and cannot be written directly")]
+    PlaceholderNodeType,
     #[error("node label must not be empty")]
     EmptyNodeLabel,
     #[error("edge source must not be empty")]
@@ -24,8 +40,48 @@ pub enum ValidationError {
     EmptyProvenanceTrigger,
     #[error("incident id must not be empty")]
     EmptyIncidentId,
+    #[error("at least one node id to confirm is required")]
+    EmptyConfirmationSet,
     #[error("at least one tombstone entry is required")]
     EmptyTombstoneSet,
+    #[error("rationale evidence URIs must not be empty")]
+    EmptyEvidenceUri,
+    #[error("rationale attribute keys must not be empty")]
+    EmptyRationaleAttributeKey,
+    #[error("at least one seed node id is required")]
+    EmptySeedSet,
+    #[error("at least one start and one end node id are required")]
+    EmptyPathEnds,
+    #[error("max_depth must be at least 1")]
+    ZeroPathDepth,
+    #[error("hops must be at most {}", MAX_HOPS)]
+    TooManyHops,
+    #[error("max_depth must be at most {}", MAX_HOPS)]
+    PathTooDeep,
+    #[error("at least one symptom node id is required")]
+    EmptySymptomSet,
+    #[error("edge type {0} is not permitted by the causal schema")]
+    EdgeTypeNotPermitted(EdgeType),
+    #[error(
+        "edge {source_id:?} -> {target_id:?} is not permitted by the causal schema \
+         ({source_type} -{edge_type}-> {target_type})"
+    )]
+    EdgeEndpointsNotPermitted {
+        source_id: String,
+        target_id: String,
+        source_type: NodeType,
+        edge_type: EdgeType,
+        target_type: NodeType,
+    },
+    #[error(
+        "edge {source_id:?} -> {target_id:?} cannot be checked against the causal schema: \
+         node {node_id:?} has no type yet"
+    )]
+    UntypedEndpoint {
+        source_id: String,
+        target_id: String,
+        node_id: String,
+    },
 }
 
 pub fn validate_provenance(prov: &proto::Provenance) -> Result<(), ValidationError> {
@@ -38,13 +94,48 @@ pub fn validate_provenance(prov: &proto::Provenance) -> Result<(), ValidationErr
     Ok(())
 }
 
-pub fn validate_node(node: &proto::Node) -> Result<(), ValidationError> {
-    if node.id.is_empty() {
-        return Err(ValidationError::EmptyNodeId);
+/// Source and trigger are taken from the request provenance, so only the content is
+/// checked.
+fn validate_rationale(rationale: &proto::EliminationRationale) -> Result<(), ValidationError> {
+    if rationale.evidence_uris.iter().any(String::is_empty) {
+        return Err(ValidationError::EmptyEvidenceUri);
     }
-    if node.r#type == proto::NodeType::Unspecified as i32 {
+    if rationale.attributes.keys().any(String::is_empty) {
+        return Err(ValidationError::EmptyRationaleAttributeKey);
+    }
+    Ok(())
+}
+
+/// A named `kind` must be registered; otherwise the enum `type` must be specified.
+fn validate_node_type(node: &proto::Node, registry: &TypeRegistry) -> Result<(), ValidationError> {
+    if !node.kind.is_empty() {
+        registry
+            .node_type(&node.kind)
+            .map_err(|e| ValidationError::UnregisteredNodeType(e.0))?;
+    } else if node.r#type == proto::NodeType::Unspecified as i32 {
         return Err(ValidationError::UnspecifiedNodeType);
+    } else if node.r#type == proto::NodeType::Placeholder as i32 {
+        return Err(ValidationError::PlaceholderNodeType);
+    }
+    Ok(())
+}
+
+fn validate_edge_type(r#type: i32, kind: &str, registry: &TypeRegistry) -> Result<(), ValidationError> {
+    if !kind.is_empty() {
+        registry
+            .edge_type(kind)
+            .map_err(|e| ValidationError::UnregisteredEdgeType(e.0))?;
+    } else if r#type == proto::EdgeType::Unspecified as i32 {
+        return Err(ValidationError::UnspecifiedEdgeType);
+    }
+    Ok(())
+}
+
+pub fn validate_node(node: &proto::Node, registry: &TypeRegistry) -> Result<(), ValidationError> {
+    if node.id.is_empty() {
+        return Err(ValidationError::EmptyNodeId);
     }
+    validate_node_type(node, registry)?;
     if node.label.is_empty() {
         return Err(ValidationError::EmptyNodeLabel);
     }
@@ -57,7 +148,7 @@ pub fn validate_node(node: &proto::Node) -> Result<(), ValidationError> {
     Ok(())
 }
 
-pub fn validate_edge(edge: &proto::Edge) -> Result<(), ValidationError> {
+pub fn validate_edge(edge: &proto::Edge, registry: &TypeRegistry) -> Result<(), ValidationError> {
     if edge.source.is_empty() {
         return Err(ValidationError::EmptyEdgeSource);
     }
@@ -67,9 +158,7 @@ pub fn validate_edge(edge: &proto::Edge) -> Result<(), ValidationError> {
     if edge.source == edge.target {
         return Err(ValidationError::SelfLoop(edge.source.clone()));
     }
-    if edge.r#type == proto::EdgeType::Unspecified as i32 {
-        return Err(ValidationError::UnspecifiedEdgeType);
-    }
+    validate_edge_type(edge.r#type, &edge.kind, registry)?;
     if edge.provenance.is_empty() {
         return Err(ValidationError::MissingProvenance);
     }
@@ -79,16 +168,127 @@ pub fn validate_edge(edge: &proto::Edge) -> Result<(), ValidationError> {
     Ok(())
 }
 
-pub fn validate_hypothesis_delta(delta: &proto::HypothesisDelta) -> Result<(), ValidationError> {
+pub fn validate_hypothesis_delta(
+    delta: &proto::HypothesisDelta,
+    registry: &TypeRegistry,
+) -> Result<(), ValidationError> {
     for node in &delta.nodes {
-        validate_node(node)?;
+        validate_node(node, registry)?;
     }
     for edge in &delta.edges {
-        validate_edge(edge)?;
+        validate_edge(edge, registry)?;
     }
     Ok(())
 }
 
+/// Ids of the nodes in `delta` that have no real type in `stored` yet — new, or still
+/// placeholders — so the delta gives them their first one.
+pub fn first_typed_nodes(
+    delta: &proto::HypothesisDelta,
+    stored: &BTreeMap<String, NodeType>,
+) -> Vec<String> {
+    delta
+        .nodes
+        .iter()
+        .filter(|n| {
+            stored
+                .get(&n.id)
+                .is_none_or(|t| *t == NodeType::Placeholder)
+        })
+        .map(|n| n.id.clone())
+        .collect()
+}
+
+/// Checks each edge in `delta` against the causal schema. An endpoint's type is taken
+/// from `stored` (the main graph, where the first write wins) or else from the delta's
+/// own nodes.
+///
+/// An endpoint with no known type — dangling, or still a placeholder — is rejected
+/// under the `REJECT` policy. Under `REPORT` and `PLACEHOLDER` the edge is stored
+/// unchecked, and checked instead by the delta that gives the endpoint its first real
+/// type: `stored_edges` are the main-graph edges touching the
+/// [`first_typed_nodes`] of `delta`, and `stored` also holds their endpoints' types.
+///
+/// Stores call this with their write lock held, so neither can change before the
+/// delta is applied.
+pub fn validate_edge_schema(
+    delta: &proto::HypothesisDelta,
+    schema: &CausalSchema,
+    stored: &BTreeMap<String, NodeType>,
+    stored_edges: &[EdgeKey],
+) -> Result<(), ValidationError> {
+    let in_delta: BTreeMap<&str, NodeType> = delta
+        .nodes
+        .iter()
+        .filter_map(|n| Some((n.id.as_str(), node_type_of(n.r#type, &n.kind).ok()?)))
+        .collect();
+    let type_of = |id: &str| {
+        stored
+            .get(id)
+            .filter(|t| **t != NodeType::Placeholder)
+            .or_else(|| in_delta.get(id))
+            .cloned()
+    };
+    let check = |source_id: &str, edge_type: EdgeType, target_id: &str| {
+        let (Some(source_type), Some(target_type)) = (type_of(source_id), type_of(target_id))
+        else {
+            return Ok(());
+        };
+        if schema.allows(&source_type, &edge_type, &target_type) {
+            return Ok(());
+        }
+        Err(ValidationError::EdgeEndpointsNotPermitted {
+            source_id: source_id.to_string(),
+            target_id: target_id.to_string(),
+            source_type,
+            edge_type,
+            target_type,
+        })
+    };
+    let defer_untyped = delta.dangling_edge_policy() != proto::DanglingEdgePolicy::Reject;
+
+    for edge in &delta.edges {
+        let Ok(edge_type) = edge_type_of(edge.r#type, &edge.kind) else {
+            continue;
+        };
+        if !schema.allows_edge_type(&edge_type) {
+            return Err(ValidationError::EdgeTypeNotPermitted(edge_type));
+        }
+        let untyped = [&edge.source, &edge.target]
+            .into_iter()
+            .find(|id| type_of(id).is_none());
+        match untyped {
+            Some(node_id) if !defer_untyped => {
+                return Err(ValidationError::UntypedEndpoint {
+                    source_id: edge.source.clone(),
+                    target_id: edge.target.clone(),
+                    node_id: node_id.clone(),
+                });
+            }
+            _ => check(&edge.source, edge_type, &edge.target)?,
+        }
+    }
+    for key in stored_edges {
+        check(&key.source, key.edge_type.clone(), &key.target)?;
+    }
+    Ok(())
+}
+
+pub fn validate_confirm_nodes_request(
+    req: &proto::ConfirmNodesRequest,
+) -> Result<(), ValidationError> {
+    if req.node_ids.is_empty() {
+        return Err(ValidationError::EmptyConfirmationSet);
+    }
+    if req.node_ids.iter().any(String::is_empty) {
+        return Err(ValidationError::EmptyNodeId);
+    }
+    match &req.provenance {
+        Some(prov) => validate_provenance(prov),
+        None => Err(ValidationError::MissingProvenance),
+    }
+}
+
 pub fn validate_node_tombstone_request(
     req: &proto::NodeTombstoneRequest,
 ) -> Result<(), ValidationError> {
@@ -102,11 +302,15 @@ pub fn validate_node_tombstone_request(
         Some(prov) => validate_provenance(prov)?,
         None => return Err(ValidationError::MissingProvenance),
     }
+    if let Some(rationale) = &req.rationale {
+        validate_rationale(rationale)?;
+    }
     Ok(())
 }
 
 pub fn validate_edge_tombstone_request(
     req: &proto::EdgeTombstoneRequest,
+    registry: &TypeRegistry,
 ) -> Result<(), ValidationError> {
     if req.incident_id.is_empty() {
         return Err(ValidationError::EmptyIncidentId);
@@ -118,6 +322,9 @@ pub fn validate_edge_tombstone_request(
         Some(prov) => validate_provenance(prov)?,
         None => return Err(ValidationError::MissingProvenance),
     }
+    if let Some(rationale) = &req.rationale {
+        validate_rationale(rationale)?;
+    }
     for entry in &req.entries {
         if entry.source.is_empty() {
             return Err(ValidationError::EmptyEdgeSource);
@@ -125,9 +332,7 @@ pub fn validate_edge_tombstone_request(
         if entry.target.is_empty() {
             return Err(ValidationError::EmptyEdgeTarget);
         }
-        if entry.r#type == proto::EdgeType::Unspecified as i32 {
-            return Err(ValidationError::UnspecifiedEdgeType);
-        }
+        validate_edge_type(entry.r#type, &entry.kind, registry)?;
     }
     Ok(())
 }
@@ -139,6 +344,93 @@ pub fn validate_incident_id(incident_id: &str) -> Result<(), ValidationError> {
     Ok(())
 }
 
+/// Kinds named in a read filter must be registered. `PLACEHOLDER` may be named too,
+/// to find the nodes Tee created for dangling edges.
+pub fn validate_graph_filter(
+    filter: &proto::GraphFilter,
+    registry: &TypeRegistry,
+) -> Result<(), ValidationError> {
+    for name in &filter.node_types {
+        if *name != NodeType::Placeholder.to_string() {
+            registry
+                .node_type(name)
+                .map_err(|e| ValidationError::UnregisteredNodeType(e.0))?;
+        }
+    }
+    for name in &filter.edge_types {
+        registry
+            .edge_type(name)
+            .map_err(|e| ValidationError::UnregisteredEdgeType(e.0))?;
+    }
+    Ok(())
+}
+
+pub fn validate_neighborhood_request(
+    req: &proto::NeighborhoodRequest,
+    registry: &TypeRegistry,
+) -> Result<(), ValidationError> {
+    validate_incident_id(&req.incident_id)?;
+    if req.node_ids.is_empty() {
+        return Err(ValidationError::EmptySeedSet);
+    }
+    if req.node_ids.iter().any(String::is_empty) {
+        return Err(ValidationError::EmptyNodeId);
+    }
+    if req.hops > MAX_HOPS {
+        return Err(ValidationError::TooManyHops);
+    }
+    for name in &req.edge_types {
+        registry
+            .edge_type(name)
+            .map_err(|e| ValidationError::UnregisteredEdgeType(e.0))?;
+    }
+    Ok(())
+}
+
+pub fn validate_path_request(
+    req: &proto::PathRequest,
+    registry: &TypeRegistry,
+) -> Result<(), ValidationError> {
+    validate_incident_id(&req.incident_id)?;
+    if req.from_node_ids.is_empty() || req.to_node_ids.is_empty() {
+        return Err(ValidationError::EmptyPathEnds);
+    }
+    if req
+        .from_node_ids
+        .iter()
+        .chain(&req.to_node_ids)
+        .any(String::is_empty)
+    {
+        return Err(ValidationError::EmptyNodeId);
+    }
+    if req.max_depth == 0 {
+        return Err(ValidationError::ZeroPathDepth);
+    }
+    if req.max_depth > MAX_HOPS {
+        return Err(ValidationError::PathTooDeep);
+    }
+    for name in &req.edge_types {
+        registry
+            .edge_type(name)
+            .map_err(|e| ValidationError::UnregisteredEdgeType(e.0))?;
+    }
+    Ok(())
+}
+
+pub fn validate_root_cause_request(req: &proto::RootCauseRequest) -> Result<(), ValidationError> {
+    validate_incident_id(&req.incident_id)?;
+    if req.symptom_node_ids.is_empty() {
+        return Err(ValidationError::EmptySymptomSet);
+    }
+    if req.symptom_node_ids.iter().any(String::is_empty) {
+        return Err(ValidationError::EmptyNodeId);
+    }
+    if req.max_depth == 0 {
+        return Err(ValidationError::ZeroPathDepth);
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -147,6 +439,7 @@ mod tests {
         proto::Provenance {
             source: "agent-1".into(),
             timestamp: None,
+            version: 0,
             trigger: "alert-fired".into(),
         }
     }
@@ -158,6 +451,7 @@ mod tests {
             label: "api-gateway".into(),
             hypothetical: true,
             provenance: vec![valid_provenance()],
+            kind: String::new(),
         }
     }
 
@@ -167,6 +461,7 @@ mod tests {
             target: "node-2".into(),
             r#type: proto::EdgeType::DependsOn as i32,
             provenance: vec![valid_provenance()],
+            kind: String::new(),
         }
     }
 
@@ -174,7 +469,7 @@ mod tests {
 
     #[test]
     fn valid_node_passes() {
-        assert!(validate_node(&valid_node()).is_ok());
+        assert!(validate_node(&valid_node(), &TypeRegistry::default()).is_ok());
     }
 
     #[test]
@@ -182,7 +477,7 @@ mod tests {
         let mut n = valid_node();
         n.id = "".into();
         assert!(matches!(
-            validate_node(&n),
+            validate_node(&n, &TypeRegistry::default()),
             Err(ValidationError::EmptyNodeId)
         ));
     }
@@ -192,17 +487,50 @@ mod tests {
         let mut n = valid_node();
         n.r#type = proto::NodeType::Unspecified as i32;
         assert!(matches!(
-            validate_node(&n),
+            validate_node(&n, &TypeRegistry::default()),
             Err(ValidationError::UnspecifiedNodeType)
         ));
     }
 
+    #[test]
+    fn placeholder_node_type_rejected() {
+        let mut n = valid_node();
+        n.r#type = proto::NodeType::Placeholder as i32;
+        assert!(matches!(
+            validate_node(&n, &TypeRegistry::default()),
+            Err(ValidationError::PlaceholderNodeType)
+        ));
+    }
+
+    #[test]
+    fn registered_node_kind_passes() {
+        let registry = TypeRegistry::from_toml(r#"node_types = ["DEPLOYMENT"]"#).unwrap();
+        let mut n = valid_node();
+        n.r#type = proto::NodeType::Unspecified as i32;
+        n.kind = "DEPLOYMENT".into();
+        assert!(validate_node(&n, &registry).is_ok());
+        assert!(matches!(
+            validate_node(&n, &TypeRegistry::default()),
+            Err(ValidationError::UnregisteredNodeType(kind)) if kind == "DEPLOYMENT"
+        ));
+    }
+
+    #[test]
+    fn placeholder_kind_rejected() {
+        let mut n = valid_node();
+        n.kind = "PLACEHOLDER".into();
+        assert!(matches!(
+            validate_node(&n, &TypeRegistry::default()),
+            Err(ValidationError::UnregisteredNodeType(_))
+        ));
+    }
+
     #[test]
     fn empty_node_label_rejected() {
         let mut n = valid_node();
         n.label = "".into();
         assert!(matches!(
-            validate_node(&n),
+            validate_node(&n, &TypeRegistry::default()),
             Err(ValidationError::EmptyNodeLabel)
         ));
     }
@@ -212,7 +540,7 @@ mod tests {
         let mut n = valid_node();
         n.provenance.clear();
         assert!(matches!(
-            validate_node(&n),
+            validate_node(&n, &TypeRegistry::default()),
             Err(ValidationError::MissingProvenance)
         ));
     }
@@ -222,7 +550,7 @@ mod tests {
         let mut n = valid_node();
         n.provenance[0].source = "".into();
         assert!(matches!(
-            validate_node(&n),
+            validate_node(&n, &TypeRegistry::default()),
             Err(ValidationError::EmptyProvenanceSource)
         ));
     }
@@ -231,7 +559,7 @@ mod tests {
 
     #[test]
     fn valid_edge_passes() {
-        assert!(validate_edge(&valid_edge()).is_ok());
+        assert!(validate_edge(&valid_edge(), &TypeRegistry::default()).is_ok());
     }
 
     #[test]
@@ -239,7 +567,7 @@ mod tests {
         let mut e = valid_edge();
         e.source = "".into();
         assert!(matches!(
-            validate_edge(&e),
+            validate_edge(&e, &TypeRegistry::default()),
             Err(ValidationError::EmptyEdgeSource)
         ));
     }
@@ -249,29 +577,174 @@ mod tests {
         let mut e = valid_edge();
         e.target = e.source.clone();
         assert!(matches!(
-            validate_edge(&e),
+            validate_edge(&e, &TypeRegistry::default()),
             Err(ValidationError::SelfLoop(_))
         ));
     }
 
+    #[test]
+    fn unregistered_edge_kind_rejected() {
+        let mut e = valid_edge();
+        e.kind = "CORRELATES_WITH".into();
+        assert!(matches!(
+            validate_edge(&e, &TypeRegistry::default()),
+            Err(ValidationError::UnregisteredEdgeType(_))
+        ));
+        let registry = TypeRegistry::from_toml(r#"edge_types = ["CORRELATES_WITH"]"#).unwrap();
+        assert!(validate_edge(&e, &registry).is_ok());
+    }
+
     #[test]
     fn unspecified_edge_type_rejected() {
         let mut e = valid_edge();
         e.r#type = proto::EdgeType::Unspecified as i32;
         assert!(matches!(
-            validate_edge(&e),
+            validate_edge(&e, &TypeRegistry::default()),
             Err(ValidationError::UnspecifiedEdgeType)
         ));
     }
 
     // --- Tombstone validation ---
 
+    fn schema() -> CausalSchema {
+        CausalSchema::new([(
+            NodeType::Mechanism,
+            EdgeType::ManifestsAs,
+            NodeType::Service,
+        )])
+    }
+
+    fn schema_delta(source_type: proto::NodeType) -> proto::HypothesisDelta {
+        let mut mechanism = valid_node();
+        mechanism.id = "m".into();
+        mechanism.r#type = source_type as i32;
+        let mut edge = valid_edge();
+        edge.source = "m".into();
+        edge.target = "svc".into();
+        edge.r#type = proto::EdgeType::ManifestsAs as i32;
+        proto::HypothesisDelta {
+            nodes: vec![mechanism],
+            edges: vec![edge],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn permitted_triple_passes() {
+        let stored = BTreeMap::from([("svc".to_string(), NodeType::Service)]);
+        let delta = schema_delta(proto::NodeType::Mechanism);
+        assert!(validate_edge_schema(&delta, &schema(), &stored, &[]).is_ok());
+    }
+
+    #[test]
+    fn forbidden_triple_rejected() {
+        let stored = BTreeMap::from([("svc".to_string(), NodeType::Service)]);
+        let delta = schema_delta(proto::NodeType::Dependency);
+        assert!(matches!(
+            validate_edge_schema(&delta, &schema(), &stored, &[]),
+            Err(ValidationError::EdgeEndpointsNotPermitted {
+                source_type: NodeType::Dependency,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn stored_type_wins_over_delta_type() {
+        let stored = BTreeMap::from([
+            ("m".to_string(), NodeType::Service),
+            ("svc".to_string(), NodeType::Service),
+        ]);
+        let delta = schema_delta(proto::NodeType::Mechanism);
+        assert!(validate_edge_schema(&delta, &schema(), &stored, &[]).is_err());
+    }
+
+    #[test]
+    fn untyped_endpoint_rejected() {
+        let mut delta = schema_delta(proto::NodeType::Mechanism);
+        delta.set_dangling_edge_policy(proto::DanglingEdgePolicy::Reject);
+        assert!(matches!(
+            validate_edge_schema(&delta, &schema(), &BTreeMap::new(), &[]),
+            Err(ValidationError::UntypedEndpoint { node_id, .. }) if node_id == "svc"
+        ));
+
+        // A placeholder has no type of its own either
+        let stored = BTreeMap::from([("svc".to_string(), NodeType::Placeholder)]);
+        assert!(matches!(
+            validate_edge_schema(&delta, &schema(), &stored, &[]),
+            Err(ValidationError::UntypedEndpoint { .. })
+        ));
+    }
+
+    #[test]
+    fn untyped_endpoint_checked_once_typed() {
+        // Under REPORT and PLACEHOLDER the edge is left for the endpoint's first write
+        for policy in [
+            proto::DanglingEdgePolicy::Report,
+            proto::DanglingEdgePolicy::Placeholder,
+        ] {
+            let mut delta = schema_delta(proto::NodeType::Mechanism);
+            delta.set_dangling_edge_policy(policy);
+            let stored = BTreeMap::from([("svc".to_string(), NodeType::Placeholder)]);
+            assert!(validate_edge_schema(&delta, &schema(), &stored, &[]).is_ok());
+        }
+
+        let stored_edges = [EdgeKey::new("m", "svc", EdgeType::ManifestsAs)];
+        let stored = BTreeMap::from([
+            ("m".to_string(), NodeType::Mechanism),
+            ("svc".to_string(), NodeType::Placeholder),
+        ]);
+        let mut svc = valid_node();
+        svc.id = "svc".into();
+        let typed_as = |node_type: proto::NodeType| proto::HypothesisDelta {
+            nodes: vec![proto::Node {
+                r#type: node_type as i32,
+                ..svc.clone()
+            }],
+            ..Default::default()
+        };
+        assert_eq!(
+            first_typed_nodes(&typed_as(proto::NodeType::Service), &stored),
+            ["svc"]
+        );
+        assert!(validate_edge_schema(
+            &typed_as(proto::NodeType::Service),
+            &schema(),
+            &stored,
+            &stored_edges
+        )
+        .is_ok());
+        assert!(matches!(
+            validate_edge_schema(
+                &typed_as(proto::NodeType::Dependency),
+                &schema(),
+                &stored,
+                &stored_edges
+            ),
+            Err(ValidationError::EdgeEndpointsNotPermitted {
+                target_type: NodeType::Dependency,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn edge_type_without_rules_rejected() {
+        let mut delta = schema_delta(proto::NodeType::Mechanism);
+        delta.edges[0].r#type = proto::EdgeType::DependsOn as i32;
+        assert!(matches!(
+            validate_edge_schema(&delta, &schema(), &BTreeMap::new(), &[]),
+            Err(ValidationError::EdgeTypeNotPermitted(EdgeType::DependsOn))
+        ));
+    }
+
     #[test]
     fn valid_node_tombstone_passes() {
         let req = proto::NodeTombstoneRequest {
             incident_id: "inc-1".into(),
             node_ids: vec!["n1".into()],
             provenance: Some(valid_provenance()),
+            rationale: None,
         };
         assert!(validate_node_tombstone_request(&req).is_ok());
     }
@@ -282,6 +755,7 @@ mod tests {
             incident_id: "".into(),
             node_ids: vec!["n1".into()],
             provenance: Some(valid_provenance()),
+            rationale: None,
         };
         assert!(matches!(
             validate_node_tombstone_request(&req),
@@ -295,6 +769,7 @@ mod tests {
             incident_id: "inc-1".into(),
             node_ids: vec![],
             provenance: Some(valid_provenance()),
+            rationale: None,
         };
         assert!(matches!(
             validate_node_tombstone_request(&req),
@@ -308,10 +783,187 @@ mod tests {
             incident_id: "inc-1".into(),
             node_ids: vec!["n1".into()],
             provenance: None,
+            rationale: None,
         };
         assert!(matches!(
             validate_node_tombstone_request(&req),
             Err(ValidationError::MissingProvenance)
         ));
     }
+
+    #[test]
+    fn confirm_nodes_needs_ids_and_provenance() {
+        let req = proto::ConfirmNodesRequest {
+            node_ids: vec!["n1".into()],
+            provenance: Some(valid_provenance()),
+        };
+        assert!(validate_confirm_nodes_request(&req).is_ok());
+
+        let cases = [
+            (vec![], Some(valid_provenance())),
+            (vec![String::new()], Some(valid_provenance())),
+            (vec!["n1".to_string()], None),
+        ];
+        for (node_ids, provenance) in cases {
+            let req = proto::ConfirmNodesRequest {
+                node_ids,
+                provenance,
+            };
+            assert!(validate_confirm_nodes_request(&req).is_err());
+        }
+    }
+
+    #[test]
+    fn tombstone_rationale_needs_evidence_and_attribute_keys() {
+        let with_rationale = |rationale: proto::EliminationRationale| proto::NodeTombstoneRequest {
+            incident_id: "inc-1".into(),
+            node_ids: vec!["n1".into()],
+            provenance: Some(valid_provenance()),
+            rationale: Some(rationale),
+        };
+        let rationale = proto::EliminationRationale {
+            text: "no errors in the window".into(),
+            evidence_uris: vec!["https://logs.example/q/1".into()],
+            attributes: [("window".to_string(), "15m".to_string())].into(),
+            ..Default::default()
+        };
+        assert!(validate_node_tombstone_request(&with_rationale(rationale.clone())).is_ok());
+
+        let empty_uri = proto::EliminationRationale {
+            evidence_uris: vec![String::new()],
+            ..rationale.clone()
+        };
+        assert!(matches!(
+            validate_node_tombstone_request(&with_rationale(empty_uri)),
+            Err(ValidationError::EmptyEvidenceUri)
+        ));
+        let empty_key = proto::EliminationRationale {
+            attributes: [(String::new(), "15m".to_string())].into(),
+            ..rationale
+        };
+        assert!(matches!(
+            validate_node_tombstone_request(&with_rationale(empty_key)),
+            Err(ValidationError::EmptyRationaleAttributeKey)
+        ));
+    }
+
+    #[test]
+    fn graph_filter_kinds_must_be_registered() {
+        let registry = TypeRegistry::default();
+        let filter = proto::GraphFilter {
+            node_types: vec!["SERVICE".into(), "PLACEHOLDER".into()],
+            edge_types: vec!["DEPENDS_ON".into()],
+            ..Default::default()
+        };
+        assert!(validate_graph_filter(&filter, &registry).is_ok());
+
+        let unknown = proto::GraphFilter {
+            node_types: vec!["DEPLOYMENT".into()],
+            ..Default::default()
+        };
+        assert!(matches!(
+            validate_graph_filter(&unknown, &registry),
+            Err(ValidationError::UnregisteredNodeType(kind)) if kind == "DEPLOYMENT"
+        ));
+    }
+
+    #[test]
+    fn neighborhood_request_needs_seeds_and_registered_kinds() {
+        let registry = TypeRegistry::default();
+        let req = proto::NeighborhoodRequest {
+            incident_id: "inc-1".into(),
+            node_ids: vec!["n1".into()],
+            edge_types: vec!["DEPENDS_ON".into()],
+            ..Default::default()
+        };
+        assert!(validate_neighborhood_request(&req, &registry).is_ok());
+
+        let no_seeds = proto::NeighborhoodRequest {
+            node_ids: vec![],
+            ..req.clone()
+        };
+        assert!(matches!(
+            validate_neighborhood_request(&no_seeds, &registry),
+            Err(ValidationError::EmptySeedSet)
+        ));
+
+        let too_far = proto::NeighborhoodRequest {
+            hops: MAX_HOPS + 1,
+            ..req.clone()
+        };
+        assert!(matches!(
+            validate_neighborhood_request(&too_far, &registry),
+            Err(ValidationError::TooManyHops)
+        ));
+
+        let unknown = proto::NeighborhoodRequest {
+            edge_types: vec!["CORRELATES_WITH".into()],
+            ..req
+        };
+        assert!(matches!(
+            validate_neighborhood_request(&unknown, &registry),
+            Err(ValidationError::UnregisteredEdgeType(kind)) if kind == "CORRELATES_WITH"
+        ));
+    }
+
+    #[test]
+    fn path_request_needs_both_ends_and_a_bounded_depth() {
+        let registry = TypeRegistry::default();
+        let req = proto::PathRequest {
+            incident_id: "inc-1".into(),
+            from_node_ids: vec!["symptom".into()],
+            to_node_ids: vec!["oom".into()],
+            max_depth: 4,
+            edge_types: vec!["MANIFESTS_AS".into()],
+            ..Default::default()
+        };
+        assert!(validate_path_request(&req, &registry).is_ok());
+
+        let no_ends = proto::PathRequest {
+            to_node_ids: vec![],
+            ..req.clone()
+        };
+        assert!(matches!(
+            validate_path_request(&no_ends, &registry),
+            Err(ValidationError::EmptyPathEnds)
+        ));
+
+        let no_depth = proto::PathRequest {
+            max_depth: 0,
+            ..req.clone()
+        };
+        assert!(matches!(
+            validate_path_request(&no_depth, &registry),
+            Err(ValidationError::ZeroPathDepth)
+        ));
+
+        let too_deep = proto::PathRequest {
+            max_depth: MAX_HOPS + 1,
+            ..req
+        };
+        assert!(matches!(
+            validate_path_request(&too_deep, &registry),
+            Err(ValidationError::PathTooDeep)
+        ));
+    }
+
+    #[test]
+    fn root_cause_request_needs_symptoms() {
+        let req = proto::RootCauseRequest {
+            incident_id: "inc-1".into(),
+            symptom_node_ids: vec!["latency".into()],
+            max_depth: 3,
+            ..Default::default()
+        };
+        assert!(validate_root_cause_request(&req).is_ok());
+
+        let no_symptoms = proto::RootCauseRequest {
+            symptom_node_ids: vec![],
+            ..req
+        };
+        assert!(matches!(
+            validate_root_cause_request(&no_symptoms),
+            Err(ValidationError::EmptySymptomSet)
+        ));
+    }
 }
@@ -1,30 +1,140 @@
+use std::future::Future;
 use std::sync::Arc;
 
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Request, Response, Status};
 
+use crate::auth::{Action, Principal, Principals};
+use crate::metrics::Metrics;
+use crate::paths::{self, PathQuery};
 use crate::proto::tee_server::Tee;
 use crate::proto::{
-    CausalGraph, CreateIncidentRequest, CreateIncidentResult, EdgeTombstoneRequest,
-    HypothesisDelta, HypothesisMergeResult, IncidentContext, IncidentContextRequest,
-    LiveViewRequest, NodeTombstoneRequest, TombstoneMergeResult, TombstoneRequest, TombstoneSet,
+    CausalGraph, ConfirmNodesRequest, ConfirmNodesResult, CreateIncidentRequest,
+    CreateIncidentResult, DanglingEdgePolicy, EdgeTombstoneRequest, GraphFilter, HypothesisDelta,
+    HypothesisMergeResult, IncidentContext, IncidentContextRequest, IncidentMetrics,
+    IncidentMetricsRequest, LiveViewEvent, LiveViewRequest, MainGraphChange, MainGraphRequest,
+    NeighborhoodRequest, NeighborhoodResult, NodeTombstoneRequest, PathRequest, PathResult,
+    RootCauseRequest, RootCauseResult, SubscribeMainGraphRequest, TombstoneMergeResult,
+    TombstoneRequest, TombstoneSet, WatchLiveViewRequest,
 };
+use crate::root_cause::{self, RootCauseQuery};
+use crate::schema::causal::CausalSchema;
+use crate::schema::registry::TypeRegistry;
 use crate::schema::validation;
-use crate::store::memory::InMemoryStore;
+use crate::store::neighborhood::NeighborhoodQuery;
+use crate::store::query::{GraphQuery, PageToken};
 use crate::store::{Store, StoreError};
+use crate::tls::ClientIdentity;
+use crate::watch::LiveViewWatch;
 
-pub struct TeeService {
-    store: Arc<InMemoryStore>,
+/// Messages buffered per streaming response before the stream stops reading from
+/// the store and waits for the client.
+const WATCH_BUFFER: usize = 64;
+
+/// Page size used by `StreamMainGraph` and `StreamLiveView` when the request sets none.
+const STREAM_CHUNK_SIZE: usize = 1000;
+
+/// Versions `SubscribeMainGraph` reads from the store at a time while catching up.
+const FEED_PAGE_VERSIONS: u64 = 256;
+
+/// gRPC handler for the `Tee` service, generic over the storage backend.
+pub struct TeeService<S> {
+    store: Arc<S>,
+    /// Applied to hypothesis deltas that leave `dangling_edge_policy` unspecified.
+    dangling_edge_policy: DanglingEdgePolicy,
+    /// When set, hypothesis edges must connect permitted endpoint types.
+    schema: Option<CausalSchema>,
+    /// Node and edge kinds accepted by validation.
+    registry: TypeRegistry,
+    /// When set, every request must authenticate as one of these principals.
+    principals: Option<Principals>,
+    /// When set, records what each write did.
+    metrics: Option<Metrics>,
 }
 
-impl TeeService {
-    pub fn new(store: Arc<InMemoryStore>) -> Self {
-        Self { store }
+impl<S: Store> TeeService<S> {
+    pub fn new(store: Arc<S>) -> Self {
+        Self {
+            store,
+            dangling_edge_policy: DanglingEdgePolicy::Report,
+            schema: None,
+            registry: TypeRegistry::default(),
+            principals: None,
+            metrics: None,
+        }
+    }
+
+    pub fn with_registry(mut self, registry: TypeRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    pub fn with_schema(mut self, schema: CausalSchema) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    pub fn with_dangling_edge_policy(mut self, policy: DanglingEdgePolicy) -> Self {
+        self.dangling_edge_policy = policy;
+        self
+    }
+
+    pub fn with_principals(mut self, principals: Principals) -> Self {
+        self.principals = Some(principals);
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Authenticates the caller and checks that it may take `action`. Without
+    /// configured principals every caller may do anything, and there is no principal.
+    fn authorize<T>(
+        &self,
+        request: &Request<T>,
+        action: Action,
+    ) -> Result<Option<&Principal>, Status> {
+        let Some(principals) = &self.principals else {
+            return Ok(None);
+        };
+        let identity = request.extensions().get::<ClientIdentity>();
+        let principal = principals
+            .authenticate(request.metadata(), identity)
+            .map_err(|err| Status::unauthenticated(err.to_string()))?;
+        if !principal.permits(action) {
+            return Err(Status::permission_denied(format!(
+                "{} may not {action}",
+                principal.name
+            )));
+        }
+        Ok(Some(principal))
+    }
+
+    /// Validates and decodes the read parameters of a graph request.
+    fn graph_query(
+        &self,
+        as_of_version: Option<u64>,
+        filter: Option<GraphFilter>,
+        page_size: u32,
+        page_token: &str,
+    ) -> Result<GraphQuery, Status> {
+        if let Some(filter) = &filter {
+            validation::validate_graph_filter(filter, &self.registry)
+                .map_err(validation_error_to_status)?;
+        }
+        GraphQuery::from_request(as_of_version, filter, page_size, page_token)
+            .map_err(|err| Status::invalid_argument(err.to_string()))
     }
 }
 
 fn store_error_to_status(err: StoreError) -> Status {
     match err {
         StoreError::IncidentNotFound(id) => Status::not_found(format!("incident not found: {id}")),
+        err @ StoreError::VersionUnavailable { .. } => Status::out_of_range(err.to_string()),
+        StoreError::SchemaViolation(err) => validation_error_to_status(err),
         StoreError::Backend(msg) => Status::internal(msg),
     }
 }
@@ -33,17 +143,100 @@ fn validation_error_to_status(err: validation::ValidationError) -> Status {
     Status::invalid_argument(err.to_string())
 }
 
+/// Checks that an authenticated caller writes only under its own name.
+fn check_sources<'a>(
+    principal: Option<&Principal>,
+    sources: impl IntoIterator<Item = &'a str>,
+) -> Result<(), Status> {
+    let Some(principal) = principal else {
+        return Ok(());
+    };
+    match sources.into_iter().find(|&source| source != principal.name) {
+        Some(source) => Err(Status::permission_denied(format!(
+            "{} may not write with provenance source {source:?}",
+            principal.name
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Streams `first` and each page after it, reading the next page with `read` until
+/// one comes back without a `next_page_token`.
+fn stream_pages<F, Fut>(
+    first: CausalGraph,
+    mut query: GraphQuery,
+    mut read: F,
+) -> ReceiverStream<Result<CausalGraph, Status>>
+where
+    F: FnMut(GraphQuery) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<CausalGraph, StoreError>> + Send,
+{
+    let (tx, rx) = mpsc::channel(WATCH_BUFFER);
+    tokio::spawn(async move {
+        let mut page = first;
+        loop {
+            let token = std::mem::take(&mut page.next_page_token);
+            if tx.send(Ok(page)).await.is_err() || token.is_empty() {
+                return;
+            }
+            query.page_token = match PageToken::decode(&token) {
+                Ok(token) => Some(token),
+                Err(err) => {
+                    let _ = tx.send(Err(Status::internal(err.to_string()))).await;
+                    return;
+                }
+            };
+            page = match read(query.clone()).await {
+                Ok(page) => page,
+                Err(err) => {
+                    let _ = tx.send(Err(store_error_to_status(err))).await;
+                    return;
+                }
+            };
+        }
+    });
+    ReceiverStream::new(rx)
+}
+
 #[tonic::async_trait]
-impl Tee for TeeService {
+impl<S: Store + 'static> Tee for TeeService<S> {
     async fn merge_hypothesis(
         &self,
         request: Request<HypothesisDelta>,
     ) -> Result<Response<HypothesisMergeResult>, Status> {
-        let delta = request.into_inner();
-        validation::validate_hypothesis_delta(&delta).map_err(validation_error_to_status)?;
+        let principal = self.authorize(&request, Action::Hypothesize)?;
+        let mut delta = request.into_inner();
+        validation::validate_hypothesis_delta(&delta, &self.registry)
+            .map_err(validation_error_to_status)?;
+        let provenance = delta.nodes.iter().flat_map(|n| &n.provenance);
+        let provenance = provenance.chain(delta.edges.iter().flat_map(|e| &e.provenance));
+        check_sources(principal, provenance.map(|p| p.source.as_str()))?;
+        // Resolve the policy here so the store (and its write-ahead log) sees a concrete one
+        if delta.dangling_edge_policy() == DanglingEdgePolicy::Unspecified {
+            delta.set_dangling_edge_policy(self.dangling_edge_policy);
+        }
         let result = self
             .store
-            .merge_hypothesis(delta)
+            .merge_hypothesis(delta, self.schema.as_ref())
+            .await
+            .map_err(store_error_to_status)?;
+        if let Some(metrics) = &self.metrics {
+            metrics.merged(&result);
+        }
+        Ok(Response::new(result))
+    }
+
+    async fn confirm_nodes(
+        &self,
+        request: Request<ConfirmNodesRequest>,
+    ) -> Result<Response<ConfirmNodesResult>, Status> {
+        let principal = self.authorize(&request, Action::Meet)?;
+        let req = request.into_inner();
+        validation::validate_confirm_nodes_request(&req).map_err(validation_error_to_status)?;
+        check_sources(principal, req.provenance.iter().map(|p| p.source.as_str()))?;
+        let result = self
+            .store
+            .confirm_nodes(req)
             .await
             .map_err(store_error_to_status)?;
         Ok(Response::new(result))
@@ -53,12 +246,13 @@ impl Tee for TeeService {
         &self,
         request: Request<CreateIncidentRequest>,
     ) -> Result<Response<CreateIncidentResult>, Status> {
+        self.authorize(&request, Action::Meet)?;
         let req = request.into_inner();
         validation::validate_incident_id(&req.incident_id)
             .map_err(validation_error_to_status)?;
         let result = self
             .store
-            .create_incident(&req.incident_id)
+            .create_incident(req)
             .await
             .map_err(store_error_to_status)?;
         Ok(Response::new(result))
@@ -68,6 +262,7 @@ impl Tee for TeeService {
         &self,
         request: Request<IncidentContextRequest>,
     ) -> Result<Response<IncidentContext>, Status> {
+        self.authorize(&request, Action::Read)?;
         let req = request.into_inner();
         validation::validate_incident_id(&req.incident_id)
             .map_err(validation_error_to_status)?;
@@ -79,18 +274,39 @@ impl Tee for TeeService {
         Ok(Response::new(result))
     }
 
+    async fn get_incident_metrics(
+        &self,
+        request: Request<IncidentMetricsRequest>,
+    ) -> Result<Response<IncidentMetrics>, Status> {
+        self.authorize(&request, Action::Read)?;
+        let req = request.into_inner();
+        validation::validate_incident_id(&req.incident_id)
+            .map_err(validation_error_to_status)?;
+        let result = self
+            .store
+            .get_incident_metrics(&req.incident_id, req.as_of_version)
+            .await
+            .map_err(store_error_to_status)?;
+        Ok(Response::new(result))
+    }
+
     async fn merge_node_tombstones(
         &self,
         request: Request<NodeTombstoneRequest>,
     ) -> Result<Response<TombstoneMergeResult>, Status> {
+        let principal = self.authorize(&request, Action::Meet)?;
         let req = request.into_inner();
         validation::validate_node_tombstone_request(&req)
             .map_err(validation_error_to_status)?;
+        check_sources(principal, req.provenance.iter().map(|p| p.source.as_str()))?;
         let result = self
             .store
             .merge_node_tombstones(req)
             .await
             .map_err(store_error_to_status)?;
+        if let Some(metrics) = &self.metrics {
+            metrics.tombstoned("node", &result);
+        }
         Ok(Response::new(result))
     }
 
@@ -98,14 +314,19 @@ impl Tee for TeeService {
         &self,
         request: Request<EdgeTombstoneRequest>,
     ) -> Result<Response<TombstoneMergeResult>, Status> {
+        let principal = self.authorize(&request, Action::Meet)?;
         let req = request.into_inner();
-        validation::validate_edge_tombstone_request(&req)
+        validation::validate_edge_tombstone_request(&req, &self.registry)
             .map_err(validation_error_to_status)?;
+        check_sources(principal, req.provenance.iter().map(|p| p.source.as_str()))?;
         let result = self
             .store
             .merge_edge_tombstones(req)
             .await
             .map_err(store_error_to_status)?;
+        if let Some(metrics) = &self.metrics {
+            metrics.tombstoned("edge", &result);
+        }
         Ok(Response::new(result))
     }
 
@@ -113,12 +334,15 @@ impl Tee for TeeService {
         &self,
         request: Request<LiveViewRequest>,
     ) -> Result<Response<CausalGraph>, Status> {
+        self.authorize(&request, Action::Read)?;
         let req = request.into_inner();
         validation::validate_incident_id(&req.incident_id)
             .map_err(validation_error_to_status)?;
+        let query =
+            self.graph_query(req.as_of_version, req.filter, req.page_size, &req.page_token)?;
         let result = self
             .store
-            .get_live_view(&req.incident_id)
+            .get_live_view(&req.incident_id, &query)
             .await
             .map_err(store_error_to_status)?;
         Ok(Response::new(result))
@@ -128,6 +352,7 @@ impl Tee for TeeService {
         &self,
         request: Request<TombstoneRequest>,
     ) -> Result<Response<TombstoneSet>, Status> {
+        self.authorize(&request, Action::Read)?;
         let req = request.into_inner();
         validation::validate_incident_id(&req.incident_id)
             .map_err(validation_error_to_status)?;
@@ -141,13 +366,1191 @@ impl Tee for TeeService {
 
     async fn get_main_graph(
         &self,
-        _request: Request<()>,
+        request: Request<MainGraphRequest>,
     ) -> Result<Response<CausalGraph>, Status> {
+        self.authorize(&request, Action::Read)?;
+        let req = request.into_inner();
+        let query =
+            self.graph_query(req.as_of_version, req.filter, req.page_size, &req.page_token)?;
+        let result = self
+            .store
+            .get_main_graph(&query)
+            .await
+            .map_err(store_error_to_status)?;
+        Ok(Response::new(result))
+    }
+
+    async fn get_neighborhood(
+        &self,
+        request: Request<NeighborhoodRequest>,
+    ) -> Result<Response<NeighborhoodResult>, Status> {
+        self.authorize(&request, Action::Read)?;
+        let req = request.into_inner();
+        validation::validate_neighborhood_request(&req, &self.registry)
+            .map_err(validation_error_to_status)?;
+        let incident_id = req.incident_id.clone();
         let result = self
             .store
-            .get_main_graph()
+            .get_neighborhood(&incident_id, &NeighborhoodQuery::from(req))
             .await
             .map_err(store_error_to_status)?;
         Ok(Response::new(result))
     }
+
+    async fn find_paths(
+        &self,
+        request: Request<PathRequest>,
+    ) -> Result<Response<PathResult>, Status> {
+        self.authorize(&request, Action::Read)?;
+        let req = request.into_inner();
+        validation::validate_path_request(&req, &self.registry)
+            .map_err(validation_error_to_status)?;
+        let incident_id = req.incident_id.clone();
+        let query = PathQuery::from(req);
+        let neighborhood = self
+            .store
+            .get_neighborhood(&incident_id, &query.neighborhood())
+            .await
+            .map_err(store_error_to_status)?;
+        let graph = neighborhood.graph.unwrap_or_default();
+        // The search can take up to its step budget; keep it off the async workers
+        let result = tokio::task::spawn_blocking(move || paths::find_paths(&query, &graph))
+            .await
+            .map_err(|e| Status::internal(format!("path search failed: {e}")))?;
+        Ok(Response::new(result))
+    }
+
+    async fn rank_root_causes(
+        &self,
+        request: Request<RootCauseRequest>,
+    ) -> Result<Response<RootCauseResult>, Status> {
+        self.authorize(&request, Action::Read)?;
+        let req = request.into_inner();
+        validation::validate_root_cause_request(&req).map_err(validation_error_to_status)?;
+        let incident_id = req.incident_id.clone();
+        let query = RootCauseQuery::from(req);
+        let neighborhood = self
+            .store
+            .get_neighborhood(&incident_id, &query.neighborhood())
+            .await
+            .map_err(store_error_to_status)?;
+        let graph = neighborhood.graph.unwrap_or_default();
+        // Path counting can take up to its budget; keep it off the async workers
+        let result =
+            tokio::task::spawn_blocking(move || root_cause::rank_candidates(&query, &graph))
+                .await
+                .map_err(|e| Status::internal(format!("root-cause ranking failed: {e}")))?;
+        Ok(Response::new(result))
+    }
+
+    type StreamLiveViewStream = ReceiverStream<Result<CausalGraph, Status>>;
+
+    async fn stream_live_view(
+        &self,
+        request: Request<LiveViewRequest>,
+    ) -> Result<Response<Self::StreamLiveViewStream>, Status> {
+        self.authorize(&request, Action::Read)?;
+        let req = request.into_inner();
+        validation::validate_incident_id(&req.incident_id)
+            .map_err(validation_error_to_status)?;
+        let mut query =
+            self.graph_query(req.as_of_version, req.filter, req.page_size, &req.page_token)?;
+        if query.page_size == 0 {
+            query.page_size = STREAM_CHUNK_SIZE;
+        }
+        let first = self
+            .store
+            .get_live_view(&req.incident_id, &query)
+            .await
+            .map_err(store_error_to_status)?;
+        let store = Arc::clone(&self.store);
+        Ok(Response::new(stream_pages(first, query, move |query| {
+            let (store, incident_id) = (Arc::clone(&store), req.incident_id.clone());
+            async move { store.get_live_view(&incident_id, &query).await }
+        })))
+    }
+
+    type StreamMainGraphStream = ReceiverStream<Result<CausalGraph, Status>>;
+
+    async fn stream_main_graph(
+        &self,
+        request: Request<MainGraphRequest>,
+    ) -> Result<Response<Self::StreamMainGraphStream>, Status> {
+        self.authorize(&request, Action::Read)?;
+        let req = request.into_inner();
+        let mut query =
+            self.graph_query(req.as_of_version, req.filter, req.page_size, &req.page_token)?;
+        if query.page_size == 0 {
+            query.page_size = STREAM_CHUNK_SIZE;
+        }
+        let first = self
+            .store
+            .get_main_graph(&query)
+            .await
+            .map_err(store_error_to_status)?;
+        let store = Arc::clone(&self.store);
+        Ok(Response::new(stream_pages(first, query, move |query| {
+            let store = Arc::clone(&store);
+            async move { store.get_main_graph(&query).await }
+        })))
+    }
+
+    type WatchLiveViewStream = ReceiverStream<Result<LiveViewEvent, Status>>;
+
+    async fn watch_live_view(
+        &self,
+        request: Request<WatchLiveViewRequest>,
+    ) -> Result<Response<Self::WatchLiveViewStream>, Status> {
+        self.authorize(&request, Action::Read)?;
+        let req = request.into_inner();
+        validation::validate_incident_id(&req.incident_id)
+            .map_err(validation_error_to_status)?;
+        // Subscribe before reading, so no change between the reads and the first
+        // received event is missed; the watch drops what the snapshot already has.
+        let mut events = self.store.subscribe();
+        let context = self
+            .store
+            .get_incident_context(&req.incident_id)
+            .await
+            .map_err(store_error_to_status)?;
+        let snapshot = self
+            .store
+            .get_live_view(&req.incident_id, &GraphQuery::default())
+            .await
+            .map_err(store_error_to_status)?;
+        let mut watch = LiveViewWatch::new(&context, snapshot.version);
+
+        let (tx, rx) = mpsc::channel(WATCH_BUFFER);
+        tx.try_send(Ok(watch.snapshot(snapshot)))
+            .expect("a new channel has room for the snapshot");
+        tokio::spawn(async move {
+            loop {
+                let event = tokio::select! {
+                    event = events.recv() => event,
+                    () = tx.closed() => return,
+                };
+                let update = match event {
+                    Ok(event) => match watch.apply(event) {
+                        Some(update) => Ok(update),
+                        None => continue,
+                    },
+                    Err(broadcast::error::RecvError::Lagged(missed)) => Err(Status::data_loss(
+                        format!("live view watch fell {missed} events behind; watch again"),
+                    )),
+                    Err(broadcast::error::RecvError::Closed) => return,
+                };
+                let lagged = update.is_err();
+                if tx.send(update).await.is_err() || lagged {
+                    return;
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    type SubscribeMainGraphStream = ReceiverStream<Result<MainGraphChange, Status>>;
+
+    async fn subscribe_main_graph(
+        &self,
+        request: Request<SubscribeMainGraphRequest>,
+    ) -> Result<Response<Self::SubscribeMainGraphStream>, Status> {
+        self.authorize(&request, Action::Read)?;
+        let mut cursor = request.into_inner().from_cursor;
+        // Store events only wake the feed; each wake-up re-reads the history after
+        // the cursor, so a missed or lagged event never loses a change.
+        let mut events = self.store.subscribe();
+        let backlog = self
+            .store
+            .get_main_graph_changes(cursor, FEED_PAGE_VERSIONS)
+            .await
+            .map_err(store_error_to_status)?;
+        let store = Arc::clone(&self.store);
+
+        let (tx, rx) = mpsc::channel(WATCH_BUFFER);
+        tokio::spawn(async move {
+            let mut changes = backlog;
+            loop {
+                // A full page may not reach the head, so read on before waiting
+                let caught_up = (changes.len() as u64) < FEED_PAGE_VERSIONS;
+                for change in changes {
+                    cursor = change.cursor;
+                    if tx.send(Ok(change)).await.is_err() {
+                        return;
+                    }
+                }
+                if caught_up {
+                    let woken = tokio::select! {
+                        event = events.recv() => event,
+                        () = tx.closed() => return,
+                    };
+                    if let Err(broadcast::error::RecvError::Closed) = woken {
+                        return;
+                    }
+                    // One re-read covers every event already queued
+                    while let Ok(_) | Err(broadcast::error::TryRecvError::Lagged(_)) =
+                        events.try_recv()
+                    {}
+                }
+                changes = match store
+                    .get_main_graph_changes(cursor, FEED_PAGE_VERSIONS)
+                    .await
+                {
+                    Ok(changes) => changes,
+                    Err(err) => {
+                        let _ = tx.send(Err(store_error_to_status(err))).await;
+                        return;
+                    }
+                };
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::domain::edge_type::EdgeType;
+    use crate::domain::node_type::NodeType;
+    use crate::store::memory::InMemoryStore;
+    use crate::store::{GraphSize, StoreEvent};
+
+    /// Fails every call with the configured error.
+    struct FailingStore {
+        error: fn() -> StoreError,
+    }
+
+    impl Store for FailingStore {
+        async fn merge_hypothesis(
+            &self,
+            _delta: HypothesisDelta,
+            _schema: Option<&CausalSchema>,
+        ) -> Result<HypothesisMergeResult, StoreError> {
+            Err((self.error)())
+        }
+
+        async fn confirm_nodes(
+            &self,
+            _request: ConfirmNodesRequest,
+        ) -> Result<ConfirmNodesResult, StoreError> {
+            Err((self.error)())
+        }
+
+        async fn create_incident(
+            &self,
+            _request: CreateIncidentRequest,
+        ) -> Result<CreateIncidentResult, StoreError> {
+            Err((self.error)())
+        }
+
+        async fn get_incident_context(
+            &self,
+            _incident_id: &str,
+        ) -> Result<IncidentContext, StoreError> {
+            Err((self.error)())
+        }
+
+        async fn get_incident_metrics(
+            &self,
+            _incident_id: &str,
+            _as_of_version: Option<u64>,
+        ) -> Result<IncidentMetrics, StoreError> {
+            Err((self.error)())
+        }
+
+        async fn merge_node_tombstones(
+            &self,
+            _request: NodeTombstoneRequest,
+        ) -> Result<TombstoneMergeResult, StoreError> {
+            Err((self.error)())
+        }
+
+        async fn merge_edge_tombstones(
+            &self,
+            _request: EdgeTombstoneRequest,
+        ) -> Result<TombstoneMergeResult, StoreError> {
+            Err((self.error)())
+        }
+
+        async fn get_live_view(
+            &self,
+            _incident_id: &str,
+            _query: &GraphQuery,
+        ) -> Result<CausalGraph, StoreError> {
+            Err((self.error)())
+        }
+
+        async fn get_neighborhood(
+            &self,
+            _incident_id: &str,
+            _query: &NeighborhoodQuery,
+        ) -> Result<NeighborhoodResult, StoreError> {
+            Err((self.error)())
+        }
+
+        async fn get_tombstones(&self, _incident_id: &str) -> Result<TombstoneSet, StoreError> {
+            Err((self.error)())
+        }
+
+        async fn get_main_graph(&self, _query: &GraphQuery) -> Result<CausalGraph, StoreError> {
+            Err((self.error)())
+        }
+
+        async fn get_main_graph_changes(
+            &self,
+            _after_cursor: u64,
+            _max_versions: u64,
+        ) -> Result<Vec<MainGraphChange>, StoreError> {
+            Err((self.error)())
+        }
+
+        async fn graph_size(&self) -> Result<GraphSize, StoreError> {
+            Err((self.error)())
+        }
+
+        fn subscribe(&self) -> broadcast::Receiver<StoreEvent> {
+            broadcast::channel(1).1
+        }
+
+        async fn check_health(&self) -> Result<(), StoreError> {
+            Err((self.error)())
+        }
+    }
+
+    /// Delegates to an `InMemoryStore` and records the incident ids it was asked about.
+    #[derive(Default)]
+    struct RecordingStore {
+        inner: InMemoryStore,
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl RecordingStore {
+        fn record(&self, call: &str) {
+            self.calls.lock().unwrap().push(call.to_string());
+        }
+    }
+
+    impl Store for RecordingStore {
+        async fn merge_hypothesis(
+            &self,
+            delta: HypothesisDelta,
+            schema: Option<&CausalSchema>,
+        ) -> Result<HypothesisMergeResult, StoreError> {
+            self.record("merge_hypothesis");
+            self.inner.merge_hypothesis(delta, schema).await
+        }
+
+        async fn confirm_nodes(
+            &self,
+            request: ConfirmNodesRequest,
+        ) -> Result<ConfirmNodesResult, StoreError> {
+            self.record("confirm_nodes");
+            self.inner.confirm_nodes(request).await
+        }
+
+        async fn create_incident(
+            &self,
+            request: CreateIncidentRequest,
+        ) -> Result<CreateIncidentResult, StoreError> {
+            self.record(&format!("create_incident:{}", request.incident_id));
+            self.inner.create_incident(request).await
+        }
+
+        async fn get_incident_context(
+            &self,
+            incident_id: &str,
+        ) -> Result<IncidentContext, StoreError> {
+            self.record(&format!("get_incident_context:{incident_id}"));
+            self.inner.get_incident_context(incident_id).await
+        }
+
+        async fn get_incident_metrics(
+            &self,
+            incident_id: &str,
+            as_of_version: Option<u64>,
+        ) -> Result<IncidentMetrics, StoreError> {
+            self.record(&format!("get_incident_metrics:{incident_id}"));
+            self.inner.get_incident_metrics(incident_id, as_of_version).await
+        }
+
+        async fn merge_node_tombstones(
+            &self,
+            request: NodeTombstoneRequest,
+        ) -> Result<TombstoneMergeResult, StoreError> {
+            self.record("merge_node_tombstones");
+            self.inner.merge_node_tombstones(request).await
+        }
+
+        async fn merge_edge_tombstones(
+            &self,
+            request: EdgeTombstoneRequest,
+        ) -> Result<TombstoneMergeResult, StoreError> {
+            self.record("merge_edge_tombstones");
+            self.inner.merge_edge_tombstones(request).await
+        }
+
+        async fn get_live_view(
+            &self,
+            incident_id: &str,
+            query: &GraphQuery,
+        ) -> Result<CausalGraph, StoreError> {
+            self.record(&format!("get_live_view:{incident_id}"));
+            self.inner.get_live_view(incident_id, query).await
+        }
+
+        async fn get_neighborhood(
+            &self,
+            incident_id: &str,
+            query: &NeighborhoodQuery,
+        ) -> Result<NeighborhoodResult, StoreError> {
+            self.record(&format!("get_neighborhood:{incident_id}"));
+            self.inner.get_neighborhood(incident_id, query).await
+        }
+
+        async fn get_tombstones(&self, incident_id: &str) -> Result<TombstoneSet, StoreError> {
+            self.record(&format!("get_tombstones:{incident_id}"));
+            self.inner.get_tombstones(incident_id).await
+        }
+
+        async fn get_main_graph(&self, query: &GraphQuery) -> Result<CausalGraph, StoreError> {
+            self.record("get_main_graph");
+            self.inner.get_main_graph(query).await
+        }
+
+        async fn get_main_graph_changes(
+            &self,
+            after_cursor: u64,
+            max_versions: u64,
+        ) -> Result<Vec<MainGraphChange>, StoreError> {
+            self.record("get_main_graph_changes");
+            self.inner
+                .get_main_graph_changes(after_cursor, max_versions)
+                .await
+        }
+
+        async fn graph_size(&self) -> Result<GraphSize, StoreError> {
+            self.inner.graph_size().await
+        }
+
+        fn subscribe(&self) -> broadcast::Receiver<StoreEvent> {
+            self.inner.subscribe()
+        }
+
+        async fn check_health(&self) -> Result<(), StoreError> {
+            self.inner.check_health().await
+        }
+    }
+
+    #[tokio::test]
+    async fn backend_error_maps_to_internal() {
+        let service = TeeService::new(Arc::new(FailingStore {
+            error: || StoreError::Backend("connection reset".into()),
+        }));
+        let status = service
+            .get_main_graph(Request::new(MainGraphRequest::default()))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Internal);
+        assert_eq!(status.message(), "connection reset");
+    }
+
+    #[tokio::test]
+    async fn missing_incident_maps_to_not_found() {
+        let service = TeeService::new(Arc::new(FailingStore {
+            error: || StoreError::IncidentNotFound("inc-1".into()),
+        }));
+        let status = service
+            .get_live_view(Request::new(LiveViewRequest {
+                incident_id: "inc-1".into(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn future_version_maps_to_out_of_range() {
+        let service = TeeService::new(Arc::new(InMemoryStore::new()));
+        let status = service
+            .get_main_graph(Request::new(MainGraphRequest {
+                as_of_version: Some(1),
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::OutOfRange);
+    }
+
+    #[tokio::test]
+    async fn unspecified_dangling_edge_policy_uses_server_default() {
+        let service = TeeService::new(Arc::new(InMemoryStore::new()))
+            .with_dangling_edge_policy(DanglingEdgePolicy::Reject);
+        let prov = crate::proto::Provenance {
+            source: "agent-1".into(),
+            trigger: "alert".into(),
+            timestamp: None,
+            version: 0,
+        };
+        let delta = HypothesisDelta {
+            nodes: vec![],
+            edges: vec![crate::proto::Edge {
+                source: "a".into(),
+                target: "b".into(),
+                r#type: crate::proto::EdgeType::DependsOn as i32,
+                provenance: vec![prov],
+                kind: String::new(),
+            }],
+            atomic: false,
+            dangling_edge_policy: DanglingEdgePolicy::Unspecified as i32,
+        };
+
+        let rejected = service
+            .merge_hypothesis(Request::new(delta.clone()))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(rejected.created_ids.is_empty());
+        assert_eq!(rejected.dangling_edge_ids.len(), 1);
+
+        // An explicit policy on the delta wins over the server default
+        let mut report = delta;
+        report.set_dangling_edge_policy(DanglingEdgePolicy::Report);
+        let reported = service
+            .merge_hypothesis(Request::new(report))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(reported.created_ids.len(), 1);
+        assert_eq!(reported.dangling_edge_ids, reported.created_ids);
+    }
+
+    #[tokio::test]
+    async fn schema_checks_stored_endpoint_types() {
+        let store = Arc::new(RecordingStore::default());
+        let service = TeeService::new(store.clone()).with_schema(CausalSchema::new([(
+            NodeType::Mechanism,
+            EdgeType::ManifestsAs,
+            NodeType::Service,
+        )]));
+        let prov = crate::proto::Provenance {
+            source: "agent-1".into(),
+            trigger: "alert".into(),
+            timestamp: None,
+            version: 0,
+        };
+        let node = |id: &str, node_type: crate::proto::NodeType| crate::proto::Node {
+            id: id.into(),
+            r#type: node_type as i32,
+            label: id.into(),
+            hypothetical: true,
+            provenance: vec![prov.clone()],
+            kind: String::new(),
+        };
+        let manifests = crate::proto::Edge {
+            source: "oom".into(),
+            target: "api".into(),
+            r#type: crate::proto::EdgeType::ManifestsAs as i32,
+            provenance: vec![prov.clone()],
+            kind: String::new(),
+        };
+        store
+            .inner
+            .merge_hypothesis(
+                HypothesisDelta {
+                    nodes: vec![
+                        node("api", crate::proto::NodeType::Service),
+                        node("db", crate::proto::NodeType::Infrastructure),
+                    ],
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        service
+            .merge_hypothesis(Request::new(HypothesisDelta {
+                nodes: vec![node("oom", crate::proto::NodeType::Mechanism)],
+                edges: vec![manifests.clone()],
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+
+        let status = service
+            .merge_hypothesis(Request::new(HypothesisDelta {
+                edges: vec![crate::proto::Edge {
+                    target: "db".into(),
+                    ..manifests.clone()
+                }],
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(status.message().contains("MECHANISM -MANIFESTS_AS-> INFRASTRUCTURE"));
+
+        // An endpoint without a type is checked by the write that types it
+        let result = service
+            .merge_hypothesis(Request::new(HypothesisDelta {
+                edges: vec![crate::proto::Edge {
+                    target: "cache".into(),
+                    ..manifests
+                }],
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(result.dangling_edge_ids.len(), 1);
+        let status = service
+            .merge_hypothesis(Request::new(HypothesisDelta {
+                nodes: vec![node("cache", crate::proto::NodeType::Infrastructure)],
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(status.message().contains("MECHANISM -MANIFESTS_AS-> INFRASTRUCTURE"));
+        assert_eq!(*store.calls.lock().unwrap(), vec!["merge_hypothesis"; 4]);
+    }
+
+    #[tokio::test]
+    async fn invalid_request_never_reaches_store() {
+        let store = Arc::new(RecordingStore::default());
+        let service = TeeService::new(store.clone());
+
+        let status = service
+            .create_incident(Request::new(CreateIncidentRequest {
+                incident_id: String::new(),
+                follow_main_graph: false,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        let status = service
+            .get_neighborhood(Request::new(NeighborhoodRequest {
+                incident_id: "inc-1".into(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        let status = service
+            .confirm_nodes(Request::new(ConfirmNodesRequest {
+                node_ids: vec!["api".into()],
+                provenance: None,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(store.calls.lock().unwrap().is_empty());
+
+        service
+            .create_incident(Request::new(CreateIncidentRequest {
+                incident_id: "inc-1".into(),
+                follow_main_graph: false,
+            }))
+            .await
+            .unwrap();
+        assert_eq!(*store.calls.lock().unwrap(), vec!["create_incident:inc-1"]);
+    }
+
+    #[tokio::test]
+    async fn metrics_count_write_outcomes() {
+        let metrics = Metrics::new();
+        let service = TeeService::new(Arc::new(InMemoryStore::new())).with_metrics(metrics.clone());
+        let prov = crate::proto::Provenance {
+            source: "agent-1".into(),
+            trigger: "alert".into(),
+            timestamp: None,
+            version: 0,
+        };
+        let node = |id: &str, label: &str| crate::proto::Node {
+            id: id.into(),
+            r#type: crate::proto::NodeType::Service as i32,
+            label: label.into(),
+            hypothetical: true,
+            provenance: vec![prov.clone()],
+            kind: String::new(),
+        };
+        let merge = |nodes| {
+            service.merge_hypothesis(Request::new(HypothesisDelta {
+                nodes,
+                ..Default::default()
+            }))
+        };
+        merge(vec![node("api", "api"), node("db", "db")]).await.unwrap();
+        merge(vec![node("api", "gateway"), node("db", "db")]).await.unwrap();
+
+        service
+            .create_incident(Request::new(CreateIncidentRequest {
+                incident_id: "inc-1".into(),
+                follow_main_graph: false,
+            }))
+            .await
+            .unwrap();
+        let tombstone = || NodeTombstoneRequest {
+            incident_id: "inc-1".into(),
+            node_ids: vec!["db".into(), "ghost".into()],
+            provenance: Some(prov.clone()),
+            rationale: None,
+        };
+        service
+            .merge_node_tombstones(Request::new(tombstone()))
+            .await
+            .unwrap();
+        service
+            .merge_node_tombstones(Request::new(tombstone()))
+            .await
+            .unwrap();
+
+        let text = metrics.encode();
+        for line in [
+            "tee_merged_ids_total{outcome=\"created\"} 2",
+            "tee_merged_ids_total{outcome=\"merged\"} 1",
+            "tee_merge_conflicts_total{field=\"label\"} 1",
+            "tee_tombstoned_ids_total{kind=\"node\",outcome=\"applied\"} 1",
+            "tee_tombstoned_ids_total{kind=\"node\",outcome=\"unmatched\"} 1",
+            "tee_tombstoned_ids_total{kind=\"node\",outcome=\"already_tombstoned\"} 2",
+        ] {
+            assert!(text.contains(line), "missing {line:?} in:\n{text}");
+        }
+    }
+
+    #[tokio::test]
+    async fn principals_gate_rpcs_and_provenance_sources() {
+        // SHA-256 of "cmbs-token" and "agent-token"
+        let principals = Principals::from_toml(
+            r#"
+            [[principals]]
+            name = "cmbs"
+            roles = ["cmbs"]
+            token_sha256 = ["8f02bace520876461545beb8db937ec164aeed97cf63fbdbd4b397a478a21d43"]
+
+            [[principals]]
+            name = "agent-1"
+            roles = ["hypothesis"]
+            token_sha256 = ["eb47b9ce4840a5b3ea138b8691253b78035b9289d9014409067e9844c272ef99"]
+            "#,
+        )
+        .unwrap();
+        let store = Arc::new(RecordingStore::default());
+        let service = TeeService::new(store.clone()).with_principals(principals);
+
+        fn signed<T>(message: T, token: &str) -> Request<T> {
+            let mut request = Request::new(message);
+            let value = format!("Bearer {token}").parse().unwrap();
+            request.metadata_mut().insert("authorization", value);
+            request
+        }
+        let tombstone = |source: &str| NodeTombstoneRequest {
+            incident_id: "inc-1".into(),
+            node_ids: vec!["api".into()],
+            provenance: Some(crate::proto::Provenance {
+                source: source.into(),
+                trigger: "elimination".into(),
+                ..Default::default()
+            }),
+            rationale: None,
+        };
+
+        let status = service
+            .get_tombstones(Request::new(TombstoneRequest {
+                incident_id: "inc-1".into(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+        // A hypothesis agent may not write tombstones, even under its own name
+        let status = service
+            .merge_node_tombstones(signed(tombstone("agent-1"), "agent-token"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+        // CMBS may, but only as itself
+        let status = service
+            .merge_node_tombstones(signed(tombstone("agent-1"), "cmbs-token"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+        assert!(store.calls.lock().unwrap().is_empty());
+
+        let create = CreateIncidentRequest {
+            incident_id: "inc-1".into(),
+            follow_main_graph: false,
+        };
+        service
+            .create_incident(signed(create, "cmbs-token"))
+            .await
+            .unwrap();
+        service
+            .merge_node_tombstones(signed(tombstone("cmbs"), "cmbs-token"))
+            .await
+            .unwrap();
+        // Every role reads
+        service
+            .get_tombstones(signed(
+                TombstoneRequest {
+                    incident_id: "inc-1".into(),
+                },
+                "agent-token",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            *store.calls.lock().unwrap(),
+            vec![
+                "create_incident:inc-1",
+                "merge_node_tombstones",
+                "get_tombstones:inc-1"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn watch_live_view_streams_snapshot_then_changes() {
+        use crate::proto::live_view_event::Event;
+        use tokio_stream::StreamExt;
+
+        let service = TeeService::new(Arc::new(InMemoryStore::new()));
+        let prov = crate::proto::Provenance {
+            source: "agent-1".into(),
+            trigger: "alert".into(),
+            timestamp: None,
+            version: 0,
+        };
+        let node = |id: &str| crate::proto::Node {
+            id: id.into(),
+            r#type: crate::proto::NodeType::Service as i32,
+            label: id.into(),
+            hypothetical: true,
+            provenance: vec![prov.clone()],
+            kind: String::new(),
+        };
+        let merge = |nodes| {
+            service.merge_hypothesis(Request::new(HypothesisDelta {
+                nodes,
+                ..Default::default()
+            }))
+        };
+        merge(vec![node("api")]).await.unwrap();
+        service
+            .create_incident(Request::new(CreateIncidentRequest {
+                incident_id: "inc-1".into(),
+                follow_main_graph: true,
+            }))
+            .await
+            .unwrap();
+
+        let mut stream = service
+            .watch_live_view(Request::new(WatchLiveViewRequest {
+                incident_id: "inc-1".into(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        let first = stream.next().await.unwrap().unwrap();
+        let Some(Event::Snapshot(graph)) = first.event else {
+            panic!("expected a snapshot, got {first:?}");
+        };
+        assert_eq!(graph.nodes.len(), 1);
+        assert_eq!(first.version, 1);
+
+        merge(vec![node("db")]).await.unwrap();
+        service
+            .merge_node_tombstones(Request::new(NodeTombstoneRequest {
+                incident_id: "inc-1".into(),
+                node_ids: vec!["api".into()],
+                provenance: Some(prov.clone()),
+                rationale: None,
+            }))
+            .await
+            .unwrap();
+        merge(vec![crate::proto::Node {
+            hypothetical: false,
+            ..node("db")
+        }])
+        .await
+        .unwrap();
+
+        let mut events = Vec::new();
+        for _ in 0..3 {
+            let update = stream.next().await.unwrap().unwrap();
+            events.push((update.version, update.event.unwrap()));
+        }
+        assert!(matches!(&events[0], (2, Event::NodeAdded(n)) if n.id == "db"));
+        assert_eq!(events[1], (2, Event::NodeTombstoned("api".into())));
+        assert_eq!(events[2], (3, Event::NodeConfirmed("db".into())));
+    }
+
+    #[tokio::test]
+    async fn watch_live_view_of_missing_incident_is_not_found() {
+        let service = TeeService::new(Arc::new(InMemoryStore::new()));
+        let status = service
+            .watch_live_view(Request::new(WatchLiveViewRequest {
+                incident_id: "inc-1".into(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn subscribe_main_graph_replays_then_follows() {
+        use tokio_stream::StreamExt;
+
+        let service = TeeService::new(Arc::new(InMemoryStore::new()));
+        let node = |id: &str| crate::proto::Node {
+            id: id.into(),
+            r#type: crate::proto::NodeType::Service as i32,
+            label: id.into(),
+            hypothetical: true,
+            provenance: vec![crate::proto::Provenance {
+                source: "agent-1".into(),
+                trigger: "alert".into(),
+                timestamp: None,
+                version: 0,
+            }],
+            kind: String::new(),
+        };
+        let merge = |id: &str| {
+            service.merge_hypothesis(Request::new(HypothesisDelta {
+                nodes: vec![node(id)],
+                ..Default::default()
+            }))
+        };
+        merge("a").await.unwrap();
+        merge("b").await.unwrap();
+
+        // Resume after the first merge: the backlog holds only the second
+        let mut stream = service
+            .subscribe_main_graph(Request::new(SubscribeMainGraphRequest { from_cursor: 1 }))
+            .await
+            .unwrap()
+            .into_inner();
+        let change = stream.next().await.unwrap().unwrap();
+        assert_eq!(change.cursor, 2);
+        assert_eq!(change.created_nodes[0].id, "b");
+
+        merge("c").await.unwrap();
+        let change = stream.next().await.unwrap().unwrap();
+        assert_eq!(change.cursor, 3);
+        assert_eq!(change.created_nodes[0].id, "c");
+    }
+
+    #[tokio::test]
+    async fn subscribe_main_graph_catches_up_page_by_page() {
+        use tokio_stream::StreamExt;
+
+        let store = Arc::new(RecordingStore::default());
+        let service = TeeService::new(store.clone());
+        let versions = FEED_PAGE_VERSIONS + 2;
+        for i in 0..versions {
+            let id = format!("n{i}");
+            let delta = HypothesisDelta {
+                nodes: vec![crate::proto::Node {
+                    id: id.clone(),
+                    r#type: crate::proto::NodeType::Service as i32,
+                    label: id,
+                    hypothetical: true,
+                    provenance: vec![crate::proto::Provenance {
+                        source: "agent-1".into(),
+                        trigger: "alert".into(),
+                        timestamp: None,
+                        version: 0,
+                    }],
+                    kind: String::new(),
+                }],
+                ..Default::default()
+            };
+            store.inner.merge_hypothesis(delta, None).await.unwrap();
+        }
+
+        // The whole backlog arrives without waiting for another write
+        let mut stream = service
+            .subscribe_main_graph(Request::new(SubscribeMainGraphRequest { from_cursor: 0 }))
+            .await
+            .unwrap()
+            .into_inner();
+        for cursor in 1..=versions {
+            let change = stream.next().await.unwrap().unwrap();
+            assert_eq!(change.cursor, cursor);
+        }
+        assert_eq!(
+            *store.calls.lock().unwrap(),
+            vec!["get_main_graph_changes"; 2]
+        );
+    }
+
+    #[tokio::test]
+    async fn subscribe_main_graph_past_head_is_out_of_range() {
+        let service = TeeService::new(Arc::new(InMemoryStore::new()));
+        let status = service
+            .subscribe_main_graph(Request::new(SubscribeMainGraphRequest { from_cursor: 5 }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::OutOfRange);
+    }
+
+    #[tokio::test]
+    async fn stream_main_graph_sends_one_message_per_page() {
+        use tokio_stream::StreamExt;
+
+        let service = TeeService::new(Arc::new(InMemoryStore::new()));
+        let nodes = ["a", "b", "c"]
+            .into_iter()
+            .map(|id| crate::proto::Node {
+                id: id.into(),
+                r#type: crate::proto::NodeType::Service as i32,
+                label: id.into(),
+                hypothetical: true,
+                provenance: vec![crate::proto::Provenance {
+                    source: "agent-1".into(),
+                    trigger: "alert".into(),
+                    timestamp: None,
+                    version: 0,
+                }],
+                kind: String::new(),
+            })
+            .collect();
+        service
+            .merge_hypothesis(Request::new(HypothesisDelta {
+                nodes,
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+
+        let pages: Vec<_> = service
+            .stream_main_graph(Request::new(MainGraphRequest {
+                page_size: 2,
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .collect()
+            .await;
+        let sizes: Vec<_> = pages.iter().map(|p| p.as_ref().unwrap().nodes.len()).collect();
+        assert_eq!(sizes, [2, 1]);
+        assert!(pages.iter().all(|p| p.as_ref().unwrap().next_page_token.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn bad_read_parameters_are_invalid_argument() {
+        let service = TeeService::new(Arc::new(InMemoryStore::new()));
+        let status = service
+            .get_main_graph(Request::new(MainGraphRequest {
+                page_token: "not-a-token".into(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        let status = service
+            .get_main_graph(Request::new(MainGraphRequest {
+                filter: Some(GraphFilter {
+                    node_types: vec!["NOT_A_TYPE".into()],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn find_paths_skips_tombstoned_nodes() {
+        let service = TeeService::new(Arc::new(InMemoryStore::new()));
+        let provenance = vec![crate::proto::Provenance {
+            source: "agent-1".into(),
+            trigger: "alert".into(),
+            timestamp: None,
+            version: 0,
+        }];
+        let node = |id: &str, node_type: crate::proto::NodeType| crate::proto::Node {
+            id: id.into(),
+            r#type: node_type as i32,
+            label: id.into(),
+            hypothetical: true,
+            provenance: provenance.clone(),
+            kind: String::new(),
+        };
+        let edge = |source: &str, target: &str, edge_type: crate::proto::EdgeType| {
+            crate::proto::Edge {
+                source: source.into(),
+                target: target.into(),
+                r#type: edge_type as i32,
+                provenance: provenance.clone(),
+                kind: String::new(),
+            }
+        };
+        // oom manifests as latency directly, and through a saturated pool
+        service
+            .merge_hypothesis(Request::new(HypothesisDelta {
+                nodes: vec![
+                    node("latency", crate::proto::NodeType::Service),
+                    node("pool", crate::proto::NodeType::Infrastructure),
+                    node("oom", crate::proto::NodeType::Mechanism),
+                ],
+                edges: vec![
+                    edge("oom", "latency", crate::proto::EdgeType::ManifestsAs),
+                    edge("oom", "pool", crate::proto::EdgeType::PropagatesTo),
+                    edge("pool", "latency", crate::proto::EdgeType::PropagatesTo),
+                ],
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+        service
+            .create_incident(Request::new(CreateIncidentRequest {
+                incident_id: "inc-1".into(),
+                follow_main_graph: true,
+            }))
+            .await
+            .unwrap();
+
+        let request = PathRequest {
+            incident_id: "inc-1".into(),
+            from_node_ids: vec!["latency".into()],
+            to_node_ids: vec!["oom".into()],
+            max_depth: 3,
+            direction: crate::proto::TraversalDirection::Upstream as i32,
+            ..Default::default()
+        };
+        let routes = |result: PathResult| -> Vec<Vec<String>> {
+            result
+                .paths
+                .into_iter()
+                .map(|p| p.nodes.into_iter().map(|n| n.id).collect())
+                .collect()
+        };
+        let result = service.find_paths(Request::new(request.clone())).await.unwrap();
+        assert_eq!(
+            routes(result.into_inner()),
+            [vec!["latency", "oom"], vec!["latency", "pool", "oom"]]
+        );
+
+        service
+            .merge_node_tombstones(Request::new(NodeTombstoneRequest {
+                incident_id: "inc-1".into(),
+                node_ids: vec!["pool".into()],
+                provenance: Some(provenance[0].clone()),
+                rationale: None,
+            }))
+            .await
+            .unwrap();
+        let result = service.find_paths(Request::new(request)).await.unwrap();
+        assert_eq!(routes(result.into_inner()), [vec!["latency", "oom"]]);
+    }
 }
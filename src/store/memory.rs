@@ -1,226 +1,504 @@
 use std::collections::{BTreeMap, BTreeSet};
-use std::sync::Arc;
+use std::ops::Bound;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use lattices::Merge;
-use tokio::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, Mutex as AsyncMutex, RwLock};
 
 use crate::domain::edge::{EdgeKey, EdgeLattice};
 use crate::domain::edge_type::EdgeType;
 use crate::domain::node::NodeLattice;
+use crate::domain::node_type::NodeType;
+use crate::domain::provenance::Provenance;
+use crate::domain::rationale::Rationale;
 use crate::proto;
 use crate::proto_convert::{
-    domain_edge_to_proto, domain_node_to_proto, proto_edge_to_domain, proto_node_to_domain,
+    domain_edge_to_proto, domain_node_to_proto, edge_id, edge_type_of, proto_edge_to_domain,
+    proto_node_to_domain,
+};
+use crate::schema::causal::CausalSchema;
+use crate::schema::validation;
+
+use super::metrics::MetricsBuilder;
+use super::neighborhood::{NeighborhoodQuery, Traversal};
+use super::query::{GraphQuery, PageBuilder, PagePosition};
+use super::wal::{IncidentCreated, Op, Recovered, Wal, WalError};
+use super::{
+    aborts_atomic, elimination_set_id, resolve_as_of, schema_node_ids, unknown_endpoints,
+    ChangeLog, GraphSize, Store, StoreError, StoreEvent, EVENT_BUFFER,
 };
 
-use super::{Store, StoreError};
-
-/// Per-incident state tracking tombstones and creation time.
-#[derive(Debug)]
+/// Per-incident state tracking tombstones, creation time and universe anchor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct IncidentState {
     created_at: (i64, i32),
-    node_tombstones: BTreeSet<String>,
-    edge_tombstones: BTreeSet<EdgeKey>,
+    /// Main-graph version the live view is resolved against.
+    /// `None` means the incident follows the main-graph head.
+    universe_anchor: Option<u64>,
+    #[serde(with = "tombstone_entries")]
+    node_tombstones: BTreeMap<String, Tombstone>,
+    #[serde(with = "tombstone_entries")]
+    edge_tombstones: BTreeMap<EdgeKey, Tombstone>,
 }
 
-/// Internal mutable state behind the RwLock.
-#[derive(Debug, Default)]
+/// Who eliminated one tombstoned id, and why. Both sets grow only.
+#[derive(Debug, Clone, Default)]
+struct Tombstone {
+    provenance: BTreeSet<Provenance>,
+    rationale: BTreeSet<Rationale>,
+}
+
+impl Tombstone {
+    fn record(&mut self, prov: &Provenance, rationale: Option<&Rationale>) {
+        self.provenance.insert(prov.clone());
+        self.rationale.extend(rationale.cloned());
+    }
+}
+
+impl IncidentState {
+    fn tombstone_set(&self) -> proto::TombstoneSet {
+        proto::TombstoneSet {
+            node_ids: self.node_tombstones.keys().cloned().collect(),
+            edge_entries: self
+                .edge_tombstones
+                .keys()
+                .map(proto::EdgeTombstoneEntry::from)
+                .collect(),
+            nodes: self
+                .node_tombstones
+                .iter()
+                .map(|(id, tombstone)| proto::NodeTombstone {
+                    node_id: id.clone(),
+                    provenance: tombstone.provenance.iter().map(Into::into).collect(),
+                    rationale: tombstone.rationale.iter().map(Into::into).collect(),
+                })
+                .collect(),
+            edges: self
+                .edge_tombstones
+                .iter()
+                .map(|(key, tombstone)| proto::EdgeTombstone {
+                    entry: Some(key.into()),
+                    provenance: tombstone.provenance.iter().map(Into::into).collect(),
+                    rationale: tombstone.rationale.iter().map(Into::into).collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Internal mutable state behind the RwLock. In durable mode this is also the
+/// snapshot format.
+#[derive(Debug, Default, Serialize, Deserialize)]
 struct InnerState {
     nodes: BTreeMap<String, NodeLattice>,
+    #[serde(with = "map_entries")]
     edges: BTreeMap<EdgeKey, EdgeLattice>,
     incidents: BTreeMap<String, IncidentState>,
+    /// Main-graph version. Bumped once by every `merge_hypothesis` that changes state.
+    version: u64,
+    /// The nodes and edges each version wrote, so the change feed reads only what
+    /// changed after its cursor. Rebuilt from their history on recovery.
+    #[serde(skip)]
+    changes: BTreeMap<u64, Touched>,
+    /// Keys of the edges into each node. Edges out of a node are a range of `edges`,
+    /// which is ordered by source first. Rebuilt on recovery.
+    #[serde(skip)]
+    edges_by_target: BTreeMap<String, BTreeSet<EdgeKey>>,
+    /// Changes applied since the store last published them to subscribers.
+    #[serde(skip)]
+    outbox: Vec<StoreEvent>,
 }
 
-/// In-memory implementation of the [`Store`] trait.
-///
-/// All state is held behind a [`RwLock`] for concurrent access.
-/// Uses the lattice-backed domain types directly — no external dependencies.
-#[derive(Debug, Clone)]
-pub struct InMemoryStore {
-    state: Arc<RwLock<InnerState>>,
+/// Main-graph entries one version created, confirmed or appended provenance to.
+#[derive(Debug, Default)]
+struct Touched {
+    nodes: BTreeSet<String>,
+    edges: BTreeSet<EdgeKey>,
 }
 
-impl InMemoryStore {
-    pub fn new() -> Self {
+impl InnerState {
+    fn anchor_of(&self, incident: &IncidentState) -> u64 {
+        incident.universe_anchor.unwrap_or(self.version)
+    }
+
+    fn require_incident(&self, incident_id: &str) -> Result<(), StoreError> {
+        match self.incidents.contains_key(incident_id) {
+            true => Ok(()),
+            false => Err(StoreError::IncidentNotFound(incident_id.to_string())),
+        }
+    }
+
+    /// A copy of the state a snapshot records, leaving out what recovery rebuilds.
+    fn durable_copy(&self) -> Self {
         Self {
-            state: Arc::new(RwLock::new(InnerState::default())),
+            nodes: self.nodes.clone(),
+            edges: self.edges.clone(),
+            incidents: self.incidents.clone(),
+            version: self.version,
+            ..Default::default()
         }
     }
-}
 
-impl Default for InMemoryStore {
-    fn default() -> Self {
-        Self::new()
+    /// Rebuilds the indexes a snapshot leaves out: `changes` from the versions recorded
+    /// on every node and edge, and `edges_by_target`.
+    fn reindex(&mut self) {
+        for (id, node) in &self.nodes {
+            let confirmed = *node.confirmed_version.as_reveal_ref();
+            let versions = node.provenance.as_reveal_ref().iter().map(|p| p.version);
+            let versions = versions
+                .chain([*node.created_version.as_reveal_ref()])
+                .chain((confirmed != u64::MAX).then_some(confirmed))
+                .chain(node.refined.as_ref().map(|r| r.version));
+            for version in versions {
+                let touched = self.changes.entry(version).or_default();
+                touched.nodes.insert(id.clone());
+            }
+        }
+        for (key, edge) in &self.edges {
+            let versions = edge.provenance.as_reveal_ref().iter().map(|p| p.version);
+            for version in versions.chain([*edge.created_version.as_reveal_ref()]) {
+                let touched = self.changes.entry(version).or_default();
+                touched.edges.insert(key.clone());
+            }
+            let into = self.edges_by_target.entry(key.target.clone()).or_default();
+            into.insert(key.clone());
+        }
     }
-}
 
-impl Store for InMemoryStore {
-    async fn merge_hypothesis(
-        &self,
+    /// Edges out of node `id`.
+    fn edges_from<'s>(
+        &'s self,
+        id: &str,
+    ) -> impl Iterator<Item = (&'s EdgeKey, &'s EdgeLattice)> + 's {
+        // No key with this source sorts before an empty target and the first edge type
+        let first = EdgeKey::new(id, "", EdgeType::DependsOn);
+        let source = first.source.clone();
+        self.edges
+            .range(first..)
+            .take_while(move |(key, _)| key.source == source)
+    }
+
+    /// Edges into node `id`.
+    fn edges_into<'s>(
+        &'s self,
+        id: &str,
+    ) -> impl Iterator<Item = (&'s EdgeKey, &'s EdgeLattice)> + 's {
+        let keys = self.edges_by_target.get(id).into_iter().flatten();
+        keys.filter_map(|key| self.edges.get_key_value(key))
+    }
+
+    /// Stored types of those `ids` that exist in the main graph with a type.
+    fn node_types(&self, ids: Vec<String>) -> BTreeMap<String, NodeType> {
+        ids.into_iter()
+            .filter_map(|id| {
+                let node_type = self.nodes.get(&id)?.node_type.as_reveal_ref()?.clone();
+                Some((id, node_type))
+            })
+            .collect()
+    }
+
+    /// Main-graph edges with an endpoint in `ids`.
+    fn edges_touching(&self, ids: &[String]) -> Vec<EdgeKey> {
+        let touching: BTreeSet<&EdgeKey> = ids
+            .iter()
+            .flat_map(|id| self.edges_from(id).chain(self.edges_into(id)))
+            .map(|(key, _)| key)
+            .collect();
+        touching.into_iter().cloned().collect()
+    }
+
+    /// Merges `delta` into the main graph. The delta is converted and merged into
+    /// staged copies of the touched nodes and edges before anything is committed, so a
+    /// conversion error writes nothing, and neither does an atomic delta with conflicts or
+    /// rejected dangling edges.
+    fn merge_hypothesis(
+        &mut self,
         delta: proto::HypothesisDelta,
     ) -> Result<proto::HypothesisMergeResult, StoreError> {
-        let mut state = self.state.write().await;
+        let policy = delta.dangling_edge_policy();
+        let nodes = delta
+            .nodes
+            .into_iter()
+            .map(proto_node_to_domain)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+        let edges = delta
+            .edges
+            .into_iter()
+            .map(proto_edge_to_domain)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+
+        let next_version = self.version + 1;
+        let mut changed = false;
+        let mut staged_nodes: BTreeMap<String, NodeLattice> = BTreeMap::new();
+        let mut staged_edges: BTreeMap<EdgeKey, EdgeLattice> = BTreeMap::new();
         let mut created_ids = Vec::new();
         let mut merged_ids = Vec::new();
         let mut conflicts = Vec::new();
 
         // Process nodes
-        for proto_node in delta.nodes {
-            let node_id = proto_node.id.clone();
-            let (id, lattice) = proto_node_to_domain(proto_node)
-                .map_err(|e| StoreError::Backend(e.to_string()))?;
+        for (id, lattice) in nodes {
+            let lattice = lattice.at_version(next_version);
 
-            match state.nodes.get_mut(&id) {
+            match staged_nodes.get(&id).or_else(|| self.nodes.get(&id)) {
                 Some(existing) => {
-                    // Clone to test merge without polluting state on conflict
+                    // Merge into a copy so a conflict leaves the existing node untouched
                     let mut candidate = existing.clone();
-                    candidate.merge(lattice);
+                    let candidate_changed = candidate.merge(lattice.clone());
                     if candidate.has_conflict() {
-                        // Report the conflict — don't persist
-                        let field = candidate
-                            .conflict_field()
-                            .unwrap_or("unknown")
-                            .to_string();
-                        let existing_value = match field.as_str() {
-                            "type" => existing
-                                .node_type
-                                .as_reveal_ref()
-                                .map(|t| t.to_string())
-                                .unwrap_or_default(),
-                            "label" => existing
-                                .label
-                                .as_reveal_ref()
-                                .cloned()
-                                .unwrap_or_default(),
-                            _ => String::new(),
-                        };
-                        conflicts.push(proto::MergeConflict {
-                            id: node_id,
-                            field,
-                            existing_value,
-                            proposed_value: String::new(), // delta already consumed
-                        });
+                        // Report each conflicted field — don't persist
+                        let first_writer: Vec<_> = existing
+                            .first_writer_provenance()
+                            .map(proto::Provenance::from)
+                            .collect();
+                        for field in candidate.conflict_fields() {
+                            conflicts.push(proto::MergeConflict {
+                                id: id.clone(),
+                                field: field.to_string(),
+                                existing_value: existing.field_value(field),
+                                proposed_value: lattice.field_value(field),
+                                first_writer_provenance: first_writer.clone(),
+                            });
+                        }
                     } else {
-                        *existing = candidate;
-                        merged_ids.push(node_id);
+                        changed |= candidate_changed;
+                        staged_nodes.insert(id.clone(), candidate);
+                        merged_ids.push(id);
                     }
                 }
                 None => {
-                    state.nodes.insert(id, lattice);
-                    created_ids.push(node_id);
+                    staged_nodes.insert(id.clone(), lattice);
+                    changed = true;
+                    created_ids.push(id);
                 }
             }
         }
 
-        // Process edges — edges have no conflict fields (only provenance grows)
-        for proto_edge in delta.edges {
-            let edge_id = format!("{}->{}:{}", proto_edge.source, proto_edge.target, proto_edge.r#type);
-            let (key, lattice) = proto_edge_to_domain(proto_edge)
-                .map_err(|e| StoreError::Backend(e.to_string()))?;
+        // Process edges — edges have no conflict fields (only provenance grows).
+        // Endpoints resolve against the main graph and the nodes staged above.
+        let mut dangling_edge_ids = Vec::new();
+        for (key, lattice) in edges {
+            let edge_id = edge_id(&key.source, &key.target, &key.edge_type);
+            let lattice = lattice.at_version(next_version);
+
+            let missing: Vec<String> = [&key.source, &key.target]
+                .into_iter()
+                .filter(|id| {
+                    !staged_nodes.contains_key(id.as_str()) && !self.nodes.contains_key(id.as_str())
+                })
+                .cloned()
+                .collect();
+            if !missing.is_empty() {
+                match policy {
+                    proto::DanglingEdgePolicy::Reject => {
+                        dangling_edge_ids.push(edge_id);
+                        continue;
+                    }
+                    proto::DanglingEdgePolicy::Placeholder => {
+                        for id in missing {
+                            let placeholder = NodeLattice::placeholder(
+                                &id,
+                                lattice.provenance.as_reveal_ref().clone(),
+                            )
+                            .at_version(next_version);
+                            staged_nodes.insert(id.clone(), placeholder);
+                            changed = true;
+                            created_ids.push(id);
+                        }
+                    }
+                    proto::DanglingEdgePolicy::Unspecified | proto::DanglingEdgePolicy::Report => {
+                        dangling_edge_ids.push(edge_id.clone());
+                    }
+                }
+            }
 
-            match state.edges.get_mut(&key) {
+            match staged_edges.get(&key).or_else(|| self.edges.get(&key)) {
                 Some(existing) => {
-                    existing.merge(lattice);
+                    let mut candidate = existing.clone();
+                    changed |= candidate.merge(lattice);
+                    staged_edges.insert(key, candidate);
                     merged_ids.push(edge_id);
                 }
                 None => {
-                    state.edges.insert(key, lattice);
+                    staged_edges.insert(key, lattice);
+                    changed = true;
                     created_ids.push(edge_id);
                 }
             }
         }
 
-        Ok(proto::HypothesisMergeResult {
+        let result = proto::HypothesisMergeResult {
             created_ids,
             merged_ids,
             conflicts,
+            version: self.version,
+            dangling_edge_ids,
+        };
+        if aborts_atomic(delta.atomic, policy, &result) {
+            return Ok(proto::HypothesisMergeResult {
+                created_ids: Vec::new(),
+                merged_ids: Vec::new(),
+                ..result
+            });
+        }
+
+        for (id, node) in &staged_nodes {
+            match self.nodes.get(id) {
+                Some(existing) if !existing.is_placeholder() || node.is_placeholder() => {
+                    let confirmed = *existing.hypothetical.as_reveal_ref()
+                        && !*node.hypothetical.as_reveal_ref();
+                    if confirmed {
+                        self.outbox.push(StoreEvent::NodeConfirmed {
+                            version: next_version,
+                            node_id: id.clone(),
+                        });
+                    }
+                }
+                _ => self.outbox.push(StoreEvent::NodeAdded {
+                    version: next_version,
+                    node: domain_node_to_proto(id.clone(), node),
+                }),
+            }
+        }
+        for (key, edge) in &staged_edges {
+            if !self.edges.contains_key(key) {
+                self.outbox.push(StoreEvent::EdgeAdded {
+                    version: next_version,
+                    edge: domain_edge_to_proto(key, edge),
+                });
+            }
+        }
+
+        if changed {
+            self.version = next_version;
+            let touched = self.changes.entry(next_version).or_default();
+            touched.nodes.extend(staged_nodes.keys().cloned());
+            touched.edges.extend(staged_edges.keys().cloned());
+        }
+        self.nodes.extend(staged_nodes);
+        for key in staged_edges.keys() {
+            let into = self.edges_by_target.entry(key.target.clone()).or_default();
+            into.insert(key.clone());
+        }
+        self.edges.extend(staged_edges);
+
+        Ok(proto::HypothesisMergeResult {
+            version: self.version,
+            ..result
         })
     }
 
-    async fn create_incident(
-        &self,
-        incident_id: &str,
-    ) -> Result<proto::CreateIncidentResult, StoreError> {
-        let mut state = self.state.write().await;
-        let created = if state.incidents.contains_key(incident_id) {
+    /// Confirms the known nodes among `request.node_ids` at the next main-graph
+    /// version, which becomes current if anything changed.
+    fn confirm_nodes(
+        &mut self,
+        request: proto::ConfirmNodesRequest,
+    ) -> Result<proto::ConfirmNodesResult, StoreError> {
+        let next_version = self.version + 1;
+        let prov = Provenance::from(request.provenance.unwrap_or_default());
+        let mut changed = false;
+        let mut touched = Vec::new();
+        let mut result = proto::ConfirmNodesResult::default();
+
+        for node_id in request.node_ids {
+            let Some(node) = self.nodes.get_mut(&node_id) else {
+                result.unknown_ids.push(node_id);
+                continue;
+            };
+            let was_hypothetical = *node.hypothetical.as_reveal_ref();
+            if node.confirm(prov.clone(), next_version) {
+                changed = true;
+                touched.push(node_id.clone());
+            }
+            if was_hypothetical {
+                self.outbox.push(StoreEvent::NodeConfirmed {
+                    version: next_version,
+                    node_id: node_id.clone(),
+                });
+                result.confirmed_ids.push(node_id);
+            } else {
+                result.already_confirmed_ids.push(node_id);
+            }
+        }
+
+        if changed {
+            self.version = next_version;
+            self.changes
+                .entry(next_version)
+                .or_default()
+                .nodes
+                .extend(touched);
+        }
+        result.version = self.version;
+        Ok(result)
+    }
+
+    fn create_incident(
+        &mut self,
+        incident_id: String,
+        follow_main_graph: bool,
+        created_at: (i64, i32),
+    ) -> proto::CreateIncidentResult {
+        let created = if self.incidents.contains_key(&incident_id) {
             false
         } else {
-            let now = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default();
-            state.incidents.insert(
-                incident_id.to_string(),
+            let universe_anchor = (!follow_main_graph).then_some(self.version);
+            self.incidents.insert(
+                incident_id.clone(),
                 IncidentState {
-                    created_at: (now.as_secs() as i64, now.subsec_nanos() as i32),
-                    node_tombstones: BTreeSet::new(),
-                    edge_tombstones: BTreeSet::new(),
+                    created_at,
+                    universe_anchor,
+                    node_tombstones: BTreeMap::new(),
+                    edge_tombstones: BTreeMap::new(),
                 },
             );
             true
         };
+        let universe_anchor = self.anchor_of(&self.incidents[&incident_id]);
 
-        Ok(proto::CreateIncidentResult {
-            incident_id: incident_id.to_string(),
+        proto::CreateIncidentResult {
+            incident_id,
             created,
-        })
-    }
-
-    async fn get_incident_context(
-        &self,
-        incident_id: &str,
-    ) -> Result<proto::IncidentContext, StoreError> {
-        let state = self.state.read().await;
-        let incident = state
-            .incidents
-            .get(incident_id)
-            .ok_or_else(|| StoreError::IncidentNotFound(incident_id.to_string()))?;
-
-        let tombstones = proto::TombstoneSet {
-            node_ids: incident.node_tombstones.iter().cloned().collect(),
-            edge_entries: incident
-                .edge_tombstones
-                .iter()
-                .map(|k| proto::EdgeTombstoneEntry {
-                    source: k.source.clone(),
-                    target: k.target.clone(),
-                    r#type: i32::from(k.edge_type),
-                })
-                .collect(),
-        };
-
-        Ok(proto::IncidentContext {
-            incident_id: incident_id.to_string(),
-            created_at: Some(prost_types::Timestamp {
-                seconds: incident.created_at.0,
-                nanos: incident.created_at.1,
-            }),
-            tombstones: Some(tombstones),
-        })
+            universe_anchor,
+        }
     }
 
-    async fn merge_node_tombstones(
-        &self,
+    fn merge_node_tombstones(
+        &mut self,
         request: proto::NodeTombstoneRequest,
     ) -> Result<proto::TombstoneMergeResult, StoreError> {
-        let mut state = self.state.write().await;
         let InnerState {
             ref nodes,
             ref mut incidents,
+            ref mut outbox,
+            version,
             ..
-        } = *state;
+        } = *self;
         let incident = incidents
             .get_mut(&request.incident_id)
             .ok_or_else(|| StoreError::IncidentNotFound(request.incident_id.clone()))?;
+        let prov = Provenance::from(request.provenance.unwrap_or_default()).with_version(version);
+        let rationale = request.rationale.map(|r| Rationale::from(r).by(&prov));
 
         let mut applied_ids = Vec::new();
         let mut already_tombstoned_ids = Vec::new();
         let mut unmatched_ids = Vec::new();
 
         for node_id in request.node_ids {
-            if incident.node_tombstones.contains(&node_id) {
+            let known = incident.node_tombstones.contains_key(&node_id);
+            let tombstone = incident.node_tombstones.entry(node_id.clone()).or_default();
+            tombstone.record(&prov, rationale.as_ref());
+            if known {
                 already_tombstoned_ids.push(node_id);
             } else {
-                incident.node_tombstones.insert(node_id.clone());
+                outbox.push(StoreEvent::NodeTombstoned {
+                    incident_id: request.incident_id.clone(),
+                    node_id: node_id.clone(),
+                });
                 if nodes.contains_key(&node_id) {
                     applied_ids.push(node_id);
                 } else {
@@ -236,34 +514,43 @@ impl Store for InMemoryStore {
         })
     }
 
-    async fn merge_edge_tombstones(
-        &self,
+    fn merge_edge_tombstones(
+        &mut self,
         request: proto::EdgeTombstoneRequest,
     ) -> Result<proto::TombstoneMergeResult, StoreError> {
-        let mut state = self.state.write().await;
         let InnerState {
             ref edges,
             ref mut incidents,
+            ref mut outbox,
+            version,
             ..
-        } = *state;
+        } = *self;
         let incident = incidents
             .get_mut(&request.incident_id)
             .ok_or_else(|| StoreError::IncidentNotFound(request.incident_id.clone()))?;
+        let prov = Provenance::from(request.provenance.unwrap_or_default()).with_version(version);
+        let rationale = request.rationale.map(|r| Rationale::from(r).by(&prov));
 
         let mut applied_ids = Vec::new();
         let mut already_tombstoned_ids = Vec::new();
         let mut unmatched_ids = Vec::new();
 
         for entry in request.entries {
-            let edge_type = EdgeType::try_from(entry.r#type)
+            let edge_type = edge_type_of(entry.r#type, &entry.kind)
                 .map_err(|e| StoreError::Backend(e.to_string()))?;
-            let key = EdgeKey::new(&entry.source, &entry.target, edge_type);
-            let edge_id = format!("{}->{}:{}", entry.source, entry.target, entry.r#type);
+            let edge_id = edge_id(&entry.source, &entry.target, &edge_type);
+            let key = EdgeKey::new(entry.source, entry.target, edge_type);
 
-            if incident.edge_tombstones.contains(&key) {
+            let known = incident.edge_tombstones.contains_key(&key);
+            let tombstone = incident.edge_tombstones.entry(key.clone()).or_default();
+            tombstone.record(&prov, rationale.as_ref());
+            if known {
                 already_tombstoned_ids.push(edge_id);
             } else {
-                incident.edge_tombstones.insert(key.clone());
+                outbox.push(StoreEvent::EdgeTombstoned {
+                    incident_id: request.incident_id.clone(),
+                    entry: proto::EdgeTombstoneEntry::from(&key),
+                });
                 if edges.contains_key(&key) {
                     applied_ids.push(edge_id);
                 } else {
@@ -279,152 +566,743 @@ impl Store for InMemoryStore {
         })
     }
 
-    async fn get_live_view(
+    /// Reads one page of the graph at `version`, leaving out whatever `incident` has
+    /// tombstoned. Only the elements from the page token onwards are visited.
+    fn read_page(
         &self,
-        incident_id: &str,
-    ) -> Result<proto::CausalGraph, StoreError> {
-        let state = self.state.read().await;
-        let incident = state
-            .incidents
-            .get(incident_id)
-            .ok_or_else(|| StoreError::IncidentNotFound(incident_id.to_string()))?;
+        query: &GraphQuery,
+        version: u64,
+        incident: Option<&IncidentState>,
+    ) -> proto::CausalGraph {
+        let node_hidden =
+            |id: &String| incident.is_some_and(|i| i.node_tombstones.contains_key(id));
+        let edge_hidden = |key: &EdgeKey| {
+            incident.is_some_and(|i| i.edge_tombstones.contains_key(key))
+                || node_hidden(&key.source)
+                || node_hidden(&key.target)
+        };
+        let (node_start, edge_start) = match query.after() {
+            None => (Some(Bound::Unbounded), Bound::Unbounded),
+            Some(PagePosition::Node(id)) => (Some(Bound::Excluded(id.clone())), Bound::Unbounded),
+            Some(after) => (
+                None,
+                after.edge_key().map_or(Bound::Unbounded, Bound::Excluded),
+            ),
+        };
 
-        let nodes: Vec<proto::Node> = state
-            .nodes
-            .iter()
-            .filter(|(id, _)| !incident.node_tombstones.contains(*id))
-            .map(|(id, lattice)| domain_node_to_proto(id.clone(), lattice))
-            .collect();
+        let mut page = PageBuilder::new(query, version);
+        if let Some(start) = node_start {
+            for (id, lattice) in self.nodes.range((start, Bound::Unbounded)) {
+                let Some(past) = lattice.as_of(version) else {
+                    continue;
+                };
+                if !node_hidden(id) && !page.node(domain_node_to_proto(id.clone(), &past)) {
+                    break;
+                }
+            }
+        }
+        for (key, lattice) in self.edges.range((edge_start, Bound::Unbounded)) {
+            if page.is_closed() {
+                break;
+            }
+            if let Some(past) = lattice.as_of(version).filter(|_| !edge_hidden(key)) {
+                page.edge(domain_edge_to_proto(key, &past));
+            }
+        }
+        page.finish()
+    }
 
-        let edges: Vec<proto::Edge> = state
-            .edges
+    /// Reads the neighborhood `query` asks for from `incident`'s live view at `version`.
+    fn neighborhood(
+        &self,
+        query: &NeighborhoodQuery,
+        version: u64,
+        incident: &IncidentState,
+    ) -> proto::NeighborhoodResult {
+        let live_node = |id: &String| {
+            let past = self.nodes.get(id)?.as_of(version)?;
+            (!incident.node_tombstones.contains_key(id))
+                .then(|| domain_node_to_proto(id.clone(), &past))
+        };
+        // Edges touching a tombstoned node never lead anywhere: that node is not live
+        let live_edge = |(key, lattice): (&EdgeKey, &EdgeLattice)| {
+            if incident.edge_tombstones.contains_key(key) {
+                return None;
+            }
+            Some(domain_edge_to_proto(key, &lattice.as_of(version)?))
+        };
+
+        // Each hop reads only the edges at its frontier, by source and by target
+        let mut traversal = Traversal::new(query);
+        traversal.admit(query.node_ids.iter().filter_map(live_node));
+        while let Some(frontier) = traversal.frontier() {
+            let touching: BTreeMap<_, _> = frontier
+                .iter()
+                .flat_map(|id| self.edges_from(id).chain(self.edges_into(id)))
+                .collect();
+            let edges: Vec<_> = touching.into_iter().filter_map(live_edge).collect();
+            let next = traversal.step(&edges);
+            traversal.admit(next.iter().filter_map(live_node));
+        }
+        let reached: BTreeSet<_> = traversal.node_ids().cloned().collect();
+        let edges = reached
             .iter()
-            .filter(|(key, _)| {
-                !incident.edge_tombstones.contains(*key)
-                    && !incident.node_tombstones.contains(&key.source)
-                    && !incident.node_tombstones.contains(&key.target)
-            })
-            .map(|(key, lattice)| domain_edge_to_proto(key, lattice))
+            .flat_map(|id| self.edges_from(id))
+            .filter(|(key, _)| reached.contains(&key.target))
+            .filter_map(live_edge)
             .collect();
-
-        Ok(proto::CausalGraph { nodes, edges })
+        traversal.finish(version, edges)
     }
 
-    async fn get_tombstones(
+    /// Counts `incident`'s universe at `version`, live and eliminated.
+    fn metrics(
         &self,
         incident_id: &str,
-    ) -> Result<proto::TombstoneSet, StoreError> {
-        let state = self.state.read().await;
-        let incident = state
-            .incidents
-            .get(incident_id)
-            .ok_or_else(|| StoreError::IncidentNotFound(incident_id.to_string()))?;
+        incident: &IncidentState,
+        version: u64,
+    ) -> proto::IncidentMetrics {
+        let mut metrics = MetricsBuilder::default();
+        for (id, lattice) in &self.nodes {
+            let Some(past) = lattice.as_of(version) else {
+                continue;
+            };
+            let node_type = past.node_type.as_reveal_ref();
+            let kind = node_type.map(ToString::to_string).unwrap_or_default();
+            let live = !incident.node_tombstones.contains_key(id);
+            let candidate = live
+                && *past.hypothetical.as_reveal_ref()
+                && node_type != Some(&NodeType::Placeholder);
+            let support = || {
+                let provenance = past.provenance.as_reveal_ref().iter();
+                provenance.map(|p| &p.source).collect::<BTreeSet<_>>().len() as u64
+            };
+            metrics.nodes(&kind, live as u64, !live as u64, candidate.then(support));
+        }
+        for (key, lattice) in &self.edges {
+            if *lattice.created_version.as_reveal_ref() > version {
+                continue;
+            }
+            let live = !incident.edge_tombstones.contains_key(key)
+                && !incident.node_tombstones.contains_key(&key.source)
+                && !incident.node_tombstones.contains_key(&key.target);
+            metrics.edges(&key.edge_type.to_string(), live as u64, !live as u64);
+        }
 
-        Ok(proto::TombstoneSet {
-            node_ids: incident.node_tombstones.iter().cloned().collect(),
-            edge_entries: incident
-                .edge_tombstones
-                .iter()
-                .map(|k| proto::EdgeTombstoneEntry {
-                    source: k.source.clone(),
-                    target: k.target.clone(),
-                    r#type: i32::from(k.edge_type),
-                })
-                .collect(),
-        })
+        let unmatched_nodes = incident
+            .node_tombstones
+            .keys()
+            .filter(|id| {
+                self.nodes
+                    .get(*id)
+                    .is_none_or(|l| *l.created_version.as_reveal_ref() > version)
+            })
+            .count();
+        let unmatched_edges = incident
+            .edge_tombstones
+            .keys()
+            .filter(|key| {
+                self.edges
+                    .get(*key)
+                    .is_none_or(|l| *l.created_version.as_reveal_ref() > version)
+            })
+            .count();
+        metrics.unmatched(unmatched_nodes as u64, unmatched_edges as u64);
+        metrics.finish(incident_id, version)
     }
 
-    async fn get_main_graph(&self) -> Result<proto::CausalGraph, StoreError> {
-        let state = self.state.read().await;
+    /// Re-applies a logged mutation during recovery. A write that was rejected when
+    /// first applied is rejected again the same way, so its error is only traced.
+    fn replay(&mut self, op: Op) {
+        let result = match op {
+            Op::MergeHypothesis(delta) => self.merge_hypothesis(delta).map(drop),
+            Op::CreateIncident(created) => {
+                let created_at = created
+                    .created_at
+                    .map(|t| (t.seconds, t.nanos))
+                    .unwrap_or_default();
+                self.create_incident(created.incident_id, created.follow_main_graph, created_at);
+                Ok(())
+            }
+            Op::MergeNodeTombstones(request) => self.merge_node_tombstones(request).map(drop),
+            Op::MergeEdgeTombstones(request) => self.merge_edge_tombstones(request).map(drop),
+            Op::ConfirmNodes(request) => self.confirm_nodes(request).map(drop),
+        };
+        if let Err(err) = result {
+            tracing::debug!("replayed write rejected: {err}");
+        }
+    }
+}
 
-        let nodes: Vec<proto::Node> = state
-            .nodes
-            .iter()
-            .map(|(id, lattice)| domain_node_to_proto(id.clone(), lattice))
-            .collect();
+/// Serializes a map as a sequence of `(key, value)` pairs, for keys that JSON
+/// cannot use as object keys.
+mod map_entries {
+    use std::collections::BTreeMap;
 
-        let edges: Vec<proto::Edge> = state
-            .edges
-            .iter()
-            .map(|(key, lattice)| domain_edge_to_proto(key, lattice))
-            .collect();
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<K, V, S>(map: &BTreeMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize,
+        V: Serialize,
+        S: Serializer,
+    {
+        serializer.collect_seq(map)
+    }
 
-        Ok(proto::CausalGraph { nodes, edges })
+    pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<BTreeMap<K, V>, D::Error>
+    where
+        K: Deserialize<'de> + Ord,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Ok(Vec::<(K, V)>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Serializes tombstones as `(key, provenance, rationale)` triples. Snapshots written
+/// before tombstones kept a rationale hold `(key, provenance)` pairs, and older ones
+/// bare keys; the missing sets read back empty.
+mod tombstone_entries {
+    use std::collections::{BTreeMap, BTreeSet};
 
-    fn make_node(id: &str, node_type: i32, label: &str) -> proto::Node {
-        proto::Node {
-            id: id.into(),
-            r#type: node_type,
-            label: label.into(),
-            hypothetical: true,
-            provenance: vec![proto::Provenance {
-                source: "agent-1".into(),
-                trigger: "alert".into(),
-                timestamp: None,
-            }],
-        }
-    }
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-    fn make_edge(source: &str, target: &str, edge_type: i32) -> proto::Edge {
-        proto::Edge {
-            source: source.into(),
-            target: target.into(),
-            r#type: edge_type,
-            provenance: vec![proto::Provenance {
-                source: "agent-1".into(),
-                trigger: "alert".into(),
-                timestamp: None,
-            }],
-        }
-    }
+    use super::Tombstone;
+    use crate::domain::provenance::Provenance;
+    use crate::domain::rationale::Rationale;
 
-    fn make_delta(nodes: Vec<proto::Node>, edges: Vec<proto::Edge>) -> proto::HypothesisDelta {
-        proto::HypothesisDelta { nodes, edges }
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Entry<K> {
+        WithRationale(K, BTreeSet<Provenance>, BTreeSet<Rationale>),
+        WithProvenance(K, BTreeSet<Provenance>),
+        Bare(K),
     }
 
-    // --- merge_hypothesis ---
-
-    #[tokio::test]
-    async fn merge_creates_new_nodes() {
-        let store = InMemoryStore::new();
-        let delta = make_delta(
-            vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
-            vec![],
-        );
-        let result = store.merge_hypothesis(delta).await.unwrap();
-        assert_eq!(result.created_ids, vec!["n1"]);
-        assert!(result.merged_ids.is_empty());
-        assert!(result.conflicts.is_empty());
+    pub fn serialize<K, S>(map: &BTreeMap<K, Tombstone>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize,
+        S: Serializer,
+    {
+        serializer.collect_seq(map.iter().map(|(key, t)| (key, &t.provenance, &t.rationale)))
     }
 
-    #[tokio::test]
-    async fn merge_idempotent_same_node() {
-        let store = InMemoryStore::new();
-        let delta = make_delta(
-            vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
-            vec![],
-        );
-        store.merge_hypothesis(delta.clone()).await.unwrap();
-        let result = store.merge_hypothesis(delta).await.unwrap();
-        assert!(result.created_ids.is_empty());
-        assert_eq!(result.merged_ids, vec!["n1"]);
-        assert!(result.conflicts.is_empty());
+    pub fn deserialize<'de, K, D>(deserializer: D) -> Result<BTreeMap<K, Tombstone>, D::Error>
+    where
+        K: Deserialize<'de> + Ord,
+        D: Deserializer<'de>,
+    {
+        Ok(Vec::<Entry<K>>::deserialize(deserializer)?
+            .into_iter()
+            .map(|entry| match entry {
+                Entry::WithRationale(key, provenance, rationale) => (
+                    key,
+                    Tombstone {
+                        provenance,
+                        rationale,
+                    },
+                ),
+                Entry::WithProvenance(key, provenance) => (
+                    key,
+                    Tombstone {
+                        provenance,
+                        ..Default::default()
+                    },
+                ),
+                Entry::Bare(key) => (key, Tombstone::default()),
+            })
+            .collect())
     }
+}
 
-    #[tokio::test]
-    async fn merge_detects_type_conflict() {
-        let store = InMemoryStore::new();
+/// In-memory implementation of the [`Store`] trait.
+///
+/// All state is held behind a [`RwLock`] for concurrent access.
+/// Uses the lattice-backed domain types directly — no external dependencies.
+///
+/// [`InMemoryStore::new`] keeps state in memory only. [`InMemoryStore::open`] adds a
+/// durable mode: every accepted write is appended to a write-ahead log before it is
+/// applied, and the log is periodically compacted into a snapshot. Log I/O runs on the
+/// blocking pool without the state lock, so reads are not held up by the disk.
+///
+/// Every successful write publishes its changes to [`Store::subscribe`] receivers.
+#[derive(Debug, Clone)]
+pub struct InMemoryStore {
+    state: Arc<RwLock<InnerState>>,
+    wal: Option<Arc<Mutex<Wal>>>,
+    /// Held by a durable write from its append until it is applied, so log order
+    /// matches apply order.
+    writer: Arc<AsyncMutex<()>>,
+    events: broadcast::Sender<StoreEvent>,
+}
+
+impl InMemoryStore {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(RwLock::new(InnerState::default())),
+            wal: None,
+            writer: Arc::default(),
+            events: broadcast::channel(EVENT_BUFFER).0,
+        }
+    }
+
+    /// Opens a durable store in `dir`, recovering from the snapshot and log already
+    /// there. A snapshot is written every `snapshot_every` logged writes (`0` = never).
+    pub fn open(dir: impl AsRef<Path>, snapshot_every: u64) -> Result<Self, StoreError> {
+        let Recovered {
+            wal,
+            snapshot,
+            records,
+        } = Wal::open(dir, snapshot_every).map_err(|e| StoreError::Backend(e.to_string()))?;
+
+        let mut state: InnerState = snapshot.unwrap_or_default();
+        state.reindex();
+        let replayed = records.len();
+        for op in records {
+            state.replay(op);
+        }
+        // Recovery restores state that was already published before the restart
+        state.outbox.clear();
+        tracing::info!(
+            "recovered in-memory store at version {} ({replayed} log records replayed)",
+            state.version
+        );
+
+        Ok(Self {
+            state: Arc::new(RwLock::new(state)),
+            wal: Some(Arc::new(Mutex::new(wal))),
+            writer: Arc::default(),
+            events: broadcast::channel(EVENT_BUFFER).0,
+        })
+    }
+
+    /// Applies `request` to the state, first logging it in durable mode. `check`
+    /// rejects a request before anything is logged; no other write runs between it and
+    /// `apply`. `op` is only built when logging.
+    async fn write_through<R, T>(
+        &self,
+        request: R,
+        check: impl FnOnce(&InnerState, &R) -> Result<(), StoreError>,
+        op: impl FnOnce(&R) -> Op,
+        apply: impl FnOnce(&mut InnerState, R) -> Result<T, StoreError>,
+    ) -> Result<T, StoreError> {
+        let Some(wal) = &self.wal else {
+            let mut state = self.state.write().await;
+            check(&state, &request)?;
+            let result = apply(&mut state, request);
+            self.publish(&mut state);
+            return result;
+        };
+        let _writing = self.writer.lock().await;
+        check(&*self.state.read().await, &request)?;
+        let record = op(&request);
+        let snapshot_due = on_log(wal, move |wal| {
+            wal.append(record)?;
+            Ok(wal.snapshot_due())
+        })
+        .await?;
+
+        let mut state = self.state.write().await;
+        let result = apply(&mut state, request);
+        self.publish(&mut state);
+        if snapshot_due {
+            let copy = state.downgrade().durable_copy();
+            // The write is already durable in the log; a failed snapshot only delays compaction.
+            if let Err(err) = on_log(wal, move |wal| wal.snapshot(&copy)).await {
+                tracing::warn!("in-memory store snapshot failed: {err}");
+            }
+        }
+        result
+    }
+
+    /// Sends the changes staged in `state.outbox` to subscribers. Called with the
+    /// state write lock held, so events arrive in apply order.
+    fn publish(&self, state: &mut InnerState) {
+        for event in state.outbox.drain(..) {
+            // No receivers is not an error: nobody is watching
+            let _ = self.events.send(event);
+        }
+    }
+}
+
+impl Default for InMemoryStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Runs `io` against the write-ahead log on the blocking pool.
+async fn on_log<T: Send + 'static>(
+    wal: &Arc<Mutex<Wal>>,
+    io: impl FnOnce(&mut Wal) -> Result<T, WalError> + Send + 'static,
+) -> Result<T, StoreError> {
+    let wal = wal.clone();
+    tokio::task::spawn_blocking(move || {
+        io(&mut wal.lock().expect("write-ahead log mutex poisoned"))
+    })
+    .await
+    .map_err(|e| StoreError::Backend(e.to_string()))?
+    .map_err(|e| StoreError::Backend(e.to_string()))
+}
+
+impl Store for InMemoryStore {
+    async fn merge_hypothesis(
+        &self,
+        delta: proto::HypothesisDelta,
+        schema: Option<&CausalSchema>,
+    ) -> Result<proto::HypothesisMergeResult, StoreError> {
+        self.write_through(
+            delta,
+            // No write can retype an endpoint between this check and the merge
+            |state, delta| {
+                if let Some(schema) = schema {
+                    let mut stored = state.node_types(schema_node_ids(delta));
+                    let stored_edges =
+                        state.edges_touching(&validation::first_typed_nodes(delta, &stored));
+                    stored.extend(state.node_types(unknown_endpoints(&stored_edges, &stored)));
+                    validation::validate_edge_schema(delta, schema, &stored, &stored_edges)?;
+                }
+                Ok(())
+            },
+            |delta| Op::MergeHypothesis(delta.clone()),
+            InnerState::merge_hypothesis,
+        )
+        .await
+    }
+
+    async fn confirm_nodes(
+        &self,
+        request: proto::ConfirmNodesRequest,
+    ) -> Result<proto::ConfirmNodesResult, StoreError> {
+        self.write_through(
+            request,
+            |_, _| Ok(()),
+            |request| Op::ConfirmNodes(request.clone()),
+            InnerState::confirm_nodes,
+        )
+        .await
+    }
+
+    async fn create_incident(
+        &self,
+        request: proto::CreateIncidentRequest,
+    ) -> Result<proto::CreateIncidentResult, StoreError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let created_at = (now.as_secs() as i64, now.subsec_nanos() as i32);
+        {
+            let mut state = self.state.write().await;
+            if state.incidents.contains_key(&request.incident_id) {
+                // Re-creating an incident changes nothing, so there is nothing to log
+                return Ok(state.create_incident(
+                    request.incident_id,
+                    request.follow_main_graph,
+                    created_at,
+                ));
+            }
+        }
+
+        self.write_through(
+            request,
+            |_, _| Ok(()),
+            |request| {
+                Op::CreateIncident(IncidentCreated {
+                    incident_id: request.incident_id.clone(),
+                    follow_main_graph: request.follow_main_graph,
+                    created_at: Some(prost_types::Timestamp {
+                        seconds: created_at.0,
+                        nanos: created_at.1,
+                    }),
+                })
+            },
+            |state, request| {
+                Ok(state.create_incident(
+                    request.incident_id,
+                    request.follow_main_graph,
+                    created_at,
+                ))
+            },
+        )
+        .await
+    }
+
+    async fn get_incident_context(
+        &self,
+        incident_id: &str,
+    ) -> Result<proto::IncidentContext, StoreError> {
+        let state = self.state.read().await;
+        let incident = state
+            .incidents
+            .get(incident_id)
+            .ok_or_else(|| StoreError::IncidentNotFound(incident_id.to_string()))?;
+
+        Ok(proto::IncidentContext {
+            incident_id: incident_id.to_string(),
+            created_at: Some(prost_types::Timestamp {
+                seconds: incident.created_at.0,
+                nanos: incident.created_at.1,
+            }),
+            tombstones: Some(incident.tombstone_set()),
+            universe_anchor: state.anchor_of(incident),
+            elimination_set_id: elimination_set_id(incident_id),
+            follows_main_graph: incident.universe_anchor.is_none(),
+        })
+    }
+
+    async fn get_incident_metrics(
+        &self,
+        incident_id: &str,
+        as_of_version: Option<u64>,
+    ) -> Result<proto::IncidentMetrics, StoreError> {
+        let state = self.state.read().await;
+        let incident = state
+            .incidents
+            .get(incident_id)
+            .ok_or_else(|| StoreError::IncidentNotFound(incident_id.to_string()))?;
+        let version = resolve_as_of(as_of_version, state.anchor_of(incident))?;
+        Ok(state.metrics(incident_id, incident, version))
+    }
+
+    async fn merge_node_tombstones(
+        &self,
+        request: proto::NodeTombstoneRequest,
+    ) -> Result<proto::TombstoneMergeResult, StoreError> {
+        self.write_through(
+            request,
+            |state, request| state.require_incident(&request.incident_id),
+            |request| Op::MergeNodeTombstones(request.clone()),
+            InnerState::merge_node_tombstones,
+        )
+        .await
+    }
+
+    async fn merge_edge_tombstones(
+        &self,
+        request: proto::EdgeTombstoneRequest,
+    ) -> Result<proto::TombstoneMergeResult, StoreError> {
+        self.write_through(
+            request,
+            |state, request| state.require_incident(&request.incident_id),
+            |request| Op::MergeEdgeTombstones(request.clone()),
+            InnerState::merge_edge_tombstones,
+        )
+        .await
+    }
+
+    async fn get_live_view(
+        &self,
+        incident_id: &str,
+        query: &GraphQuery,
+    ) -> Result<proto::CausalGraph, StoreError> {
+        let state = self.state.read().await;
+        let incident = state
+            .incidents
+            .get(incident_id)
+            .ok_or_else(|| StoreError::IncidentNotFound(incident_id.to_string()))?;
+        let version = query.resolve_version(state.anchor_of(incident))?;
+        Ok(state.read_page(query, version, Some(incident)))
+    }
+
+    async fn get_neighborhood(
+        &self,
+        incident_id: &str,
+        query: &NeighborhoodQuery,
+    ) -> Result<proto::NeighborhoodResult, StoreError> {
+        let state = self.state.read().await;
+        let incident = state
+            .incidents
+            .get(incident_id)
+            .ok_or_else(|| StoreError::IncidentNotFound(incident_id.to_string()))?;
+        let version = resolve_as_of(query.as_of_version, state.anchor_of(incident))?;
+        Ok(state.neighborhood(query, version, incident))
+    }
+
+    async fn get_tombstones(
+        &self,
+        incident_id: &str,
+    ) -> Result<proto::TombstoneSet, StoreError> {
+        let state = self.state.read().await;
+        let incident = state
+            .incidents
+            .get(incident_id)
+            .ok_or_else(|| StoreError::IncidentNotFound(incident_id.to_string()))?;
+
+        Ok(incident.tombstone_set())
+    }
+
+    async fn get_main_graph(&self, query: &GraphQuery) -> Result<proto::CausalGraph, StoreError> {
+        let state = self.state.read().await;
+        let version = query.resolve_version(state.version)?;
+        Ok(state.read_page(query, version, None))
+    }
+
+    async fn get_main_graph_changes(
+        &self,
+        after_cursor: u64,
+        max_versions: u64,
+    ) -> Result<Vec<proto::MainGraphChange>, StoreError> {
+        let state = self.state.read().await;
+        resolve_as_of(Some(after_cursor), state.version)?;
+
+        let through = after_cursor.saturating_add(max_versions);
+        let mut nodes = BTreeSet::new();
+        let mut edges = BTreeSet::new();
+        let window = (Bound::Excluded(after_cursor), Bound::Included(through));
+        for touched in state.changes.range(window).map(|(_, touched)| touched) {
+            nodes.extend(&touched.nodes);
+            edges.extend(&touched.edges);
+        }
+
+        let mut log = ChangeLog::between(after_cursor, through);
+        let nodes = nodes
+            .into_iter()
+            .filter_map(|id| state.nodes.get_key_value(id));
+        for (id, lattice) in nodes {
+            let confirmed = *lattice.confirmed_version.as_reveal_ref();
+            log.node(
+                domain_node_to_proto(id.clone(), lattice),
+                *lattice.created_version.as_reveal_ref(),
+                (confirmed != u64::MAX).then_some(confirmed),
+                lattice.refined.clone(),
+            );
+        }
+        let edges = edges
+            .into_iter()
+            .filter_map(|key| state.edges.get_key_value(key));
+        for (key, lattice) in edges {
+            log.edge(
+                domain_edge_to_proto(key, lattice),
+                *lattice.created_version.as_reveal_ref(),
+            );
+        }
+        Ok(log.into_changes())
+    }
+
+    async fn graph_size(&self) -> Result<GraphSize, StoreError> {
+        let state = self.state.read().await;
+        let incidents = state.incidents.values();
+        Ok(GraphSize {
+            version: state.version,
+            nodes: state.nodes.len() as u64,
+            edges: state.edges.len() as u64,
+            incidents: state.incidents.len() as u64,
+            node_tombstones: incidents
+                .clone()
+                .map(|i| i.node_tombstones.len() as u64)
+                .sum(),
+            edge_tombstones: incidents.map(|i| i.edge_tombstones.len() as u64).sum(),
+        })
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<StoreEvent> {
+        self.events.subscribe()
+    }
+
+    async fn check_health(&self) -> Result<(), StoreError> {
+        // A write that panicked mid-append leaves the log unusable for later writes
+        match &self.wal {
+            Some(wal) if wal.is_poisoned() => {
+                Err(StoreError::Backend("write-ahead log mutex poisoned".into()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::validation::ValidationError;
+    use crate::store::neighborhood::Direction;
+    use crate::store::query::{GraphFilter, PageToken};
+
+    fn make_node(id: &str, node_type: i32, label: &str) -> proto::Node {
+        proto::Node {
+            id: id.into(),
+            r#type: node_type,
+            label: label.into(),
+            hypothetical: true,
+            provenance: vec![proto::Provenance {
+                source: "agent-1".into(),
+                trigger: "alert".into(),
+                timestamp: None,
+                version: 0,
+            }],
+            kind: String::new(),
+        }
+    }
+
+    fn make_edge(source: &str, target: &str, edge_type: i32) -> proto::Edge {
+        proto::Edge {
+            source: source.into(),
+            target: target.into(),
+            r#type: edge_type,
+            provenance: vec![proto::Provenance {
+                source: "agent-1".into(),
+                trigger: "alert".into(),
+                timestamp: None,
+                version: 0,
+            }],
+            kind: String::new(),
+        }
+    }
+
+    fn make_delta(nodes: Vec<proto::Node>, edges: Vec<proto::Edge>) -> proto::HypothesisDelta {
+        proto::HypothesisDelta {
+            nodes,
+            edges,
+            atomic: false,
+            dangling_edge_policy: proto::DanglingEdgePolicy::Report as i32,
+        }
+    }
+
+    fn create_request(incident_id: &str) -> proto::CreateIncidentRequest {
+        proto::CreateIncidentRequest {
+            incident_id: incident_id.into(),
+            follow_main_graph: false,
+        }
+    }
+
+    // --- merge_hypothesis ---
+
+    #[tokio::test]
+    async fn merge_creates_new_nodes() {
+        let store = InMemoryStore::new();
+        let delta = make_delta(
+            vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
+            vec![],
+        );
+        let result = store.merge_hypothesis(delta, None).await.unwrap();
+        assert_eq!(result.created_ids, vec!["n1"]);
+        assert!(result.merged_ids.is_empty());
+        assert!(result.conflicts.is_empty());
+    }
+
+    #[tokio::test]
+    async fn merge_idempotent_same_node() {
+        let store = InMemoryStore::new();
+        let delta = make_delta(
+            vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
+            vec![],
+        );
+        store.merge_hypothesis(delta.clone(), None).await.unwrap();
+        let result = store.merge_hypothesis(delta, None).await.unwrap();
+        assert!(result.created_ids.is_empty());
+        assert_eq!(result.merged_ids, vec!["n1"]);
+        assert!(result.conflicts.is_empty());
+    }
+
+    #[tokio::test]
+    async fn merge_detects_type_conflict() {
+        let store = InMemoryStore::new();
         let delta1 = make_delta(
             vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
             vec![],
         );
-        store.merge_hypothesis(delta1).await.unwrap();
+        store.merge_hypothesis(delta1, None).await.unwrap();
 
         let delta2 = make_delta(
             vec![make_node(
@@ -434,12 +1312,64 @@ mod tests {
             )],
             vec![],
         );
-        let result = store.merge_hypothesis(delta2).await.unwrap();
+        let result = store.merge_hypothesis(delta2, None).await.unwrap();
         assert!(result.created_ids.is_empty());
         assert!(result.merged_ids.is_empty());
         assert_eq!(result.conflicts.len(), 1);
         assert_eq!(result.conflicts[0].id, "n1");
         assert_eq!(result.conflicts[0].field, "type");
+        assert_eq!(result.conflicts[0].existing_value, "SERVICE");
+        assert_eq!(result.conflicts[0].proposed_value, "INFRASTRUCTURE");
+    }
+
+    #[tokio::test]
+    async fn merge_reports_every_conflicting_field_with_first_writer() {
+        let store = InMemoryStore::new();
+        store
+            .merge_hypothesis(
+                make_delta(
+                    vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
+                    vec![],
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+        // A later compatible write adds provenance that is not the first writer's
+        let mut later = make_node("n1", proto::NodeType::Service as i32, "svc");
+        later.provenance[0].source = "agent-2".into();
+        store
+            .merge_hypothesis(make_delta(vec![later], vec![]), None)
+            .await
+            .unwrap();
+
+        let result = store
+            .merge_hypothesis(
+                make_delta(
+                    vec![make_node(
+                        "n1",
+                        proto::NodeType::Infrastructure as i32,
+                        "db",
+                    )],
+                    vec![],
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.conflicts.len(), 2);
+        let type_conflict = &result.conflicts[0];
+        assert_eq!(type_conflict.field, "type");
+        assert_eq!(type_conflict.proposed_value, "INFRASTRUCTURE");
+        let label_conflict = &result.conflicts[1];
+        assert_eq!(label_conflict.field, "label");
+        assert_eq!(label_conflict.existing_value, "svc");
+        assert_eq!(label_conflict.proposed_value, "db");
+        for conflict in &result.conflicts {
+            assert_eq!(conflict.first_writer_provenance.len(), 1);
+            assert_eq!(conflict.first_writer_provenance[0].source, "agent-1");
+            assert_eq!(conflict.first_writer_provenance[0].version, 1);
+        }
     }
 
     #[tokio::test]
@@ -449,7 +1379,7 @@ mod tests {
             vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
             vec![],
         );
-        store.merge_hypothesis(delta1).await.unwrap();
+        store.merge_hypothesis(delta1, None).await.unwrap();
 
         // Attempt conflicting merge
         let delta2 = make_delta(
@@ -460,293 +1390,1526 @@ mod tests {
             )],
             vec![],
         );
-        store.merge_hypothesis(delta2).await.unwrap();
+        store.merge_hypothesis(delta2, None).await.unwrap();
+
+        // Original should be intact — re-merge same type should work
+        let delta3 = make_delta(
+            vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
+            vec![],
+        );
+        let result = store.merge_hypothesis(delta3, None).await.unwrap();
+        assert!(result.conflicts.is_empty());
+        assert_eq!(result.merged_ids, vec!["n1"]);
+    }
+
+    #[tokio::test]
+    async fn non_atomic_merge_applies_around_conflicts() {
+        let store = InMemoryStore::new();
+        store
+            .merge_hypothesis(
+                make_delta(
+                    vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
+                    vec![],
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let result = store
+            .merge_hypothesis(
+                make_delta(
+                    vec![
+                        make_node("n1", proto::NodeType::Service as i32, "renamed"),
+                        make_node("n2", proto::NodeType::Service as i32, "svc2"),
+                    ],
+                    vec![],
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.created_ids, vec!["n2"]);
+        assert_eq!(store.get_main_graph(&GraphQuery::default()).await.unwrap().nodes.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn atomic_merge_with_conflicts_writes_nothing() {
+        let store = InMemoryStore::new();
+        store
+            .merge_hypothesis(
+                make_delta(
+                    vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
+                    vec![],
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let mut delta = make_delta(
+            vec![
+                make_node("n2", proto::NodeType::Service as i32, "svc2"),
+                make_node("n1", proto::NodeType::Infrastructure as i32, "svc"),
+                make_node("n3", proto::NodeType::Service as i32, "svc3"),
+                make_node("n3", proto::NodeType::Service as i32, "other"),
+            ],
+            vec![make_edge("n2", "n3", proto::EdgeType::DependsOn as i32)],
+        );
+        delta.atomic = true;
+        let result = store.merge_hypothesis(delta, None).await.unwrap();
+
+        // Both the stored conflict and the in-batch one are reported
+        let conflicted: Vec<_> = result.conflicts.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(conflicted, vec!["n1", "n3"]);
+        assert!(result.created_ids.is_empty());
+        assert!(result.merged_ids.is_empty());
+        assert_eq!(result.version, 1);
+
+        let graph = store.get_main_graph(&GraphQuery::default()).await.unwrap();
+        assert_eq!(graph.version, 1);
+        assert_eq!(graph.nodes.len(), 1);
+        assert!(graph.edges.is_empty());
+    }
+
+    #[tokio::test]
+    async fn atomic_merge_without_conflicts_applies() {
+        let store = InMemoryStore::new();
+        let mut delta = make_delta(
+            vec![
+                make_node("n1", proto::NodeType::Service as i32, "svc1"),
+                make_node("n2", proto::NodeType::Service as i32, "svc2"),
+            ],
+            vec![make_edge("n1", "n2", proto::EdgeType::DependsOn as i32)],
+        );
+        delta.atomic = true;
+        let result = store.merge_hypothesis(delta, None).await.unwrap();
+        assert!(result.conflicts.is_empty());
+        assert_eq!(result.created_ids.len(), 3);
+        assert_eq!(result.version, 1);
+    }
+
+    #[tokio::test]
+    async fn conversion_error_leaves_no_partial_state() {
+        let store = InMemoryStore::new();
+        let result = store
+            .merge_hypothesis(
+                make_delta(
+                    vec![
+                        make_node("n1", proto::NodeType::Service as i32, "svc"),
+                        make_node("n2", 99, "bad"),
+                    ],
+                    vec![],
+                ),
+                None,
+            )
+            .await;
+        assert!(matches!(result, Err(StoreError::Backend(_))));
+
+        let graph = store.get_main_graph(&GraphQuery::default()).await.unwrap();
+        assert_eq!(graph.version, 0);
+        assert!(graph.nodes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn merge_creates_and_merges_edges() {
+        let store = InMemoryStore::new();
+        let delta1 = make_delta(
+            vec![],
+            vec![make_edge("a", "b", proto::EdgeType::DependsOn as i32)],
+        );
+        let result1 = store.merge_hypothesis(delta1, None).await.unwrap();
+        assert_eq!(result1.created_ids.len(), 1);
+
+        let delta2 = make_delta(
+            vec![],
+            vec![make_edge("a", "b", proto::EdgeType::DependsOn as i32)],
+        );
+        let result2 = store.merge_hypothesis(delta2, None).await.unwrap();
+        assert_eq!(result2.merged_ids.len(), 1);
+    }
+
+    // --- confirm_nodes ---
+
+    fn confirm(node_ids: &[&str], source: &str) -> proto::ConfirmNodesRequest {
+        proto::ConfirmNodesRequest {
+            node_ids: node_ids.iter().map(|id| id.to_string()).collect(),
+            provenance: Some(proto::Provenance {
+                source: source.into(),
+                trigger: "review".into(),
+                ..Default::default()
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn confirm_nodes_splits_ids_and_bumps_version() {
+        let store = InMemoryStore::new();
+        store
+            .merge_hypothesis(
+                make_delta(
+                    vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
+                    vec![],
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+        let mut events = store.subscribe();
+
+        let result = store
+            .confirm_nodes(confirm(&["n1", "ghost"], "reviewer"))
+            .await
+            .unwrap();
+        assert_eq!(result.confirmed_ids, vec!["n1"]);
+        assert_eq!(result.unknown_ids, vec!["ghost"]);
+        assert_eq!(result.version, 2);
+        assert_eq!(
+            drain(&mut events),
+            [StoreEvent::NodeConfirmed {
+                version: 2,
+                node_id: "n1".into()
+            }]
+        );
+
+        // Idempotent: the same writer again changes nothing
+        let again = store
+            .confirm_nodes(confirm(&["n1"], "reviewer"))
+            .await
+            .unwrap();
+        assert_eq!(again.already_confirmed_ids, vec!["n1"]);
+        assert_eq!(again.version, 2);
+        assert!(drain(&mut events).is_empty());
+
+        let graph = store.get_main_graph(&GraphQuery::default()).await.unwrap();
+        assert!(!graph.nodes[0].hypothetical);
+        assert_eq!(graph.nodes[0].provenance.len(), 2);
+        let past = GraphQuery {
+            as_of_version: Some(1),
+            ..Default::default()
+        };
+        assert!(store.get_main_graph(&past).await.unwrap().nodes[0].hypothetical);
+
+        let changes = store.get_main_graph_changes(1, u64::MAX).await.unwrap();
+        assert_eq!(changes[0].confirmed_node_ids, vec!["n1"]);
+        assert_eq!(changes[0].provenance_appends[0].provenance[0].source, "reviewer");
+    }
+
+    // --- dangling edges ---
+
+    fn dangling_delta(policy: proto::DanglingEdgePolicy) -> proto::HypothesisDelta {
+        // a -> b resolves against the node in the same delta; a -> c dangles
+        let mut delta = make_delta(
+            vec![
+                make_node("a", proto::NodeType::Service as i32, "svc-a"),
+                make_node("b", proto::NodeType::Service as i32, "svc-b"),
+            ],
+            vec![
+                make_edge("a", "b", proto::EdgeType::DependsOn as i32),
+                make_edge("a", "c", proto::EdgeType::DependsOn as i32),
+            ],
+        );
+        delta.set_dangling_edge_policy(policy);
+        delta
+    }
+
+    #[tokio::test]
+    async fn report_policy_writes_and_lists_dangling_edges() {
+        let store = InMemoryStore::new();
+        let result = store
+            .merge_hypothesis(dangling_delta(proto::DanglingEdgePolicy::Report), None)
+            .await
+            .unwrap();
+        assert_eq!(result.dangling_edge_ids, vec!["a->c:1"]);
+        assert!(result.created_ids.contains(&"a->c:1".to_string()));
+        assert_eq!(store.get_main_graph(&GraphQuery::default()).await.unwrap().edges.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn reject_policy_skips_dangling_edges() {
+        let store = InMemoryStore::new();
+        let result = store
+            .merge_hypothesis(dangling_delta(proto::DanglingEdgePolicy::Reject), None)
+            .await
+            .unwrap();
+        assert_eq!(result.dangling_edge_ids, vec!["a->c:1"]);
+        assert_eq!(result.created_ids, vec!["a", "b", "a->b:1"]);
+
+        let graph = store.get_main_graph(&GraphQuery::default()).await.unwrap();
+        assert_eq!(graph.edges.len(), 1);
+        assert_eq!(graph.edges[0].target, "b");
+    }
+
+    #[tokio::test]
+    async fn reject_policy_aborts_atomic_delta() {
+        let store = InMemoryStore::new();
+        let mut delta = dangling_delta(proto::DanglingEdgePolicy::Reject);
+        delta.atomic = true;
+        let result = store.merge_hypothesis(delta, None).await.unwrap();
+        assert_eq!(result.dangling_edge_ids, vec!["a->c:1"]);
+        assert!(result.created_ids.is_empty());
+
+        let graph = store.get_main_graph(&GraphQuery::default()).await.unwrap();
+        assert_eq!(graph.version, 0);
+        assert!(graph.nodes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn placeholder_policy_creates_missing_endpoints() {
+        let store = InMemoryStore::new();
+        let result = store
+            .merge_hypothesis(dangling_delta(proto::DanglingEdgePolicy::Placeholder), None)
+            .await
+            .unwrap();
+        assert!(result.dangling_edge_ids.is_empty());
+        assert!(result.created_ids.contains(&"c".to_string()));
+
+        let graph = store.get_main_graph(&GraphQuery::default()).await.unwrap();
+        let c = graph.nodes.iter().find(|n| n.id == "c").unwrap();
+        assert_eq!(c.r#type, proto::NodeType::Placeholder as i32);
+        assert_eq!(c.label, "c");
+
+        // The first real write of the node replaces the placeholder without conflict
+        let result = store
+            .merge_hypothesis(
+                make_delta(
+                    vec![make_node("c", proto::NodeType::Infrastructure as i32, "db")],
+                    vec![],
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(result.conflicts.is_empty());
+        assert_eq!(result.merged_ids, vec!["c"]);
+        let graph = store.get_main_graph(&GraphQuery::default()).await.unwrap();
+        let c = graph.nodes.iter().find(|n| n.id == "c").unwrap();
+        assert_eq!(c.r#type, proto::NodeType::Infrastructure as i32);
+        assert_eq!(c.label, "db");
+
+        // Reads between the two writes still see the placeholder
+        let past = store.get_main_graph(&GraphQuery::as_of(1)).await.unwrap();
+        let c = past.nodes.iter().find(|n| n.id == "c").unwrap();
+        assert_eq!(c.r#type, proto::NodeType::Placeholder as i32);
+        assert_eq!(c.label, "c");
+        assert_eq!(c.provenance.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn placeholders_are_created_at_the_next_version() {
+        let store = InMemoryStore::new();
+        let edge = || make_edge("a", "b", proto::EdgeType::DependsOn as i32);
+        store
+            .merge_hypothesis(make_delta(vec![], vec![edge()]), None)
+            .await
+            .unwrap();
+
+        // The edge already exists; only its placeholders are new
+        let mut delta = make_delta(vec![], vec![edge()]);
+        delta.set_dangling_edge_policy(proto::DanglingEdgePolicy::Placeholder);
+        let result = store.merge_hypothesis(delta, None).await.unwrap();
+        assert_eq!(result.version, 2);
+        assert_eq!(result.created_ids, vec!["a", "b"]);
+
+        let past = store.get_main_graph(&GraphQuery::as_of(1)).await.unwrap();
+        assert!(past.nodes.is_empty());
+        let head = store.get_main_graph(&GraphQuery::default()).await.unwrap();
+        assert!(head.nodes.iter().all(|n| n.provenance[0].version == 2));
+
+        let changes = store.get_main_graph_changes(0, u64::MAX).await.unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[1].cursor, 2);
+        let created: Vec<_> = changes[1].created_nodes.iter().map(|n| &n.id).collect();
+        assert_eq!(created, ["a", "b"]);
+
+        // A conflicting write names the placeholder's writer
+        let mut first = make_node("a", proto::NodeType::Service as i32, "svc");
+        first.provenance[0].source = "agent-2".into();
+        store
+            .merge_hypothesis(make_delta(vec![first], vec![]), None)
+            .await
+            .unwrap();
+        let result = store
+            .merge_hypothesis(
+                make_delta(
+                    vec![make_node("a", proto::NodeType::Service as i32, "other")],
+                    vec![],
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+        let first_writers: Vec<_> = result.conflicts[0]
+            .first_writer_provenance
+            .iter()
+            .map(|p| p.source.as_str())
+            .collect();
+        assert_eq!(first_writers, ["agent-1"]);
+    }
+
+    #[tokio::test]
+    async fn schema_checks_edges_once_their_endpoints_are_typed() {
+        let store = InMemoryStore::new();
+        let schema = CausalSchema::new([(
+            NodeType::Mechanism,
+            EdgeType::ManifestsAs,
+            NodeType::Service,
+        )]);
+        let manifests =
+            |target: &str| make_edge("oom", target, proto::EdgeType::ManifestsAs as i32);
+        let typed = |id: &str, node_type: proto::NodeType| {
+            make_delta(vec![make_node(id, node_type as i32, id)], vec![])
+        };
+
+        // REJECT turns away an edge it cannot check yet
+        let mut delta = make_delta(vec![], vec![manifests("api")]);
+        delta.set_dangling_edge_policy(proto::DanglingEdgePolicy::Reject);
+        let err = store.merge_hypothesis(delta, Some(&schema)).await.unwrap_err();
+        assert!(matches!(
+            err,
+            StoreError::SchemaViolation(ValidationError::UntypedEndpoint { .. })
+        ));
+        assert_eq!(store.graph_size().await.unwrap(), GraphSize::default());
+
+        // PLACEHOLDER and REPORT store it, and the endpoints' first writes are checked
+        let mut delta = make_delta(vec![], vec![manifests("api")]);
+        delta.set_dangling_edge_policy(proto::DanglingEdgePolicy::Placeholder);
+        store.merge_hypothesis(delta, Some(&schema)).await.unwrap();
+        let mut delta = make_delta(vec![], vec![manifests("web")]);
+        delta.set_dangling_edge_policy(proto::DanglingEdgePolicy::Report);
+        let result = store.merge_hypothesis(delta, Some(&schema)).await.unwrap();
+        assert_eq!(result.dangling_edge_ids.len(), 1);
+        store
+            .merge_hypothesis(typed("oom", proto::NodeType::Mechanism), Some(&schema))
+            .await
+            .unwrap();
+
+        for id in ["api", "web"] {
+            let before = store.graph_size().await.unwrap();
+            let err = store
+                .merge_hypothesis(typed(id, proto::NodeType::Infrastructure), Some(&schema))
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err,
+                StoreError::SchemaViolation(ValidationError::EdgeEndpointsNotPermitted {
+                    target_type: NodeType::Infrastructure,
+                    ..
+                })
+            ));
+            assert_eq!(store.graph_size().await.unwrap(), before);
+            store
+                .merge_hypothesis(typed(id, proto::NodeType::Service), Some(&schema))
+                .await
+                .unwrap();
+        }
+        let graph = store.get_main_graph(&GraphQuery::default()).await.unwrap();
+        assert!(graph
+            .nodes
+            .iter()
+            .all(|n| n.r#type != proto::NodeType::Placeholder as i32));
+    }
+
+    // --- registered kinds ---
+
+    #[tokio::test]
+    async fn registered_kinds_merge_and_tombstone() {
+        let store = InMemoryStore::new();
+        let mut deploy = make_node("d1", proto::NodeType::Unspecified as i32, "deploy-42");
+        deploy.kind = "DEPLOYMENT".into();
+        let mut edge = make_edge("d1", "n1", proto::EdgeType::Unspecified as i32);
+        edge.kind = "CORRELATES_WITH".into();
+        let result = store
+            .merge_hypothesis(
+                make_delta(
+                    vec![
+                        deploy,
+                        make_node("n1", proto::NodeType::Service as i32, "svc"),
+                    ],
+                    vec![edge],
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.created_ids, vec!["d1", "n1", "d1->n1:CORRELATES_WITH"]);
+
+        let graph = store.get_main_graph(&GraphQuery::default()).await.unwrap();
+        let d1 = graph.nodes.iter().find(|n| n.id == "d1").unwrap();
+        assert_eq!(d1.kind, "DEPLOYMENT");
+        assert_eq!(d1.r#type, proto::NodeType::Unspecified as i32);
+        assert_eq!(graph.edges[0].kind, "CORRELATES_WITH");
+
+        store.create_incident(create_request("inc-1")).await.unwrap();
+        let result = store
+            .merge_edge_tombstones(proto::EdgeTombstoneRequest {
+                incident_id: "inc-1".into(),
+                entries: vec![proto::EdgeTombstoneEntry {
+                    source: "d1".into(),
+                    target: "n1".into(),
+                    r#type: proto::EdgeType::Unspecified as i32,
+                    kind: "CORRELATES_WITH".into(),
+                }],
+                provenance: None,
+                rationale: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(result.applied_ids, vec!["d1->n1:CORRELATES_WITH"]);
+        let view = store.get_live_view("inc-1", &GraphQuery::default()).await.unwrap();
+        assert!(view.edges.is_empty());
+    }
+
+    // --- create_incident ---
+
+    #[tokio::test]
+    async fn create_incident_new() {
+        let store = InMemoryStore::new();
+        let result = store
+            .create_incident(create_request("inc-1"))
+            .await
+            .unwrap();
+        assert!(result.created);
+        assert_eq!(result.incident_id, "inc-1");
+    }
+
+    #[tokio::test]
+    async fn create_incident_idempotent() {
+        let store = InMemoryStore::new();
+        store.create_incident(create_request("inc-1")).await.unwrap();
+        let result = store
+            .create_incident(create_request("inc-1"))
+            .await
+            .unwrap();
+        assert!(!result.created);
+    }
+
+    // --- get_incident_context ---
+
+    #[tokio::test]
+    async fn get_incident_context_not_found() {
+        let store = InMemoryStore::new();
+        let result = store.get_incident_context("nope").await;
+        assert!(matches!(result, Err(StoreError::IncidentNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn get_incident_context_returns_tombstones() {
+        let store = InMemoryStore::new();
+        store.create_incident(create_request("inc-1")).await.unwrap();
+
+        // Add a node to the main graph first
+        let delta = make_delta(
+            vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
+            vec![],
+        );
+        store.merge_hypothesis(delta, None).await.unwrap();
+
+        // Tombstone it
+        store
+            .merge_node_tombstones(proto::NodeTombstoneRequest {
+                incident_id: "inc-1".into(),
+                node_ids: vec!["n1".into()],
+                provenance: Some(proto::Provenance {
+                    source: "agent".into(),
+                    trigger: "elim".into(),
+                    timestamp: None,
+                    version: 0,
+                }),
+                rationale: None,
+            })
+            .await
+            .unwrap();
+
+        let ctx = store.get_incident_context("inc-1").await.unwrap();
+        assert_eq!(ctx.incident_id, "inc-1");
+        assert!(ctx.created_at.is_some());
+        let tombstones = ctx.tombstones.unwrap();
+        assert_eq!(tombstones.node_ids, vec!["n1"]);
+    }
+
+    // --- merge_node_tombstones ---
+
+    #[tokio::test]
+    async fn tombstone_applied_for_existing_node() {
+        let store = InMemoryStore::new();
+        store.create_incident(create_request("inc-1")).await.unwrap();
+
+        let delta = make_delta(
+            vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
+            vec![],
+        );
+        store.merge_hypothesis(delta, None).await.unwrap();
+
+        let result = store
+            .merge_node_tombstones(proto::NodeTombstoneRequest {
+                incident_id: "inc-1".into(),
+                node_ids: vec!["n1".into()],
+                provenance: Some(proto::Provenance {
+                    source: "agent".into(),
+                    trigger: "elim".into(),
+                    timestamp: None,
+                    version: 0,
+                }),
+                rationale: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(result.applied_ids, vec!["n1"]);
+        assert!(result.already_tombstoned_ids.is_empty());
+        assert!(result.unmatched_ids.is_empty());
+    }
+
+    #[tokio::test]
+    async fn tombstone_unmatched_for_missing_node() {
+        let store = InMemoryStore::new();
+        store.create_incident(create_request("inc-1")).await.unwrap();
+
+        let result = store
+            .merge_node_tombstones(proto::NodeTombstoneRequest {
+                incident_id: "inc-1".into(),
+                node_ids: vec!["ghost".into()],
+                provenance: Some(proto::Provenance {
+                    source: "agent".into(),
+                    trigger: "elim".into(),
+                    timestamp: None,
+                    version: 0,
+                }),
+                rationale: None,
+            })
+            .await
+            .unwrap();
+        assert!(result.applied_ids.is_empty());
+        assert_eq!(result.unmatched_ids, vec!["ghost"]);
+    }
+
+    #[tokio::test]
+    async fn tombstone_idempotent() {
+        let store = InMemoryStore::new();
+        store.create_incident(create_request("inc-1")).await.unwrap();
+
+        let delta = make_delta(
+            vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
+            vec![],
+        );
+        store.merge_hypothesis(delta, None).await.unwrap();
+
+        let req = proto::NodeTombstoneRequest {
+            incident_id: "inc-1".into(),
+            node_ids: vec!["n1".into()],
+            provenance: Some(proto::Provenance {
+                source: "agent".into(),
+                trigger: "elim".into(),
+                timestamp: None,
+                version: 0,
+            }),
+            rationale: None,
+        };
+        store.merge_node_tombstones(req.clone()).await.unwrap();
+        let result = store.merge_node_tombstones(req).await.unwrap();
+        assert!(result.applied_ids.is_empty());
+        assert_eq!(result.already_tombstoned_ids, vec!["n1"]);
+    }
+
+    // --- merge_edge_tombstones ---
+
+    #[tokio::test]
+    async fn edge_tombstone_applied() {
+        let store = InMemoryStore::new();
+        store.create_incident(create_request("inc-1")).await.unwrap();
+
+        let delta = make_delta(
+            vec![],
+            vec![make_edge("a", "b", proto::EdgeType::DependsOn as i32)],
+        );
+        store.merge_hypothesis(delta, None).await.unwrap();
+
+        let result = store
+            .merge_edge_tombstones(proto::EdgeTombstoneRequest {
+                incident_id: "inc-1".into(),
+                entries: vec![proto::EdgeTombstoneEntry {
+                    source: "a".into(),
+                    target: "b".into(),
+                    r#type: proto::EdgeType::DependsOn as i32,
+                    kind: String::new(),
+                }],
+                provenance: Some(proto::Provenance {
+                    source: "agent".into(),
+                    trigger: "elim".into(),
+                    timestamp: None,
+                    version: 0,
+                }),
+                rationale: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(result.applied_ids.len(), 1);
+    }
+
+    // --- get_live_view ---
+
+    #[tokio::test]
+    async fn live_view_filters_tombstoned_nodes() {
+        let store = InMemoryStore::new();
+        let delta = make_delta(
+            vec![
+                make_node("n1", proto::NodeType::Service as i32, "svc1"),
+                make_node("n2", proto::NodeType::Service as i32, "svc2"),
+            ],
+            vec![],
+        );
+        store.merge_hypothesis(delta, None).await.unwrap();
+        store.create_incident(create_request("inc-1")).await.unwrap();
+
+        store
+            .merge_node_tombstones(proto::NodeTombstoneRequest {
+                incident_id: "inc-1".into(),
+                node_ids: vec!["n1".into()],
+                provenance: Some(proto::Provenance {
+                    source: "agent".into(),
+                    trigger: "elim".into(),
+                    timestamp: None,
+                    version: 0,
+                }),
+                rationale: None,
+            })
+            .await
+            .unwrap();
+
+        let view = store.get_live_view("inc-1", &GraphQuery::default()).await.unwrap();
+        assert_eq!(view.nodes.len(), 1);
+        assert_eq!(view.nodes[0].id, "n2");
+    }
+
+    #[tokio::test]
+    async fn live_view_filters_edges_of_tombstoned_nodes() {
+        let store = InMemoryStore::new();
+        let delta = make_delta(
+            vec![
+                make_node("n1", proto::NodeType::Service as i32, "svc1"),
+                make_node("n2", proto::NodeType::Service as i32, "svc2"),
+                make_node("n3", proto::NodeType::Service as i32, "svc3"),
+            ],
+            vec![
+                make_edge("n1", "n2", proto::EdgeType::DependsOn as i32),
+                make_edge("n2", "n3", proto::EdgeType::DependsOn as i32),
+            ],
+        );
+        store.merge_hypothesis(delta, None).await.unwrap();
+        store.create_incident(create_request("inc-1")).await.unwrap();
+
+        // Tombstone n1 — edge n1->n2 should also disappear
+        store
+            .merge_node_tombstones(proto::NodeTombstoneRequest {
+                incident_id: "inc-1".into(),
+                node_ids: vec!["n1".into()],
+                provenance: Some(proto::Provenance {
+                    source: "agent".into(),
+                    trigger: "elim".into(),
+                    timestamp: None,
+                    version: 0,
+                }),
+                rationale: None,
+            })
+            .await
+            .unwrap();
+
+        let view = store.get_live_view("inc-1", &GraphQuery::default()).await.unwrap();
+        assert_eq!(view.nodes.len(), 2);
+        assert_eq!(view.edges.len(), 1);
+        assert_eq!(view.edges[0].source, "n2");
+        assert_eq!(view.edges[0].target, "n3");
+    }
+
+    #[tokio::test]
+    async fn live_view_filters_tombstoned_edges() {
+        let store = InMemoryStore::new();
+        let delta = make_delta(
+            vec![
+                make_node("n1", proto::NodeType::Service as i32, "svc1"),
+                make_node("n2", proto::NodeType::Service as i32, "svc2"),
+            ],
+            vec![make_edge("n1", "n2", proto::EdgeType::DependsOn as i32)],
+        );
+        store.merge_hypothesis(delta, None).await.unwrap();
+        store.create_incident(create_request("inc-1")).await.unwrap();
+
+        // Tombstone just the edge
+        store
+            .merge_edge_tombstones(proto::EdgeTombstoneRequest {
+                incident_id: "inc-1".into(),
+                entries: vec![proto::EdgeTombstoneEntry {
+                    source: "n1".into(),
+                    target: "n2".into(),
+                    r#type: proto::EdgeType::DependsOn as i32,
+                    kind: String::new(),
+                }],
+                provenance: Some(proto::Provenance {
+                    source: "agent".into(),
+                    trigger: "elim".into(),
+                    timestamp: None,
+                    version: 0,
+                }),
+                rationale: None,
+            })
+            .await
+            .unwrap();
+
+        let view = store.get_live_view("inc-1", &GraphQuery::default()).await.unwrap();
+        assert_eq!(view.nodes.len(), 2);
+        assert_eq!(view.edges.len(), 0);
+    }
+
+    // --- incident isolation ---
+
+    #[tokio::test]
+    async fn tombstones_isolated_between_incidents() {
+        let store = InMemoryStore::new();
+        let delta = make_delta(
+            vec![
+                make_node("n1", proto::NodeType::Service as i32, "svc1"),
+                make_node("n2", proto::NodeType::Service as i32, "svc2"),
+            ],
+            vec![],
+        );
+        store.merge_hypothesis(delta, None).await.unwrap();
+        store.create_incident(create_request("inc-1")).await.unwrap();
+        store.create_incident(create_request("inc-2")).await.unwrap();
 
-        // Original should be intact — re-merge same type should work
-        let delta3 = make_delta(
-            vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
-            vec![],
-        );
-        let result = store.merge_hypothesis(delta3).await.unwrap();
-        assert!(result.conflicts.is_empty());
-        assert_eq!(result.merged_ids, vec!["n1"]);
+        // Tombstone n1 in inc-1 only
+        store
+            .merge_node_tombstones(proto::NodeTombstoneRequest {
+                incident_id: "inc-1".into(),
+                node_ids: vec!["n1".into()],
+                provenance: Some(proto::Provenance {
+                    source: "agent".into(),
+                    trigger: "elim".into(),
+                    timestamp: None,
+                    version: 0,
+                }),
+                rationale: None,
+            })
+            .await
+            .unwrap();
+
+        let view1 = store.get_live_view("inc-1", &GraphQuery::default()).await.unwrap();
+        let view2 = store.get_live_view("inc-2", &GraphQuery::default()).await.unwrap();
+        assert_eq!(view1.nodes.len(), 1); // n1 tombstoned
+        assert_eq!(view2.nodes.len(), 2); // both visible
     }
 
+    // --- get_main_graph ---
+
     #[tokio::test]
-    async fn merge_creates_and_merges_edges() {
+    async fn main_graph_includes_all() {
         let store = InMemoryStore::new();
-        let delta1 = make_delta(
-            vec![],
-            vec![make_edge("a", "b", proto::EdgeType::DependsOn as i32)],
+        let delta = make_delta(
+            vec![
+                make_node("n1", proto::NodeType::Service as i32, "svc1"),
+                make_node("n2", proto::NodeType::Service as i32, "svc2"),
+            ],
+            vec![make_edge("n1", "n2", proto::EdgeType::DependsOn as i32)],
         );
-        let result1 = store.merge_hypothesis(delta1).await.unwrap();
-        assert_eq!(result1.created_ids.len(), 1);
+        store.merge_hypothesis(delta, None).await.unwrap();
 
-        let delta2 = make_delta(
-            vec![],
-            vec![make_edge("a", "b", proto::EdgeType::DependsOn as i32)],
-        );
-        let result2 = store.merge_hypothesis(delta2).await.unwrap();
-        assert_eq!(result2.merged_ids.len(), 1);
+        let graph = store.get_main_graph(&GraphQuery::default()).await.unwrap();
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.edges.len(), 1);
     }
 
-    // --- create_incident ---
+    // --- universe anchor ---
 
     #[tokio::test]
-    async fn create_incident_new() {
+    async fn anchored_incident_excludes_later_nodes() {
         let store = InMemoryStore::new();
-        let result = store.create_incident("inc-1").await.unwrap();
-        assert!(result.created);
-        assert_eq!(result.incident_id, "inc-1");
+        store
+            .merge_hypothesis(
+                make_delta(
+                    vec![make_node("n1", proto::NodeType::Service as i32, "svc1")],
+                    vec![],
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+        let created = store
+            .create_incident(create_request("inc-1"))
+            .await
+            .unwrap();
+        assert_eq!(created.universe_anchor, 1);
+
+        store
+            .merge_hypothesis(
+                make_delta(
+                    vec![make_node("n2", proto::NodeType::Service as i32, "svc2")],
+                    vec![make_edge("n1", "n2", proto::EdgeType::DependsOn as i32)],
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let view = store.get_live_view("inc-1", &GraphQuery::default()).await.unwrap();
+        assert_eq!(view.version, 1);
+        assert_eq!(view.nodes.len(), 1);
+        assert_eq!(view.nodes[0].id, "n1");
+        assert!(view.edges.is_empty());
     }
 
     #[tokio::test]
-    async fn create_incident_idempotent() {
+    async fn following_incident_tracks_main_graph() {
         let store = InMemoryStore::new();
-        store.create_incident("inc-1").await.unwrap();
-        let result = store.create_incident("inc-1").await.unwrap();
-        assert!(!result.created);
-    }
+        store
+            .create_incident(proto::CreateIncidentRequest {
+                incident_id: "inc-1".into(),
+                follow_main_graph: true,
+            })
+            .await
+            .unwrap();
+        store
+            .merge_hypothesis(
+                make_delta(
+                    vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
+                    vec![],
+                ),
+                None,
+            )
+            .await
+            .unwrap();
 
-    // --- get_incident_context ---
+        let view = store.get_live_view("inc-1", &GraphQuery::default()).await.unwrap();
+        assert_eq!(view.version, 1);
+        assert_eq!(view.nodes.len(), 1);
+
+        let ctx = store.get_incident_context("inc-1").await.unwrap();
+        assert!(ctx.follows_main_graph);
+        assert_eq!(ctx.universe_anchor, 1);
+    }
 
     #[tokio::test]
-    async fn get_incident_context_not_found() {
+    async fn incident_context_reports_anchor_and_elimination_set() {
         let store = InMemoryStore::new();
-        let result = store.get_incident_context("nope").await;
-        assert!(matches!(result, Err(StoreError::IncidentNotFound(_))));
+        store
+            .create_incident(create_request("inc-1"))
+            .await
+            .unwrap();
+
+        let ctx = store.get_incident_context("inc-1").await.unwrap();
+        assert_eq!(ctx.universe_anchor, 0);
+        assert!(!ctx.follows_main_graph);
+        assert_eq!(ctx.elimination_set_id, elimination_set_id("inc-1"));
+        assert_ne!(ctx.elimination_set_id, elimination_set_id("inc-2"));
     }
 
     #[tokio::test]
-    async fn get_incident_context_returns_tombstones() {
+    async fn main_graph_version_bumps_only_on_change() {
         let store = InMemoryStore::new();
-        store.create_incident("inc-1").await.unwrap();
+        let delta = make_delta(
+            vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
+            vec![],
+        );
+        store.merge_hypothesis(delta.clone(), None).await.unwrap();
+        assert_eq!(
+            store
+                .get_main_graph(&GraphQuery::default())
+                .await
+                .unwrap()
+                .version,
+            1
+        );
 
-        // Add a node to the main graph first
+        store.merge_hypothesis(delta, None).await.unwrap();
+        assert_eq!(
+            store
+                .get_main_graph(&GraphQuery::default())
+                .await
+                .unwrap()
+                .version,
+            1
+        );
+    }
+
+    // --- versioning ---
+
+    #[tokio::test]
+    async fn merge_result_reports_version() {
+        let store = InMemoryStore::new();
         let delta = make_delta(
             vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
             vec![],
         );
-        store.merge_hypothesis(delta).await.unwrap();
+        let first = store.merge_hypothesis(delta.clone(), None).await.unwrap();
+        assert_eq!(first.version, 1);
+        // No-op merge leaves the version where it was
+        assert_eq!(
+            store.merge_hypothesis(delta, None).await.unwrap().version,
+            1
+        );
+    }
 
-        // Tombstone it
+    #[tokio::test]
+    async fn main_graph_as_of_reconstructs_past_version() {
+        let store = InMemoryStore::new();
         store
-            .merge_node_tombstones(proto::NodeTombstoneRequest {
-                incident_id: "inc-1".into(),
-                node_ids: vec!["n1".into()],
-                provenance: Some(proto::Provenance {
-                    source: "agent".into(),
-                    trigger: "elim".into(),
-                    timestamp: None,
-                }),
-            })
+            .merge_hypothesis(
+                make_delta(
+                    vec![make_node("n1", proto::NodeType::Service as i32, "svc1")],
+                    vec![],
+                ),
+                None,
+            )
             .await
             .unwrap();
 
-        let ctx = store.get_incident_context("inc-1").await.unwrap();
-        assert_eq!(ctx.incident_id, "inc-1");
-        assert!(ctx.created_at.is_some());
-        let tombstones = ctx.tombstones.unwrap();
-        assert_eq!(tombstones.node_ids, vec!["n1"]);
+        let mut confirm = make_node("n1", proto::NodeType::Service as i32, "svc1");
+        confirm.hypothetical = false;
+        confirm.provenance[0].source = "agent-2".into();
+        store
+            .merge_hypothesis(
+                make_delta(
+                    vec![
+                        confirm,
+                        make_node("n2", proto::NodeType::Service as i32, "svc2"),
+                    ],
+                    vec![make_edge("n1", "n2", proto::EdgeType::DependsOn as i32)],
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let past = store.get_main_graph(&GraphQuery::as_of(1)).await.unwrap();
+        assert_eq!(past.version, 1);
+        assert_eq!(past.nodes.len(), 1);
+        assert!(past.nodes[0].hypothetical);
+        assert_eq!(past.nodes[0].provenance.len(), 1);
+        assert_eq!(past.nodes[0].provenance[0].version, 1);
+        assert!(past.edges.is_empty());
+
+        let head = store.get_main_graph(&GraphQuery::default()).await.unwrap();
+        assert_eq!(head.version, 2);
+        assert!(!head.nodes[0].hypothetical);
+        assert_eq!(head.nodes[0].provenance.len(), 2);
+        assert_eq!(head.edges.len(), 1);
+        assert_eq!(head.edges[0].provenance[0].version, 2);
+
+        let empty = store.get_main_graph(&GraphQuery::as_of(0)).await.unwrap();
+        assert!(empty.nodes.is_empty());
     }
 
-    // --- merge_node_tombstones ---
+    #[tokio::test]
+    async fn as_of_beyond_latest_is_rejected() {
+        let store = InMemoryStore::new();
+        store
+            .merge_hypothesis(
+                make_delta(
+                    vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
+                    vec![],
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+        store
+            .create_incident(create_request("inc-1"))
+            .await
+            .unwrap();
+        store
+            .merge_hypothesis(
+                make_delta(
+                    vec![make_node("n2", proto::NodeType::Service as i32, "svc2")],
+                    vec![],
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let result = store.get_main_graph(&GraphQuery::as_of(3)).await;
+        assert!(matches!(
+            result,
+            Err(StoreError::VersionUnavailable {
+                requested: 3,
+                latest: 2
+            })
+        ));
+
+        // A pinned incident cannot read past its universe anchor
+        let result = store.get_live_view("inc-1", &GraphQuery::as_of(2)).await;
+        assert!(matches!(
+            result,
+            Err(StoreError::VersionUnavailable {
+                requested: 2,
+                latest: 1
+            })
+        ));
+        let view = store.get_live_view("inc-1", &GraphQuery::as_of(0)).await.unwrap();
+        assert!(view.nodes.is_empty());
+    }
+
+    // --- pagination and filters ---
+
+    /// Reads every page of the main graph with `query`, returning the pages.
+    async fn all_pages(store: &InMemoryStore, mut query: GraphQuery) -> Vec<proto::CausalGraph> {
+        let mut pages = Vec::new();
+        loop {
+            let page = store.get_main_graph(&query).await.unwrap();
+            let token = page.next_page_token.clone();
+            pages.push(page);
+            if token.is_empty() {
+                return pages;
+            }
+            query.page_token = Some(PageToken::decode(&token).unwrap());
+        }
+    }
+
+    async fn three_node_chain(store: &InMemoryStore) {
+        store
+            .merge_hypothesis(
+                make_delta(
+                    vec![
+                        make_node("n1", proto::NodeType::Service as i32, "svc1"),
+                        make_node("n2", proto::NodeType::Service as i32, "svc2"),
+                        make_node("n3", proto::NodeType::Mechanism as i32, "oom"),
+                    ],
+                    vec![
+                        make_edge("n1", "n2", proto::EdgeType::DependsOn as i32),
+                        make_edge("n3", "n2", proto::EdgeType::ManifestsAs as i32),
+                    ],
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+    }
 
     #[tokio::test]
-    async fn tombstone_applied_for_existing_node() {
+    async fn pages_cover_the_graph_at_one_version() {
         let store = InMemoryStore::new();
-        store.create_incident("inc-1").await.unwrap();
+        three_node_chain(&store).await;
 
-        let delta = make_delta(
-            vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
-            vec![],
-        );
-        store.merge_hypothesis(delta).await.unwrap();
+        let query = GraphQuery {
+            page_size: 2,
+            ..GraphQuery::default()
+        };
+        let first = store.get_main_graph(&query).await.unwrap();
+        assert_eq!(first.nodes.len(), 2);
 
-        let result = store
-            .merge_node_tombstones(proto::NodeTombstoneRequest {
-                incident_id: "inc-1".into(),
-                node_ids: vec!["n1".into()],
-                provenance: Some(proto::Provenance {
-                    source: "agent".into(),
-                    trigger: "elim".into(),
-                    timestamp: None,
-                }),
-            })
+        // A write between pages doesn't leak into later pages
+        store
+            .merge_hypothesis(
+                make_delta(
+                    vec![make_node("n4", proto::NodeType::Service as i32, "svc4")],
+                    vec![],
+                ),
+                None,
+            )
             .await
             .unwrap();
-        assert_eq!(result.applied_ids, vec!["n1"]);
-        assert!(result.already_tombstoned_ids.is_empty());
-        assert!(result.unmatched_ids.is_empty());
+        let mut rest = all_pages(
+            &store,
+            GraphQuery {
+                page_token: Some(PageToken::decode(&first.next_page_token).unwrap()),
+                ..query
+            },
+        )
+        .await;
+        assert_eq!(rest.len(), 2);
+        let last = rest.pop().unwrap();
+        let second = rest.pop().unwrap();
+        assert_eq!(second.version, 1);
+        assert_eq!(second.nodes[0].id, "n3");
+        assert_eq!(second.edges.len(), 1);
+        assert_eq!(last.edges.len(), 1);
+        assert!(last.nodes.is_empty());
+        assert!(last.next_page_token.is_empty());
     }
 
     #[tokio::test]
-    async fn tombstone_unmatched_for_missing_node() {
+    async fn filters_apply_before_paging() {
         let store = InMemoryStore::new();
-        store.create_incident("inc-1").await.unwrap();
+        three_node_chain(&store).await;
+        let mut other = make_node("n1", proto::NodeType::Service as i32, "svc1");
+        other.provenance[0].source = "agent-2".into();
+        other.hypothetical = false;
+        store
+            .merge_hypothesis(make_delta(vec![other], vec![]), None)
+            .await
+            .unwrap();
 
-        let result = store
-            .merge_node_tombstones(proto::NodeTombstoneRequest {
-                incident_id: "inc-1".into(),
-                node_ids: vec!["ghost".into()],
-                provenance: Some(proto::Provenance {
-                    source: "agent".into(),
-                    trigger: "elim".into(),
-                    timestamp: None,
-                }),
+        let services = GraphQuery {
+            filter: GraphFilter {
+                node_types: [NodeType::Service].into(),
+                edge_types: [EdgeType::ManifestsAs].into(),
+                ..GraphFilter::default()
+            },
+            page_size: 1,
+            ..GraphQuery::default()
+        };
+        let pages = all_pages(&store, services).await;
+        let ids: Vec<_> = pages.iter().flat_map(|p| &p.nodes).map(|n| &n.id).collect();
+        assert_eq!(ids, ["n1", "n2"]);
+        let edges: Vec<_> = pages.iter().flat_map(|p| &p.edges).collect();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].source, "n3");
+
+        let confirmed_by_agent_2 = GraphQuery {
+            filter: GraphFilter {
+                provenance_sources: ["agent-2".to_string()].into(),
+                hypothetical: Some(false),
+                ..GraphFilter::default()
+            },
+            ..GraphQuery::default()
+        };
+        let graph = store.get_main_graph(&confirmed_by_agent_2).await.unwrap();
+        assert_eq!(graph.nodes.len(), 1);
+        assert!(graph.edges.is_empty());
+
+        // As of version 1 neither the confirmation nor agent-2's provenance existed
+        let past = store
+            .get_main_graph(&GraphQuery {
+                as_of_version: Some(1),
+                ..confirmed_by_agent_2
             })
             .await
             .unwrap();
-        assert!(result.applied_ids.is_empty());
-        assert_eq!(result.unmatched_ids, vec!["ghost"]);
+        assert!(past.nodes.is_empty());
     }
 
     #[tokio::test]
-    async fn tombstone_idempotent() {
+    async fn live_view_pages_skip_tombstoned_elements() {
         let store = InMemoryStore::new();
-        store.create_incident("inc-1").await.unwrap();
-
-        let delta = make_delta(
-            vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
-            vec![],
-        );
-        store.merge_hypothesis(delta).await.unwrap();
+        three_node_chain(&store).await;
+        store.create_incident(create_request("inc-1")).await.unwrap();
+        store.merge_node_tombstones(tombstone("inc-1", "n1")).await.unwrap();
 
-        let req = proto::NodeTombstoneRequest {
-            incident_id: "inc-1".into(),
-            node_ids: vec!["n1".into()],
-            provenance: Some(proto::Provenance {
-                source: "agent".into(),
-                trigger: "elim".into(),
-                timestamp: None,
-            }),
+        let mut query = GraphQuery {
+            page_size: 1,
+            ..GraphQuery::default()
         };
-        store.merge_node_tombstones(req.clone()).await.unwrap();
-        let result = store.merge_node_tombstones(req).await.unwrap();
-        assert!(result.applied_ids.is_empty());
-        assert_eq!(result.already_tombstoned_ids, vec!["n1"]);
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+        loop {
+            let page = store.get_live_view("inc-1", &query).await.unwrap();
+            assert!(page.nodes.len() + page.edges.len() <= 1);
+            nodes.extend(page.nodes.into_iter().map(|n| n.id));
+            edges.extend(page.edges.into_iter().map(|e| e.source));
+            if page.next_page_token.is_empty() {
+                break;
+            }
+            query.page_token = Some(PageToken::decode(&page.next_page_token).unwrap());
+        }
+        assert_eq!(nodes, ["n2", "n3"]);
+        assert_eq!(edges, ["n3"]);
     }
 
-    // --- merge_edge_tombstones ---
+    // --- incident metrics ---
 
     #[tokio::test]
-    async fn edge_tombstone_applied() {
+    async fn metrics_count_live_and_eliminated_elements() {
+        let store = InMemoryStore::new();
+        three_node_chain(&store).await;
+        store.create_incident(create_request("inc-1")).await.unwrap();
+        store.merge_node_tombstones(tombstone("inc-1", "n1")).await.unwrap();
+        store.merge_node_tombstones(tombstone("inc-1", "ghost")).await.unwrap();
+
+        let metrics = store.get_incident_metrics("inc-1", None).await.unwrap();
+        assert_eq!((metrics.live_nodes, metrics.eliminated_nodes), (2, 1));
+        assert_eq!((metrics.live_edges, metrics.eliminated_edges), (1, 1));
+        assert_eq!(metrics.unmatched_node_tombstones, 1);
+        assert_eq!(metrics.candidate_count, 2);
+        assert_eq!(metrics.entropy_bits, 1.0);
+        assert!((metrics.eliminated_fraction - 1.0 / 3.0).abs() < 1e-9);
+        let services = &metrics.node_kinds[1];
+        assert_eq!((services.kind.as_str(), services.live, services.eliminated), ("SERVICE", 1, 1));
+
+        // Before the first merge the universe is empty and every tombstone unmatched
+        let past = store.get_incident_metrics("inc-1", Some(0)).await.unwrap();
+        assert_eq!(past.live_nodes + past.eliminated_nodes, 0);
+        assert_eq!(past.unmatched_node_tombstones, 2);
+
+        // A second source makes n3 twice as likely as each of n1 and n2
+        let mut again = make_node("n3", proto::NodeType::Mechanism as i32, "oom");
+        again.provenance[0].source = "agent-2".into();
+        store
+            .merge_hypothesis(make_delta(vec![again], vec![]), None)
+            .await
+            .unwrap();
+        store
+            .create_incident(proto::CreateIncidentRequest {
+                incident_id: "inc-2".into(),
+                follow_main_graph: true,
+            })
+            .await
+            .unwrap();
+        let metrics = store.get_incident_metrics("inc-2", None).await.unwrap();
+        assert_eq!(metrics.candidate_count, 3);
+        // p = (1/4, 1/4, 1/2)
+        assert_eq!(metrics.entropy_bits, 1.5);
+        // inc-1 stays anchored before the second source
+        let anchored = store.get_incident_metrics("inc-1", None).await.unwrap();
+        assert_eq!(anchored.entropy_bits, 1.0);
+    }
+
+    // --- neighborhoods ---
+
+    fn neighborhood_ids(result: &proto::NeighborhoodResult) -> (Vec<&str>, Vec<&str>) {
+        let graph = result.graph.as_ref().unwrap();
+        (
+            graph.nodes.iter().map(|n| n.id.as_str()).collect(),
+            graph.edges.iter().map(|e| e.source.as_str()).collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn neighborhood_follows_direction_and_hops() {
         let store = InMemoryStore::new();
-        store.create_incident("inc-1").await.unwrap();
+        three_node_chain(&store).await;
+        store.create_incident(create_request("inc-1")).await.unwrap();
+
+        let downstream = NeighborhoodQuery {
+            node_ids: ["n1".to_string()].into(),
+            hops: 2,
+            direction: Direction::Downstream,
+            ..NeighborhoodQuery::default()
+        };
+        let result = store.get_neighborhood("inc-1", &downstream).await.unwrap();
+        assert_eq!(neighborhood_ids(&result), (vec!["n1", "n2"], vec!["n1"]));
 
-        let delta = make_delta(
-            vec![],
-            vec![make_edge("a", "b", proto::EdgeType::DependsOn as i32)],
+        let both = NeighborhoodQuery {
+            direction: Direction::Both,
+            ..downstream
+        };
+        let result = store.get_neighborhood("inc-1", &both).await.unwrap();
+        assert_eq!(
+            neighborhood_ids(&result),
+            (vec!["n1", "n2", "n3"], vec!["n1", "n3"])
         );
-        store.merge_hypothesis(delta).await.unwrap();
+        assert!(!result.truncated);
+    }
 
-        let result = store
+    #[tokio::test]
+    async fn neighborhood_respects_tombstones() {
+        let store = InMemoryStore::new();
+        three_node_chain(&store).await;
+        store.create_incident(create_request("inc-1")).await.unwrap();
+        store.merge_node_tombstones(tombstone("inc-1", "n2")).await.unwrap();
+
+        let query = NeighborhoodQuery {
+            node_ids: ["n1".to_string(), "n2".to_string()].into(),
+            hops: 3,
+            ..NeighborhoodQuery::default()
+        };
+        let result = store.get_neighborhood("inc-1", &query).await.unwrap();
+        assert_eq!(neighborhood_ids(&result), (vec!["n1"], vec![]));
+
+        store.create_incident(create_request("inc-2")).await.unwrap();
+        store
             .merge_edge_tombstones(proto::EdgeTombstoneRequest {
-                incident_id: "inc-1".into(),
+                incident_id: "inc-2".into(),
                 entries: vec![proto::EdgeTombstoneEntry {
-                    source: "a".into(),
-                    target: "b".into(),
+                    source: "n1".into(),
+                    target: "n2".into(),
                     r#type: proto::EdgeType::DependsOn as i32,
+                    kind: String::new(),
                 }],
                 provenance: Some(proto::Provenance {
                     source: "agent".into(),
                     trigger: "elim".into(),
                     timestamp: None,
+                    version: 0,
                 }),
+                rationale: None,
             })
             .await
             .unwrap();
-        assert_eq!(result.applied_ids.len(), 1);
+        let query = NeighborhoodQuery {
+            node_ids: ["n3".to_string()].into(),
+            ..query
+        };
+        let result = store.get_neighborhood("inc-2", &query).await.unwrap();
+        assert_eq!(neighborhood_ids(&result), (vec!["n2", "n3"], vec!["n3"]));
+    }
+
+    #[tokio::test]
+    async fn neighborhood_reads_edges_by_endpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = InMemoryStore::open(dir.path(), 1).unwrap();
+        // "n1" is a prefix of "n10", so the scan of n1's edges must stop at the exact id
+        let depends_on =
+            |source, target| make_edge(source, target, proto::EdgeType::DependsOn as i32);
+        let nodes = ["n1", "n10", "n2"]
+            .map(|id| make_node(id, proto::NodeType::Service as i32, id))
+            .to_vec();
+        let edges = vec![
+            depends_on("n10", "n1"),
+            depends_on("n1", "n2"),
+            depends_on("n2", "n10"),
+        ];
+        store
+            .merge_hypothesis(make_delta(nodes, edges), None)
+            .await
+            .unwrap();
+        store
+            .create_incident(create_request("inc-1"))
+            .await
+            .unwrap();
+        // The target index is rebuilt from the snapshot
+        drop(store);
+        let store = InMemoryStore::open(dir.path(), 1).unwrap();
+
+        let state = store.state.read().await;
+        let from: Vec<_> = state
+            .edges_from("n1")
+            .map(|(k, _)| k.target.as_str())
+            .collect();
+        let into: Vec<_> = state
+            .edges_into("n1")
+            .map(|(k, _)| k.source.as_str())
+            .collect();
+        assert_eq!((from, into), (vec!["n2"], vec!["n10"]));
+        drop(state);
+
+        let query = |direction| NeighborhoodQuery {
+            node_ids: ["n1".to_string()].into(),
+            hops: 1,
+            direction,
+            ..NeighborhoodQuery::default()
+        };
+        let result = store
+            .get_neighborhood("inc-1", &query(Direction::Upstream))
+            .await
+            .unwrap();
+        assert_eq!(neighborhood_ids(&result), (vec!["n1", "n10"], vec!["n10"]));
+        let result = store
+            .get_neighborhood("inc-1", &query(Direction::Both))
+            .await
+            .unwrap();
+        assert_eq!(
+            neighborhood_ids(&result),
+            (vec!["n1", "n10", "n2"], vec!["n1", "n10", "n2"])
+        );
     }
 
-    // --- get_live_view ---
+    // --- change events ---
+
+    /// Every event already published to `events`.
+    fn drain(events: &mut broadcast::Receiver<StoreEvent>) -> Vec<StoreEvent> {
+        std::iter::from_fn(|| events.try_recv().ok()).collect()
+    }
 
     #[tokio::test]
-    async fn live_view_filters_tombstoned_nodes() {
+    async fn merge_publishes_added_and_confirmed() {
         let store = InMemoryStore::new();
-        store.create_incident("inc-1").await.unwrap();
-
-        let delta = make_delta(
-            vec![
-                make_node("n1", proto::NodeType::Service as i32, "svc1"),
-                make_node("n2", proto::NodeType::Service as i32, "svc2"),
-            ],
-            vec![],
-        );
-        store.merge_hypothesis(delta).await.unwrap();
-
+        let mut events = store.subscribe();
         store
-            .merge_node_tombstones(proto::NodeTombstoneRequest {
-                incident_id: "inc-1".into(),
-                node_ids: vec!["n1".into()],
-                provenance: Some(proto::Provenance {
-                    source: "agent".into(),
-                    trigger: "elim".into(),
-                    timestamp: None,
-                }),
-            })
+            .merge_hypothesis(
+                make_delta(
+                    vec![
+                        make_node("n1", proto::NodeType::Service as i32, "svc1"),
+                        make_node("n2", proto::NodeType::Service as i32, "svc2"),
+                    ],
+                    vec![make_edge("n1", "n2", proto::EdgeType::DependsOn as i32)],
+                ),
+                None,
+            )
             .await
             .unwrap();
-
-        let view = store.get_live_view("inc-1").await.unwrap();
-        assert_eq!(view.nodes.len(), 1);
-        assert_eq!(view.nodes[0].id, "n2");
+        let published = drain(&mut events);
+        assert_eq!(published.len(), 3);
+        assert!(matches!(
+            &published[0],
+            StoreEvent::NodeAdded { version: 1, node } if node.id == "n1"
+        ));
+        assert!(matches!(
+            &published[2],
+            StoreEvent::EdgeAdded { version: 1, edge } if edge.source == "n1"
+        ));
+
+        // Re-merging changes nothing, so publishes nothing; confirming publishes once
+        let mut confirmed = make_node("n1", proto::NodeType::Service as i32, "svc1");
+        store
+            .merge_hypothesis(make_delta(vec![confirmed.clone()], vec![]), None)
+            .await
+            .unwrap();
+        assert!(drain(&mut events).is_empty());
+        confirmed.hypothetical = false;
+        store
+            .merge_hypothesis(make_delta(vec![confirmed], vec![]), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            drain(&mut events),
+            vec![StoreEvent::NodeConfirmed {
+                version: 2,
+                node_id: "n1".into(),
+            }]
+        );
     }
 
     #[tokio::test]
-    async fn live_view_filters_edges_of_tombstoned_nodes() {
+    async fn refined_placeholder_is_published_as_added() {
         let store = InMemoryStore::new();
-        store.create_incident("inc-1").await.unwrap();
-
-        let delta = make_delta(
-            vec![
-                make_node("n1", proto::NodeType::Service as i32, "svc1"),
-                make_node("n2", proto::NodeType::Service as i32, "svc2"),
-                make_node("n3", proto::NodeType::Service as i32, "svc3"),
-            ],
-            vec![
-                make_edge("n1", "n2", proto::EdgeType::DependsOn as i32),
-                make_edge("n2", "n3", proto::EdgeType::DependsOn as i32),
-            ],
+        let mut delta = make_delta(
+            vec![],
+            vec![make_edge("a", "b", proto::EdgeType::DependsOn as i32)],
         );
-        store.merge_hypothesis(delta).await.unwrap();
+        delta.set_dangling_edge_policy(proto::DanglingEdgePolicy::Placeholder);
+        store.merge_hypothesis(delta, None).await.unwrap();
 
-        // Tombstone n1 — edge n1->n2 should also disappear
+        let mut events = store.subscribe();
         store
-            .merge_node_tombstones(proto::NodeTombstoneRequest {
-                incident_id: "inc-1".into(),
-                node_ids: vec!["n1".into()],
-                provenance: Some(proto::Provenance {
-                    source: "agent".into(),
-                    trigger: "elim".into(),
-                    timestamp: None,
-                }),
-            })
+            .merge_hypothesis(
+                make_delta(
+                    vec![make_node("a", proto::NodeType::Service as i32, "svc")],
+                    vec![],
+                ),
+                None,
+            )
             .await
             .unwrap();
-
-        let view = store.get_live_view("inc-1").await.unwrap();
-        assert_eq!(view.nodes.len(), 2);
-        assert_eq!(view.edges.len(), 1);
-        assert_eq!(view.edges[0].source, "n2");
-        assert_eq!(view.edges[0].target, "n3");
+        let published = drain(&mut events);
+        assert!(matches!(
+            &published[..],
+            [StoreEvent::NodeAdded { version: 2, node }] if node.label == "svc"
+        ));
     }
 
     #[tokio::test]
-    async fn live_view_filters_tombstoned_edges() {
+    async fn aborted_atomic_merge_publishes_nothing() {
         let store = InMemoryStore::new();
-        store.create_incident("inc-1").await.unwrap();
-
-        let delta = make_delta(
+        store
+            .merge_hypothesis(
+                make_delta(
+                    vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
+                    vec![],
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+        let mut events = store.subscribe();
+        let mut delta = make_delta(
             vec![
-                make_node("n1", proto::NodeType::Service as i32, "svc1"),
+                make_node("n1", proto::NodeType::Service as i32, "other"),
                 make_node("n2", proto::NodeType::Service as i32, "svc2"),
             ],
-            vec![make_edge("n1", "n2", proto::EdgeType::DependsOn as i32)],
+            vec![],
         );
-        store.merge_hypothesis(delta).await.unwrap();
+        delta.atomic = true;
+        store.merge_hypothesis(delta, None).await.unwrap();
+        assert!(drain(&mut events).is_empty());
+    }
 
-        // Tombstone just the edge
+    #[tokio::test]
+    async fn new_tombstones_are_published_once() {
+        let store = InMemoryStore::new();
+        store.create_incident(create_request("inc-1")).await.unwrap();
+        let mut events = store.subscribe();
+
+        store.merge_node_tombstones(tombstone("inc-1", "n1")).await.unwrap();
+        store.merge_node_tombstones(tombstone("inc-1", "n1")).await.unwrap();
         store
             .merge_edge_tombstones(proto::EdgeTombstoneRequest {
                 incident_id: "inc-1".into(),
@@ -754,75 +2917,379 @@ mod tests {
                     source: "n1".into(),
                     target: "n2".into(),
                     r#type: proto::EdgeType::DependsOn as i32,
+                    kind: String::new(),
                 }],
-                provenance: Some(proto::Provenance {
-                    source: "agent".into(),
-                    trigger: "elim".into(),
-                    timestamp: None,
-                }),
+                provenance: None,
+                rationale: None,
             })
             .await
             .unwrap();
 
-        let view = store.get_live_view("inc-1").await.unwrap();
-        assert_eq!(view.nodes.len(), 2);
-        assert_eq!(view.edges.len(), 0);
+        assert_eq!(
+            drain(&mut events),
+            vec![
+                StoreEvent::NodeTombstoned {
+                    incident_id: "inc-1".into(),
+                    node_id: "n1".into(),
+                },
+                StoreEvent::EdgeTombstoned {
+                    incident_id: "inc-1".into(),
+                    entry: proto::EdgeTombstoneEntry {
+                        source: "n1".into(),
+                        target: "n2".into(),
+                        r#type: proto::EdgeType::DependsOn as i32,
+                        kind: "DEPENDS_ON".into(),
+                    },
+                },
+            ]
+        );
     }
 
-    // --- incident isolation ---
+    // --- change feed ---
 
     #[tokio::test]
-    async fn tombstones_isolated_between_incidents() {
+    async fn change_feed_groups_history_by_version() {
         let store = InMemoryStore::new();
-        store.create_incident("inc-1").await.unwrap();
-        store.create_incident("inc-2").await.unwrap();
+        store
+            .merge_hypothesis(
+                make_delta(
+                    vec![
+                        make_node("n1", proto::NodeType::Service as i32, "svc1"),
+                        make_node("n2", proto::NodeType::Service as i32, "svc2"),
+                    ],
+                    vec![make_edge("n1", "n2", proto::EdgeType::DependsOn as i32)],
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+        let mut again = make_node("n1", proto::NodeType::Service as i32, "svc1");
+        again.hypothetical = false;
+        again.provenance[0].source = "agent-2".into();
+        let mut edge_again = make_edge("n1", "n2", proto::EdgeType::DependsOn as i32);
+        edge_again.provenance[0].source = "agent-2".into();
+        store
+            .merge_hypothesis(make_delta(vec![again], vec![edge_again]), None)
+            .await
+            .unwrap();
 
-        let delta = make_delta(
-            vec![
-                make_node("n1", proto::NodeType::Service as i32, "svc1"),
-                make_node("n2", proto::NodeType::Service as i32, "svc2"),
-            ],
-            vec![],
-        );
-        store.merge_hypothesis(delta).await.unwrap();
+        let changes = store.get_main_graph_changes(0, u64::MAX).await.unwrap();
+        assert_eq!(changes.len(), 2);
+        let (first, second) = (&changes[0], &changes[1]);
+        assert_eq!(first.cursor, 1);
+        assert_eq!(first.created_nodes.len(), 2);
+        assert!(first.created_nodes.iter().all(|n| n.hypothetical));
+        assert!(first.created_nodes.iter().all(|n| n.provenance.len() == 1));
+        assert_eq!(first.created_edges.len(), 1);
+        assert!(first.provenance_appends.is_empty());
+
+        assert_eq!(second.cursor, 2);
+        assert!(second.created_nodes.is_empty());
+        assert_eq!(second.confirmed_node_ids, vec!["n1"]);
+        assert_eq!(second.provenance_appends.len(), 2);
+        for append in &second.provenance_appends {
+            assert_eq!(append.provenance.len(), 1);
+            assert_eq!(append.provenance[0].source, "agent-2");
+        }
 
-        // Tombstone n1 in inc-1 only
+        // Resuming from a cursor skips everything up to and including it
+        let resumed = store.get_main_graph_changes(1, u64::MAX).await.unwrap();
+        assert_eq!(resumed, changes[1..]);
+        let page = store.get_main_graph_changes(0, 1).await.unwrap();
+        assert_eq!(page, changes[..1]);
+        assert!(store
+            .get_main_graph_changes(2, u64::MAX)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn change_feed_records_placeholder_refinement() {
+        let store = InMemoryStore::new();
         store
-            .merge_node_tombstones(proto::NodeTombstoneRequest {
-                incident_id: "inc-1".into(),
-                node_ids: vec!["n1".into()],
-                provenance: Some(proto::Provenance {
-                    source: "agent".into(),
-                    trigger: "elim".into(),
-                    timestamp: None,
-                }),
-            })
+            .merge_hypothesis(dangling_delta(proto::DanglingEdgePolicy::Placeholder), None)
+            .await
+            .unwrap();
+        let mut real = make_node("c", proto::NodeType::Infrastructure as i32, "db");
+        real.provenance[0].source = "agent-2".into();
+        store
+            .merge_hypothesis(make_delta(vec![real], vec![]), None)
             .await
             .unwrap();
 
-        let view1 = store.get_live_view("inc-1").await.unwrap();
-        let view2 = store.get_live_view("inc-2").await.unwrap();
-        assert_eq!(view1.nodes.len(), 1); // n1 tombstoned
-        assert_eq!(view2.nodes.len(), 2); // both visible
+        let changes = store.get_main_graph_changes(0, u64::MAX).await.unwrap();
+        assert_eq!(changes.len(), 2);
+        let c = changes[0]
+            .created_nodes
+            .iter()
+            .find(|n| n.id == "c")
+            .unwrap();
+        assert_eq!(c.r#type, proto::NodeType::Placeholder as i32);
+        assert_eq!(c.kind, "PLACEHOLDER");
+        assert_eq!(c.label, "c");
+        assert!(changes[0].refined_nodes.is_empty());
+
+        // The real write retypes the node at its own version and appends its provenance
+        let second = &changes[1];
+        assert_eq!(second.cursor, 2);
+        assert!(second.created_nodes.is_empty());
+        assert_eq!(second.refined_nodes.len(), 1);
+        let c = &second.refined_nodes[0];
+        assert_eq!(c.id, "c");
+        assert_eq!(c.r#type, proto::NodeType::Infrastructure as i32);
+        assert_eq!(c.label, "db");
+        assert!(c.provenance.is_empty());
+        assert_eq!(second.provenance_appends.len(), 1);
+        assert!(matches!(
+            &second.provenance_appends[0].target,
+            Some(proto::provenance_append::Target::NodeId(id)) if id == "c"
+        ));
+
+        assert_eq!(
+            store.get_main_graph_changes(0, 1).await.unwrap(),
+            changes[..1]
+        );
+        assert_eq!(
+            store.get_main_graph_changes(1, u64::MAX).await.unwrap(),
+            changes[1..]
+        );
     }
 
-    // --- get_main_graph ---
-
     #[tokio::test]
-    async fn main_graph_includes_all() {
+    async fn change_feed_rejects_cursor_past_head() {
         let store = InMemoryStore::new();
-        let delta = make_delta(
-            vec![
-                make_node("n1", proto::NodeType::Service as i32, "svc1"),
-                make_node("n2", proto::NodeType::Service as i32, "svc2"),
-            ],
-            vec![make_edge("n1", "n2", proto::EdgeType::DependsOn as i32)],
-        );
-        store.merge_hypothesis(delta).await.unwrap();
+        let err = store.get_main_graph_changes(1, u64::MAX).await.unwrap_err();
+        assert!(matches!(
+            err,
+            StoreError::VersionUnavailable {
+                requested: 1,
+                latest: 0
+            }
+        ));
+    }
+
+    // --- durable mode ---
+
+    fn tombstone(incident_id: &str, node_id: &str) -> proto::NodeTombstoneRequest {
+        proto::NodeTombstoneRequest {
+            incident_id: incident_id.into(),
+            node_ids: vec![node_id.into()],
+            provenance: Some(proto::Provenance {
+                source: "agent".into(),
+                trigger: "elim".into(),
+                timestamp: None,
+                version: 0,
+            }),
+            rationale: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn durable_store_recovers_after_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = InMemoryStore::open(dir.path(), 0).unwrap();
+        store
+            .merge_hypothesis(
+                make_delta(
+                    vec![
+                        make_node("n1", proto::NodeType::Service as i32, "svc1"),
+                        make_node("n2", proto::NodeType::Service as i32, "svc2"),
+                    ],
+                    vec![make_edge("n1", "n2", proto::EdgeType::DependsOn as i32)],
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+        store
+            .confirm_nodes(confirm(&["n2"], "reviewer"))
+            .await
+            .unwrap();
+        let created = store
+            .create_incident(create_request("inc-1"))
+            .await
+            .unwrap();
+        store
+            .merge_node_tombstones(tombstone("inc-1", "n1"))
+            .await
+            .unwrap();
+        let context = store.get_incident_context("inc-1").await.unwrap();
+        drop(store);
 
-        let graph = store.get_main_graph().await.unwrap();
+        let store = InMemoryStore::open(dir.path(), 0).unwrap();
+        let graph = store.get_main_graph(&GraphQuery::default()).await.unwrap();
+        assert_eq!(graph.version, 2);
         assert_eq!(graph.nodes.len(), 2);
         assert_eq!(graph.edges.len(), 1);
+
+        let recovered = store.get_incident_context("inc-1").await.unwrap();
+        assert_eq!(recovered.created_at, context.created_at);
+        assert_eq!(recovered.universe_anchor, created.universe_anchor);
+        let tombstones = recovered.tombstones.unwrap();
+        assert_eq!(tombstones.node_ids, vec!["n1"]);
+        assert_eq!(tombstones.nodes[0].provenance[0].source, "agent");
+
+        let view = store.get_live_view("inc-1", &GraphQuery::default()).await.unwrap();
+        assert_eq!(view.nodes.len(), 1);
+        assert_eq!(view.nodes[0].id, "n2");
+        assert!(!view.nodes[0].hypothetical);
+    }
+
+    #[tokio::test]
+    async fn durable_store_drops_torn_final_record() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = InMemoryStore::open(dir.path(), 0).unwrap();
+        store
+            .merge_hypothesis(
+                make_delta(
+                    vec![make_node("n1", proto::NodeType::Service as i32, "svc1")],
+                    vec![],
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+        store
+            .merge_hypothesis(
+                make_delta(
+                    vec![make_node("n2", proto::NodeType::Service as i32, "svc2")],
+                    vec![],
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+        drop(store);
+
+        // Simulate a crash partway through writing the second record
+        let log = dir.path().join("wal.log");
+        let len = std::fs::metadata(&log).unwrap().len();
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open(&log)
+            .unwrap()
+            .set_len(len - 10)
+            .unwrap();
+
+        let store = InMemoryStore::open(dir.path(), 0).unwrap();
+        let graph = store.get_main_graph(&GraphQuery::default()).await.unwrap();
+        assert_eq!(graph.version, 1);
+        assert_eq!(graph.nodes.len(), 1);
+        assert_eq!(graph.nodes[0].id, "n1");
+
+        // The store keeps accepting writes after recovery
+        let result = store
+            .merge_hypothesis(
+                make_delta(
+                    vec![make_node("n3", proto::NodeType::Service as i32, "svc3")],
+                    vec![],
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.version, 2);
+        drop(store);
+        let store = InMemoryStore::open(dir.path(), 0).unwrap();
+        assert_eq!(store.get_main_graph(&GraphQuery::default()).await.unwrap().nodes.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn durable_store_replays_snapshot_and_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = InMemoryStore::open(dir.path(), 2).unwrap();
+        for i in 0..5 {
+            let id = format!("n{i}");
+            store
+                .merge_hypothesis(
+                    make_delta(
+                        vec![make_node(&id, proto::NodeType::Service as i32, &id)],
+                        vec![],
+                    ),
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+        store
+            .create_incident(create_request("inc-1"))
+            .await
+            .unwrap();
+        store
+            .merge_node_tombstones(tombstone("inc-1", "n0"))
+            .await
+            .unwrap();
+        let changes = store.get_main_graph_changes(0, u64::MAX).await.unwrap();
+        drop(store);
+        assert!(dir.path().join("snapshot.json").exists());
+
+        let store = InMemoryStore::open(dir.path(), 2).unwrap();
+        let graph = store.get_main_graph(&GraphQuery::default()).await.unwrap();
+        assert_eq!(graph.version, 5);
+        assert_eq!(graph.nodes.len(), 5);
+        // Versions stamped before the snapshot survive it
+        let past = store.get_main_graph(&GraphQuery::as_of(2)).await.unwrap();
+        assert_eq!(past.nodes.len(), 2);
+        let view = store.get_live_view("inc-1", &GraphQuery::default()).await.unwrap();
+        assert_eq!(view.nodes.len(), 4);
+        // So does the change feed, from any cursor
+        assert_eq!(
+            store.get_main_graph_changes(0, u64::MAX).await.unwrap(),
+            changes
+        );
+        assert_eq!(
+            store.get_main_graph_changes(3, u64::MAX).await.unwrap(),
+            changes[3..]
+        );
+    }
+
+    #[tokio::test]
+    async fn reads_do_not_wait_for_the_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = InMemoryStore::open(dir.path(), 0).unwrap();
+        // Stall the log as a slow disk would, until `release` is sent
+        let log = store.wal.clone().unwrap();
+        let (stalled_tx, stalled) = std::sync::mpsc::channel();
+        let (release, release_rx) = std::sync::mpsc::channel::<()>();
+        std::thread::spawn(move || {
+            let _log = log.lock().unwrap();
+            stalled_tx.send(()).unwrap();
+            let _ = release_rx.recv();
+        });
+        stalled.recv().unwrap();
+        let writer = store.clone();
+        let write = tokio::spawn(async move {
+            let delta = make_delta(
+                vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
+                vec![],
+            );
+            writer.merge_hypothesis(delta, None).await
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        let query = GraphQuery::default();
+        let read = store.get_main_graph(&query);
+        let graph = tokio::time::timeout(std::time::Duration::from_secs(5), read)
+            .await
+            .expect("a read must not wait for a pending append")
+            .unwrap();
+        assert_eq!(graph.version, 0);
+
+        release.send(()).unwrap();
+        assert_eq!(write.await.unwrap().unwrap().version, 1);
+    }
+
+    #[tokio::test]
+    async fn rejected_writes_are_not_logged() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = InMemoryStore::open(dir.path(), 0).unwrap();
+        let result = store.merge_node_tombstones(tombstone("missing", "n1")).await;
+        assert!(matches!(result, Err(StoreError::IncidentNotFound(_))));
+        assert_eq!(
+            std::fs::metadata(dir.path().join("wal.log")).unwrap().len(),
+            0
+        );
     }
 
     // --- get_tombstones ---
@@ -830,13 +3297,13 @@ mod tests {
     #[tokio::test]
     async fn get_tombstones_returns_sets() {
         let store = InMemoryStore::new();
-        store.create_incident("inc-1").await.unwrap();
+        store.create_incident(create_request("inc-1")).await.unwrap();
 
         let delta = make_delta(
             vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
             vec![make_edge("n1", "n2", proto::EdgeType::DependsOn as i32)],
         );
-        store.merge_hypothesis(delta).await.unwrap();
+        store.merge_hypothesis(delta, None).await.unwrap();
 
         store
             .merge_node_tombstones(proto::NodeTombstoneRequest {
@@ -846,7 +3313,9 @@ mod tests {
                     source: "agent".into(),
                     trigger: "elim".into(),
                     timestamp: None,
+                    version: 0,
                 }),
+                rationale: None,
             })
             .await
             .unwrap();
@@ -858,12 +3327,15 @@ mod tests {
                     source: "n1".into(),
                     target: "n2".into(),
                     r#type: proto::EdgeType::DependsOn as i32,
+                    kind: String::new(),
                 }],
                 provenance: Some(proto::Provenance {
                     source: "agent".into(),
                     trigger: "elim".into(),
                     timestamp: None,
+                    version: 0,
                 }),
+                rationale: None,
             })
             .await
             .unwrap();
@@ -872,4 +3344,151 @@ mod tests {
         assert_eq!(tombstones.node_ids, vec!["n1"]);
         assert_eq!(tombstones.edge_entries.len(), 1);
     }
+
+    #[tokio::test]
+    async fn graph_size_counts_every_incident() {
+        let store = InMemoryStore::new();
+        assert_eq!(store.graph_size().await.unwrap(), GraphSize::default());
+
+        let delta = make_delta(
+            vec![
+                make_node("n1", proto::NodeType::Service as i32, "svc"),
+                make_node("n2", proto::NodeType::Service as i32, "db"),
+            ],
+            vec![make_edge("n1", "n2", proto::EdgeType::DependsOn as i32)],
+        );
+        store.merge_hypothesis(delta, None).await.unwrap();
+        for incident_id in ["inc-1", "inc-2"] {
+            store.create_incident(create_request(incident_id)).await.unwrap();
+            store
+                .merge_node_tombstones(tombstone(incident_id, "n1"))
+                .await
+                .unwrap();
+        }
+
+        let size = store.graph_size().await.unwrap();
+        assert_eq!(
+            size,
+            GraphSize {
+                version: 1,
+                nodes: 2,
+                edges: 1,
+                incidents: 2,
+                node_tombstones: 2,
+                edge_tombstones: 0,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn tombstone_provenance_grows_with_every_writer() {
+        let store = InMemoryStore::new();
+        store.create_incident(create_request("inc-1")).await.unwrap();
+        store.merge_node_tombstones(tombstone("inc-1", "n1")).await.unwrap();
+        store
+            .merge_hypothesis(
+                make_delta(
+                    vec![make_node("n1", proto::NodeType::Service as i32, "svc")],
+                    vec![],
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let mut second = tombstone("inc-1", "n1");
+        second.provenance.as_mut().unwrap().source = "reviewer".into();
+        let result = store.merge_node_tombstones(second.clone()).await.unwrap();
+        assert_eq!(result.already_tombstoned_ids, vec!["n1"]);
+        // The same writer again adds nothing
+        store.merge_node_tombstones(second).await.unwrap();
+
+        let tombstones = store.get_tombstones("inc-1").await.unwrap();
+        let provenance: Vec<_> = tombstones.nodes[0]
+            .provenance
+            .iter()
+            .map(|p| (p.source.as_str(), p.version))
+            .collect();
+        assert_eq!(provenance, [("agent", 0), ("reviewer", 1)]);
+
+        let context = store.get_incident_context("inc-1").await.unwrap();
+        assert_eq!(context.tombstones.unwrap(), tombstones);
+    }
+
+    #[tokio::test]
+    async fn tombstone_rationale_keeps_the_first_per_writer() {
+        let store = InMemoryStore::new();
+        store.create_incident(create_request("inc-1")).await.unwrap();
+        let with_rationale = |text: &str| proto::EdgeTombstoneRequest {
+            incident_id: "inc-1".into(),
+            entries: vec![proto::EdgeTombstoneEntry {
+                source: "n1".into(),
+                target: "n2".into(),
+                r#type: proto::EdgeType::DependsOn as i32,
+                kind: String::new(),
+            }],
+            provenance: tombstone("inc-1", "n1").provenance,
+            rationale: Some(proto::EliminationRationale {
+                // Attributed to the request provenance whatever is sent
+                source: "someone-else".into(),
+                text: text.into(),
+                evidence_uris: vec!["https://logs.example/q/42".into()],
+                attributes: [("p99_ms".to_string(), "12".to_string())].into(),
+                ..Default::default()
+            }),
+        };
+        store
+            .merge_edge_tombstones(with_rationale("latency normal"))
+            .await
+            .unwrap();
+        store
+            .merge_edge_tombstones(with_rationale("second thoughts"))
+            .await
+            .unwrap();
+
+        let tombstones = store.get_tombstones("inc-1").await.unwrap();
+        let rationale = &tombstones.edges[0].rationale;
+        assert_eq!(rationale.len(), 1);
+        assert_eq!(
+            (rationale[0].source.as_str(), rationale[0].trigger.as_str()),
+            ("agent", "elim")
+        );
+        assert_eq!(rationale[0].text, "latency normal");
+        assert_eq!(rationale[0].evidence_uris, ["https://logs.example/q/42"]);
+        assert_eq!(rationale[0].attributes["p99_ms"], "12");
+    }
+
+    #[test]
+    fn snapshot_tombstones_in_older_formats_still_load() {
+        // Bare ids predate tombstone provenance, pairs predate rationale
+        let older = r#"{
+            "created_at": [1, 0],
+            "universe_anchor": null,
+            "node_tombstones": [
+                "n1",
+                ["n2", [{"source": "agent", "trigger": "elim",
+                         "timestamp_seconds": 0, "timestamp_nanos": 0}]]
+            ],
+            "edge_tombstones": [{"source": "n1", "target": "n2", "edge_type": "DependsOn"}]
+        }"#;
+        let mut incident: IncidentState = serde_json::from_str(older).unwrap();
+        assert!(incident.node_tombstones["n1"].provenance.is_empty());
+        assert_eq!(incident.node_tombstones["n2"].provenance.len(), 1);
+        assert_eq!(incident.edge_tombstones.len(), 1);
+
+        let rationale = Rationale {
+            text: "error rate flat".into(),
+            ..Default::default()
+        };
+        incident.node_tombstones.get_mut("n2").unwrap().record(
+            &Provenance::new("reviewer", "audit"),
+            Some(&rationale.by(&Provenance::new("reviewer", "audit"))),
+        );
+        let json = serde_json::to_string(&incident).unwrap();
+        let reloaded: IncidentState = serde_json::from_str(&json).unwrap();
+        let n2 = &reloaded.node_tombstones["n2"];
+        assert_eq!(n2.provenance.len(), 2);
+        assert_eq!(n2.rationale.first().unwrap().text, "error rate flat");
+        assert!(reloaded.node_tombstones["n1"].provenance.is_empty());
+    }
 }
@@ -0,0 +1,153 @@
+//! Belief-state metrics of one incident (`GetIncidentMetrics`).
+//!
+//! The incident's universe is the main graph at the version read. Its live view is
+//! the universe less the incident's tombstones; whatever else is in the universe is
+//! eliminated. Stores count both per kind and hand the counts to [`MetricsBuilder`],
+//! which derives the totals, the eliminated fraction and the entropy.
+//!
+//! The entropy is that of a belief over the candidates that weighs each by its
+//! support, the number of distinct sources that proposed it, as `RankRootCauses`
+//! breaks ties: `H = -Σ p·log2(p)` with `p` a candidate's share of the total support.
+
+use std::collections::BTreeMap;
+
+use crate::proto;
+
+/// Accumulates the counts of one incident's universe.
+#[derive(Debug, Default)]
+pub(crate) struct MetricsBuilder {
+    nodes: BTreeMap<String, proto::KindCounts>,
+    edges: BTreeMap<String, proto::KindCounts>,
+    /// Support of each candidate.
+    candidates: Vec<u64>,
+    unmatched_node_tombstones: u64,
+    unmatched_edge_tombstones: u64,
+}
+
+impl MetricsBuilder {
+    /// Adds nodes of `kind`. `candidates` has the support of each of the `live` ones
+    /// that is still hypothetical.
+    pub fn nodes(
+        &mut self,
+        kind: &str,
+        live: u64,
+        eliminated: u64,
+        candidates: impl IntoIterator<Item = u64>,
+    ) {
+        add(&mut self.nodes, kind, live, eliminated);
+        // Every candidate was proposed by someone, even with its provenance out of view
+        self.candidates
+            .extend(candidates.into_iter().map(|support| support.max(1)));
+    }
+
+    pub fn edges(&mut self, kind: &str, live: u64, eliminated: u64) {
+        add(&mut self.edges, kind, live, eliminated);
+    }
+
+    /// Adds tombstones that name no node or edge of the universe.
+    pub fn unmatched(&mut self, node_tombstones: u64, edge_tombstones: u64) {
+        self.unmatched_node_tombstones += node_tombstones;
+        self.unmatched_edge_tombstones += edge_tombstones;
+    }
+
+    pub fn finish(self, incident_id: &str, version: u64) -> proto::IncidentMetrics {
+        let live_nodes = self.nodes.values().map(|c| c.live).sum();
+        let eliminated_nodes = self.nodes.values().map(|c| c.eliminated).sum();
+        let universe = live_nodes + eliminated_nodes;
+        proto::IncidentMetrics {
+            incident_id: incident_id.to_string(),
+            version,
+            live_nodes,
+            eliminated_nodes,
+            live_edges: self.edges.values().map(|c| c.live).sum(),
+            eliminated_edges: self.edges.values().map(|c| c.eliminated).sum(),
+            node_kinds: self.nodes.into_values().collect(),
+            edge_kinds: self.edges.into_values().collect(),
+            unmatched_node_tombstones: self.unmatched_node_tombstones,
+            unmatched_edge_tombstones: self.unmatched_edge_tombstones,
+            eliminated_fraction: if universe == 0 {
+                0.0
+            } else {
+                eliminated_nodes as f64 / universe as f64
+            },
+            candidate_count: self.candidates.len() as u64,
+            entropy_bits: entropy(&self.candidates),
+        }
+    }
+}
+
+/// Shannon entropy, in bits, of the belief that weighs each candidate by `support`.
+fn entropy(support: &[u64]) -> f64 {
+    let total = support.iter().sum::<u64>() as f64;
+    support
+        .iter()
+        .map(|&s| {
+            let p = s as f64 / total;
+            p * (1.0 / p).log2()
+        })
+        .sum()
+}
+
+fn add(counts: &mut BTreeMap<String, proto::KindCounts>, kind: &str, live: u64, eliminated: u64) {
+    let entry = counts
+        .entry(kind.to_string())
+        .or_insert_with(|| proto::KindCounts {
+            kind: kind.to_string(),
+            ..Default::default()
+        });
+    entry.live += live;
+    entry.eliminated += eliminated;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derives_totals_fraction_and_entropy() {
+        let mut builder = MetricsBuilder::default();
+        builder.nodes("SERVICE", 3, 1, [1, 1]);
+        builder.nodes("MECHANISM", 2, 2, [1, 1]);
+        builder.nodes("SERVICE", 1, 1, []);
+        builder.edges("DEPENDS_ON", 4, 2);
+        builder.unmatched(1, 0);
+
+        let metrics = builder.finish("inc-1", 5);
+        assert_eq!(metrics.version, 5);
+        assert_eq!((metrics.live_nodes, metrics.eliminated_nodes), (6, 4));
+        assert_eq!((metrics.live_edges, metrics.eliminated_edges), (4, 2));
+        assert_eq!(metrics.unmatched_node_tombstones, 1);
+        assert_eq!(metrics.eliminated_fraction, 0.4);
+        assert_eq!(metrics.candidate_count, 4);
+        assert_eq!(metrics.entropy_bits, 2.0);
+
+        let kinds: Vec<_> = metrics
+            .node_kinds
+            .iter()
+            .map(|c| (c.kind.as_str(), c.live, c.eliminated))
+            .collect();
+        assert_eq!(kinds, [("MECHANISM", 2, 2), ("SERVICE", 4, 2)]);
+    }
+
+    #[test]
+    fn entropy_weighs_candidates_by_support() {
+        let mut builder = MetricsBuilder::default();
+        builder.nodes("MECHANISM", 3, 0, [2, 1, 1]);
+        let metrics = builder.finish("inc-1", 1);
+        assert_eq!(metrics.candidate_count, 3);
+        // p = (1/2, 1/4, 1/4)
+        assert_eq!(metrics.entropy_bits, 1.5);
+
+        // A lone candidate is certain, whatever its support
+        let mut builder = MetricsBuilder::default();
+        builder.nodes("MECHANISM", 1, 0, [3]);
+        assert_eq!(builder.finish("inc-1", 1).entropy_bits, 0.0);
+    }
+
+    #[test]
+    fn empty_universe_has_no_entropy() {
+        let metrics = MetricsBuilder::default().finish("inc-1", 0);
+        assert_eq!(metrics.eliminated_fraction, 0.0);
+        assert_eq!(metrics.entropy_bits, 0.0);
+    }
+}
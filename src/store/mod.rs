@@ -1,57 +1,363 @@
 pub mod memory;
+pub(crate) mod metrics;
+pub mod neighborhood;
+pub mod neo4j;
+pub mod query;
+mod wal;
 
+use std::collections::{BTreeMap, BTreeSet};
+use std::future::Future;
+
+use tokio::sync::broadcast;
+
+use crate::domain::edge::EdgeKey;
+use crate::domain::node::Refinement;
+use crate::domain::node_type::NodeType;
 use crate::proto;
+use crate::schema::causal::CausalSchema;
+use crate::schema::validation::ValidationError;
+
+use self::neighborhood::NeighborhoodQuery;
+use self::query::GraphQuery;
 
 /// Errors from the storage layer.
 #[derive(Debug, thiserror::Error)]
 pub enum StoreError {
     #[error("incident not found: {0}")]
     IncidentNotFound(String),
+    #[error("version {requested} is beyond the latest available version {latest}")]
+    VersionUnavailable { requested: u64, latest: u64 },
+    #[error("{0}")]
+    SchemaViolation(#[from] ValidationError),
     #[error("storage backend error: {0}")]
     Backend(String),
 }
 
+/// How many unreceived [`StoreEvent`]s a subscriber may fall behind by before it
+/// starts missing them.
+pub const EVENT_BUFFER: usize = 1024;
+
+/// A change committed by a store, published to [`Store::subscribe`] receivers once
+/// the write that made it has succeeded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StoreEvent {
+    /// A node entered the main graph at `version`, or a placeholder was replaced by
+    /// its first real write.
+    NodeAdded { version: u64, node: proto::Node },
+    /// An edge entered the main graph at `version`.
+    EdgeAdded { version: u64, edge: proto::Edge },
+    /// A hypothetical node was confirmed at `version`.
+    NodeConfirmed { version: u64, node_id: String },
+    /// A node was newly tombstoned for an incident.
+    NodeTombstoned {
+        incident_id: String,
+        node_id: String,
+    },
+    /// An edge was newly tombstoned for an incident.
+    EdgeTombstoned {
+        incident_id: String,
+        entry: proto::EdgeTombstoneEntry,
+    },
+}
+
+/// How much a store holds: the graph-size gauges of the metrics endpoint.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GraphSize {
+    /// Main-graph version.
+    pub version: u64,
+    pub nodes: u64,
+    pub edges: u64,
+    pub incidents: u64,
+    /// Across all incidents.
+    pub node_tombstones: u64,
+    pub edge_tombstones: u64,
+}
+
+/// Ids of `delta`'s nodes and of the nodes at either end of its edges, whose stored
+/// types a schema check needs.
+pub(crate) fn schema_node_ids(delta: &proto::HypothesisDelta) -> Vec<String> {
+    let ids: BTreeSet<&String> = delta
+        .nodes
+        .iter()
+        .map(|n| &n.id)
+        .chain(delta.edges.iter().flat_map(|e| [&e.source, &e.target]))
+        .collect();
+    ids.into_iter().cloned().collect()
+}
+
+/// Ids of the nodes at either end of `edges` that `stored` has no type for yet.
+pub(crate) fn unknown_endpoints(
+    edges: &[EdgeKey],
+    stored: &BTreeMap<String, NodeType>,
+) -> Vec<String> {
+    let ids: BTreeSet<&String> = edges
+        .iter()
+        .flat_map(|k| [&k.source, &k.target])
+        .filter(|id| !stored.contains_key(*id))
+        .collect();
+    ids.into_iter().cloned().collect()
+}
+
+/// Identity of an incident's elimination (tombstone) set. There is exactly one per
+/// incident, so it is derived from the incident id rather than stored.
+pub fn elimination_set_id(incident_id: &str) -> String {
+    format!("{incident_id}/eliminations")
+}
+
+/// Resolves an optional `as_of_version` against the latest version a read may see.
+pub(crate) fn resolve_as_of(as_of_version: Option<u64>, latest: u64) -> Result<u64, StoreError> {
+    match as_of_version {
+        None => Ok(latest),
+        Some(requested) if requested <= latest => Ok(requested),
+        Some(requested) => Err(StoreError::VersionUnavailable { requested, latest }),
+    }
+}
+
+/// Rebuilds [`proto::MainGraphChange`]s from stored history: creation, confirmation
+/// and refinement versions, and the version stamped on each provenance entry.
+#[derive(Debug, Default)]
+pub(crate) struct ChangeLog {
+    after: u64,
+    through: u64,
+    batches: BTreeMap<u64, proto::MainGraphChange>,
+}
+
+impl ChangeLog {
+    /// Collects the changes made after main-graph version `after`, up to and including
+    /// version `through`.
+    pub(crate) fn between(after: u64, through: u64) -> Self {
+        Self {
+            after,
+            through,
+            batches: BTreeMap::new(),
+        }
+    }
+
+    fn batch(&mut self, version: u64) -> &mut proto::MainGraphChange {
+        self.batches
+            .entry(version)
+            .or_insert_with(|| proto::MainGraphChange {
+                cursor: version,
+                ..Default::default()
+            })
+    }
+
+    /// Adds the history of `node`, given at the head with all of its provenance.
+    /// `confirmed` is the version it stopped being hypothetical at, if it has, and
+    /// `refined` the real write that replaced it if it was created as a placeholder.
+    pub(crate) fn node(
+        &mut self,
+        node: proto::Node,
+        created: u64,
+        confirmed: Option<u64>,
+        refined: Option<Refinement>,
+    ) {
+        let (at_creation, appends) = split_provenance(node.provenance.clone(), created);
+        let after = self.after;
+        for (version, provenance) in appends.into_iter().filter(|(v, _)| *v > after) {
+            self.batch(version)
+                .provenance_appends
+                .push(proto::ProvenanceAppend {
+                    target: Some(proto::provenance_append::Target::NodeId(node.id.clone())),
+                    provenance,
+                });
+        }
+        if let Some(version) = confirmed.filter(|v| *v > created && *v > self.after) {
+            self.batch(version).confirmed_node_ids.push(node.id.clone());
+        }
+        let hypothetical_at = |version: u64| confirmed.is_none_or(|v| v > version);
+        if let Some(refined) = refined.as_ref().filter(|r| r.version > self.after) {
+            self.batch(refined.version).refined_nodes.push(proto::Node {
+                hypothetical: hypothetical_at(refined.version),
+                provenance: Vec::new(),
+                ..node.clone()
+            });
+        }
+        if created > self.after {
+            let (r#type, kind, label) = match refined {
+                Some(refined) => (
+                    i32::from(&NodeType::Placeholder),
+                    NodeType::Placeholder.to_string(),
+                    refined.placeholder_label,
+                ),
+                None => (node.r#type, node.kind, node.label),
+            };
+            self.batch(created).created_nodes.push(proto::Node {
+                r#type,
+                kind,
+                label,
+                hypothetical: hypothetical_at(created),
+                provenance: at_creation,
+                ..node
+            });
+        }
+    }
+
+    /// Adds the history of `edge`, given at the head with all of its provenance.
+    pub(crate) fn edge(&mut self, edge: proto::Edge, created: u64) {
+        let (at_creation, appends) = split_provenance(edge.provenance.clone(), created);
+        let identity = proto::EdgeTombstoneEntry {
+            source: edge.source.clone(),
+            target: edge.target.clone(),
+            r#type: edge.r#type,
+            kind: edge.kind.clone(),
+        };
+        let after = self.after;
+        for (version, provenance) in appends.into_iter().filter(|(v, _)| *v > after) {
+            self.batch(version)
+                .provenance_appends
+                .push(proto::ProvenanceAppend {
+                    target: Some(proto::provenance_append::Target::Edge(identity.clone())),
+                    provenance,
+                });
+        }
+        if created > self.after {
+            self.batch(created).created_edges.push(proto::Edge {
+                provenance: at_creation,
+                ..edge
+            });
+        }
+    }
+
+    /// The changes in cursor order.
+    pub(crate) fn into_changes(mut self) -> Vec<proto::MainGraphChange> {
+        self.batches.split_off(&self.through.saturating_add(1));
+        self.batches.into_values().collect()
+    }
+}
+
+/// Splits provenance into the entries recorded at `created` and the later ones,
+/// grouped by the version that appended them.
+fn split_provenance(
+    provenance: Vec<proto::Provenance>,
+    created: u64,
+) -> (
+    Vec<proto::Provenance>,
+    BTreeMap<u64, Vec<proto::Provenance>>,
+) {
+    let mut at_creation = Vec::new();
+    let mut appends: BTreeMap<u64, Vec<proto::Provenance>> = BTreeMap::new();
+    for prov in provenance {
+        if prov.version <= created {
+            at_creation.push(prov);
+        } else {
+            appends.entry(prov.version).or_default().push(prov);
+        }
+    }
+    (at_creation, appends)
+}
+
+/// Whether an atomic delta must be discarded given its merge result: a node
+/// conflicted, or the `Reject` policy turned away a dangling edge.
+pub(crate) fn aborts_atomic(
+    atomic: bool,
+    policy: proto::DanglingEdgePolicy,
+    result: &proto::HypothesisMergeResult,
+) -> bool {
+    let rejected_edges =
+        policy == proto::DanglingEdgePolicy::Reject && !result.dangling_edge_ids.is_empty();
+    atomic && (!result.conflicts.is_empty() || rejected_edges)
+}
+
 /// The storage trait that Tee's gRPC handlers delegate to.
 ///
 /// Each method corresponds to a gRPC RPC. Implementations include:
-/// - `Neo4jStore` (Phase 3) — the production backend
-/// - `InMemoryStore` (future) — for testing without Neo4j
-#[allow(async_fn_in_trait)]
+/// - `Neo4jStore` — the production backend
+/// - `InMemoryStore` — for testing without Neo4j
+///
+/// Methods return `Send` futures so `TeeService` can be generic over any backend.
+/// Implementations may still be written with `async fn`.
 pub trait Store: Send + Sync {
-    async fn merge_hypothesis(
+    /// Merges `delta` into the main graph. With a `schema`, its edges are first checked
+    /// against the endpoint types stored when the write is applied; a violation fails
+    /// with [`StoreError::SchemaViolation`] and writes nothing.
+    fn merge_hypothesis(
         &self,
         delta: proto::HypothesisDelta,
-    ) -> Result<proto::HypothesisMergeResult, StoreError>;
+        schema: Option<&CausalSchema>,
+    ) -> impl Future<Output = Result<proto::HypothesisMergeResult, StoreError>> + Send;
 
-    async fn create_incident(
+    /// Confirms the main-graph nodes among the request's ids, appending its provenance.
+    /// Ids not in the main graph are reported and left alone.
+    fn confirm_nodes(
+        &self,
+        request: proto::ConfirmNodesRequest,
+    ) -> impl Future<Output = Result<proto::ConfirmNodesResult, StoreError>> + Send;
+
+    fn create_incident(
+        &self,
+        request: proto::CreateIncidentRequest,
+    ) -> impl Future<Output = Result<proto::CreateIncidentResult, StoreError>> + Send;
+
+    fn get_incident_context(
         &self,
         incident_id: &str,
-    ) -> Result<proto::CreateIncidentResult, StoreError>;
+    ) -> impl Future<Output = Result<proto::IncidentContext, StoreError>> + Send;
 
-    async fn get_incident_context(
+    /// Live and eliminated counts of the incident's universe, read at `as_of_version`
+    /// or at the incident's universe anchor if unset.
+    fn get_incident_metrics(
         &self,
         incident_id: &str,
-    ) -> Result<proto::IncidentContext, StoreError>;
+        as_of_version: Option<u64>,
+    ) -> impl Future<Output = Result<proto::IncidentMetrics, StoreError>> + Send;
 
-    async fn merge_node_tombstones(
+    fn merge_node_tombstones(
         &self,
         request: proto::NodeTombstoneRequest,
-    ) -> Result<proto::TombstoneMergeResult, StoreError>;
+    ) -> impl Future<Output = Result<proto::TombstoneMergeResult, StoreError>> + Send;
 
-    async fn merge_edge_tombstones(
+    fn merge_edge_tombstones(
         &self,
         request: proto::EdgeTombstoneRequest,
-    ) -> Result<proto::TombstoneMergeResult, StoreError>;
+    ) -> impl Future<Output = Result<proto::TombstoneMergeResult, StoreError>> + Send;
 
-    async fn get_live_view(
+    /// One page of the incident's live view, read at the query's version or at the
+    /// incident's universe anchor if unset.
+    fn get_live_view(
         &self,
         incident_id: &str,
-    ) -> Result<proto::CausalGraph, StoreError>;
+        query: &GraphQuery,
+    ) -> impl Future<Output = Result<proto::CausalGraph, StoreError>> + Send;
 
-    async fn get_tombstones(
+    /// The subgraph of the incident's live view within the query's hops of its seed
+    /// nodes, read at the query's version or at the universe anchor if unset.
+    fn get_neighborhood(
         &self,
         incident_id: &str,
-    ) -> Result<proto::TombstoneSet, StoreError>;
+        query: &NeighborhoodQuery,
+    ) -> impl Future<Output = Result<proto::NeighborhoodResult, StoreError>> + Send;
+
+    fn get_tombstones(
+        &self,
+        incident_id: &str,
+    ) -> impl Future<Output = Result<proto::TombstoneSet, StoreError>> + Send;
+
+    /// One page of the main graph, read at the query's version or at the current head
+    /// if unset.
+    fn get_main_graph(
+        &self,
+        query: &GraphQuery,
+    ) -> impl Future<Output = Result<proto::CausalGraph, StoreError>> + Send;
+
+    /// The main-graph changes made in the `max_versions` versions after `after_cursor`,
+    /// one [`proto::MainGraphChange`] per version in increasing order. Every version
+    /// changes the main graph, so fewer than `max_versions` changes means the head was
+    /// reached. Only the nodes and edges changed after the cursor are read, so following
+    /// the head stays cheap.
+    fn get_main_graph_changes(
+        &self,
+        after_cursor: u64,
+        max_versions: u64,
+    ) -> impl Future<Output = Result<Vec<proto::MainGraphChange>, StoreError>> + Send;
+
+    /// Counts the main graph, incidents and tombstones at the latest version.
+    fn graph_size(&self) -> impl Future<Output = Result<GraphSize, StoreError>> + Send;
+
+    /// Receives a [`StoreEvent`] for every change committed after the call. A receiver
+    /// that falls more than [`EVENT_BUFFER`] events behind reports `Lagged`.
+    fn subscribe(&self) -> broadcast::Receiver<StoreEvent>;
 
-    async fn get_main_graph(&self) -> Result<proto::CausalGraph, StoreError>;
+    /// Succeeds while the backend can serve requests.
+    fn check_health(&self) -> impl Future<Output = Result<(), StoreError>> + Send;
 }
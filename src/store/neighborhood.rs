@@ -0,0 +1,297 @@
+//! k-hop neighborhoods of an incident's live view (`GetNeighborhood`).
+//!
+//! A traversal starts from the seed nodes and expands one hop at a time along edges
+//! of the allowed kinds, in the requested direction. Each hop admits the nodes it
+//! reaches in id order until `max_nodes` is hit. The result is every admitted node
+//! with the allowed edges between them. Stores look up live nodes and edges;
+//! [`Traversal`] decides what to visit, so every store truncates the same way.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::domain::edge_type::EdgeType;
+use crate::proto;
+use crate::proto_convert::edge_type_of;
+
+/// Most hops one neighborhood read, or edges one path, may span.
+pub const MAX_HOPS: u32 = 10;
+
+/// Which way a traversal follows edges, relative to their source → target direction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Direction {
+    /// From an edge's source to its target.
+    Downstream,
+    /// From an edge's target to its source.
+    Upstream,
+    #[default]
+    Both,
+}
+
+impl From<proto::TraversalDirection> for Direction {
+    fn from(direction: proto::TraversalDirection) -> Self {
+        match direction {
+            proto::TraversalDirection::Downstream => Self::Downstream,
+            proto::TraversalDirection::Upstream => Self::Upstream,
+            proto::TraversalDirection::Unspecified | proto::TraversalDirection::Both => Self::Both,
+        }
+    }
+}
+
+impl Direction {
+    pub fn follows_outgoing(self) -> bool {
+        matches!(self, Self::Downstream | Self::Both)
+    }
+
+    pub fn follows_incoming(self) -> bool {
+        matches!(self, Self::Upstream | Self::Both)
+    }
+}
+
+/// Parameters of a neighborhood read.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NeighborhoodQuery {
+    pub node_ids: BTreeSet<String>,
+    /// Number of hops to expand; `0` returns the seeds alone.
+    pub hops: u32,
+    pub direction: Direction,
+    /// Edge kinds traversed and returned; empty allows every kind.
+    pub edge_types: BTreeSet<EdgeType>,
+    /// Maximum number of nodes returned; `0` is unlimited.
+    pub max_nodes: usize,
+    pub as_of_version: Option<u64>,
+}
+
+impl From<proto::NeighborhoodRequest> for NeighborhoodQuery {
+    fn from(request: proto::NeighborhoodRequest) -> Self {
+        Self {
+            direction: request.direction().into(),
+            node_ids: request.node_ids.into_iter().collect(),
+            hops: request.hops,
+            edge_types: request
+                .edge_types
+                .iter()
+                .map(|name| EdgeType::from_name(name))
+                .collect(),
+            max_nodes: request.max_nodes as usize,
+            as_of_version: request.as_of_version,
+        }
+    }
+}
+
+impl NeighborhoodQuery {
+    /// Whether edges of this kind are traversed and returned.
+    pub fn allows(&self, edge: &proto::Edge) -> bool {
+        self.edge_types.is_empty()
+            || edge_type_of(edge.r#type, &edge.kind).is_ok_and(|t| self.edge_types.contains(&t))
+    }
+}
+
+/// The state of one neighborhood traversal. A store admits the live seed nodes, then
+/// while there is a [`frontier`](Self::frontier) passes the live edges touching it to
+/// [`step`](Self::step) and admits the live nodes among the ids it returns. Finally it
+/// passes the live edges between the admitted nodes to [`finish`](Self::finish).
+#[derive(Debug)]
+pub(crate) struct Traversal<'q> {
+    query: &'q NeighborhoodQuery,
+    nodes: BTreeMap<String, proto::Node>,
+    /// Nodes admitted by the latest hop, expanded by the next one.
+    frontier: BTreeSet<String>,
+    hops: u32,
+    truncated: bool,
+}
+
+impl<'q> Traversal<'q> {
+    pub fn new(query: &'q NeighborhoodQuery) -> Self {
+        Self {
+            query,
+            nodes: BTreeMap::new(),
+            frontier: BTreeSet::new(),
+            hops: 0,
+            truncated: false,
+        }
+    }
+
+    /// Admits the live nodes found for the latest candidate ids, in id order, while
+    /// there is room. The admitted nodes become the next frontier.
+    pub fn admit(&mut self, nodes: impl IntoIterator<Item = proto::Node>) {
+        let found: BTreeMap<_, _> = nodes.into_iter().map(|n| (n.id.clone(), n)).collect();
+        self.frontier.clear();
+        for (id, node) in found {
+            if self.nodes.contains_key(&id) {
+                continue;
+            }
+            if self.query.max_nodes != 0 && self.nodes.len() >= self.query.max_nodes {
+                self.truncated = true;
+                break;
+            }
+            self.frontier.insert(id.clone());
+            self.nodes.insert(id, node);
+        }
+    }
+
+    /// The nodes the next hop expands, or `None` once the traversal is done.
+    pub fn frontier(&self) -> Option<&BTreeSet<String>> {
+        (self.hops < self.query.hops && !self.frontier.is_empty()).then_some(&self.frontier)
+    }
+
+    /// Takes one hop along `edges`, the live edges touching the frontier, and returns
+    /// the ids of the not yet admitted nodes it leads to.
+    pub fn step<'e>(
+        &mut self,
+        edges: impl IntoIterator<Item = &'e proto::Edge>,
+    ) -> BTreeSet<String> {
+        self.hops += 1;
+        let direction = self.query.direction;
+        let mut next = BTreeSet::new();
+        for edge in edges.into_iter().filter(|e| self.query.allows(e)) {
+            if direction.follows_outgoing() && self.frontier.contains(&edge.source) {
+                next.insert(edge.target.clone());
+            }
+            if direction.follows_incoming() && self.frontier.contains(&edge.target) {
+                next.insert(edge.source.clone());
+            }
+        }
+        next.retain(|id| !self.nodes.contains_key(id));
+        next
+    }
+
+    /// Ids of every node admitted so far.
+    pub fn node_ids(&self) -> impl Iterator<Item = &String> {
+        self.nodes.keys()
+    }
+
+    /// Builds the result from the admitted nodes and `edges`, of which only the
+    /// allowed ones between admitted nodes are kept.
+    pub fn finish(self, version: u64, edges: Vec<proto::Edge>) -> proto::NeighborhoodResult {
+        let edges = edges
+            .into_iter()
+            .filter(|e| {
+                self.query.allows(e)
+                    && self.nodes.contains_key(&e.source)
+                    && self.nodes.contains_key(&e.target)
+            })
+            .collect();
+        proto::NeighborhoodResult {
+            graph: Some(proto::CausalGraph {
+                nodes: self.nodes.into_values().collect(),
+                edges,
+                version,
+                next_page_token: String::new(),
+            }),
+            truncated: self.truncated,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str) -> proto::Node {
+        proto::Node {
+            id: id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn edge(source: &str, target: &str, edge_type: proto::EdgeType) -> proto::Edge {
+        proto::Edge {
+            source: source.to_string(),
+            target: target.to_string(),
+            r#type: edge_type as i32,
+            ..Default::default()
+        }
+    }
+
+    fn query(direction: Direction, hops: u32) -> NeighborhoodQuery {
+        NeighborhoodQuery {
+            node_ids: ["b".to_string()].into(),
+            hops,
+            direction,
+            ..Default::default()
+        }
+    }
+
+    /// Runs a traversal over a graph where every node exists.
+    fn run(query: &NeighborhoodQuery, edges: &[proto::Edge]) -> proto::NeighborhoodResult {
+        let mut traversal = Traversal::new(query);
+        traversal.admit(query.node_ids.iter().map(|id| node(id)));
+        while traversal.frontier().is_some() {
+            let next = traversal.step(edges);
+            traversal.admit(next.iter().map(|id| node(id)));
+        }
+        traversal.finish(1, edges.to_vec())
+    }
+
+    fn ids(result: &proto::NeighborhoodResult) -> Vec<&str> {
+        let graph = result.graph.as_ref().unwrap();
+        graph.nodes.iter().map(|n| n.id.as_str()).collect()
+    }
+
+    /// a -> b -> c -> d, and e -> b
+    fn chain() -> Vec<proto::Edge> {
+        vec![
+            edge("a", "b", proto::EdgeType::DependsOn),
+            edge("b", "c", proto::EdgeType::DependsOn),
+            edge("c", "d", proto::EdgeType::PropagatesTo),
+            edge("e", "b", proto::EdgeType::PropagatesTo),
+        ]
+    }
+
+    #[test]
+    fn direction_selects_the_edges_followed() {
+        let edges = chain();
+        assert_eq!(
+            ids(&run(&query(Direction::Downstream, 2), &edges)),
+            ["b", "c", "d"]
+        );
+        assert_eq!(
+            ids(&run(&query(Direction::Upstream, 2), &edges)),
+            ["a", "b", "e"]
+        );
+        assert_eq!(
+            ids(&run(&query(Direction::Both, 1), &edges)),
+            ["a", "b", "c", "e"]
+        );
+        assert_eq!(ids(&run(&query(Direction::Both, 0), &edges)), ["b"]);
+    }
+
+    #[test]
+    fn only_allowed_edge_kinds_are_traversed_and_returned() {
+        let edges = chain();
+        let query = NeighborhoodQuery {
+            edge_types: [EdgeType::DependsOn].into(),
+            ..query(Direction::Both, 3)
+        };
+        let result = run(&query, &edges);
+        assert_eq!(ids(&result), ["a", "b", "c"]);
+        assert_eq!(result.graph.unwrap().edges.len(), 2);
+    }
+
+    #[test]
+    fn max_nodes_keeps_nearer_nodes_in_id_order() {
+        let edges = chain();
+        let capped = NeighborhoodQuery {
+            max_nodes: 3,
+            ..query(Direction::Both, 2)
+        };
+        let result = run(&capped, &edges);
+        assert_eq!(ids(&result), ["a", "b", "c"]);
+        assert!(result.truncated);
+        // Only edges between returned nodes are kept
+        assert_eq!(result.graph.unwrap().edges.len(), 2);
+
+        assert!(!run(&query(Direction::Both, 2), &edges).truncated);
+    }
+
+    #[test]
+    fn missing_seeds_are_ignored() {
+        let query = NeighborhoodQuery {
+            node_ids: ["b".to_string(), "zz".to_string()].into(),
+            ..query(Direction::Both, 1)
+        };
+        let mut traversal = Traversal::new(&query);
+        traversal.admit([node("b")]);
+        let next = traversal.step(&chain());
+        assert_eq!(next, ["a", "c", "e"].map(String::from).into());
+    }
+}
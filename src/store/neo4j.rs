@@ -0,0 +1,2418 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use neo4rs::{query, ConfigBuilder, Graph, Query, Row, Txn};
+use tokio::sync::broadcast;
+
+use crate::config::Neo4jConfig;
+use crate::domain::edge::EdgeKey;
+use crate::domain::edge_type::EdgeType;
+use crate::domain::node::Refinement;
+use crate::domain::node_type::NodeType;
+use crate::domain::provenance::Provenance;
+use crate::domain::rationale::Rationale;
+use crate::proto;
+use crate::proto_convert::{edge_id, edge_type_of, node_type_of};
+use crate::schema::causal::CausalSchema;
+use crate::schema::validation;
+
+use super::metrics::MetricsBuilder;
+use super::neighborhood::{NeighborhoodQuery, Traversal};
+use super::query::{GraphQuery, PageBuilder, PagePosition};
+use super::{
+    aborts_atomic, elimination_set_id, resolve_as_of, schema_node_ids, unknown_endpoints,
+    ChangeLog, GraphSize, Store, StoreError, StoreEvent, EVENT_BUFFER,
+};
+
+/// Schema constraints and indexes from the README. All are `IF NOT EXISTS`, so
+/// running them on every startup is idempotent.
+const CONSTRAINTS: &[&str] = &[
+    "CREATE CONSTRAINT hypothesis_id IF NOT EXISTS \
+     FOR (n:Hypothesis) REQUIRE n.id IS UNIQUE",
+    "CREATE CONSTRAINT edge_identity IF NOT EXISTS \
+     FOR (e:HypothesisEdge) REQUIRE (e.source, e.target, e.type) IS UNIQUE",
+    "CREATE CONSTRAINT incident_id IF NOT EXISTS \
+     FOR (n:Incident) REQUIRE n.incident_id IS UNIQUE",
+    "CREATE CONSTRAINT node_tombstone_unique IF NOT EXISTS \
+     FOR (t:NodeTombstone) REQUIRE (t.incident_id, t.node_id) IS UNIQUE",
+    "CREATE CONSTRAINT edge_tombstone_unique IF NOT EXISTS \
+     FOR (t:EdgeTombstone) REQUIRE (t.incident_id, t.source, t.target, t.type) IS UNIQUE",
+    "CREATE CONSTRAINT main_graph_id IF NOT EXISTS \
+     FOR (g:MainGraph) REQUIRE g.id IS UNIQUE",
+    "CREATE INDEX edge_target IF NOT EXISTS \
+     FOR (e:HypothesisEdge) ON (e.target)",
+    "CREATE INDEX hypothesis_changed_version IF NOT EXISTS \
+     FOR (n:Hypothesis) ON (n.changed_version)",
+    "CREATE INDEX edge_changed_version IF NOT EXISTS \
+     FOR (e:HypothesisEdge) ON (e.changed_version)",
+];
+
+/// Stamps nodes and edges written before `changed_version` existed with the current
+/// version. The change feed still rebuilds their history from the versions they
+/// record; this only makes them visible to reads from a cursor before the head.
+const BACKFILL_CHANGED_VERSION: &str = "
+OPTIONAL MATCH (g:MainGraph {id: 'main'})
+WITH coalesce(g.version, 0) AS version
+MATCH (x)
+WHERE (x:Hypothesis OR x:HypothesisEdge) AND x.changed_version IS NULL
+SET x.changed_version = version
+";
+
+/// Reads the main-graph version and takes the singleton's write lock, so concurrent
+/// hypothesis merges serialize on version assignment.
+const LOCK_VERSION: &str = "
+MERGE (g:MainGraph {id: 'main'})
+ON CREATE SET g.version = 0
+SET g._lock = true
+REMOVE g._lock
+RETURN g.version AS version
+";
+
+const SET_VERSION: &str = "
+MATCH (g:MainGraph {id: 'main'})
+SET g.version = $version
+";
+
+const GET_VERSION: &str = "
+OPTIONAL MATCH (g:MainGraph {id: 'main'})
+RETURN coalesce(g.version, 0) AS version
+";
+
+/// Label counts, answered from Neo4j's count store.
+const GRAPH_SIZE: &str = "
+OPTIONAL MATCH (g:MainGraph {id: 'main'})
+CALL { MATCH (n:Hypothesis) RETURN count(n) AS nodes }
+CALL { MATCH (e:HypothesisEdge) RETURN count(e) AS edges }
+CALL { MATCH (i:Incident) RETURN count(i) AS incidents }
+CALL { MATCH (t:NodeTombstone) RETURN count(t) AS node_tombstones }
+CALL { MATCH (t:EdgeTombstone) RETURN count(t) AS edge_tombstones }
+RETURN coalesce(g.version, 0) AS version, nodes, edges, incidents,
+       node_tombstones, edge_tombstones
+";
+
+/// Create-or-lock the node, then apply the lattice merge only if `type` and `label`
+/// agree with the first write. The MERGE takes the node's write lock, so the
+/// compare and the provenance append are atomic within the transaction.
+/// A `PLACEHOLDER` node takes the type and label of the first real write, keeping its
+/// own label and that write's version as `placeholder_label` and `refined_version`
+/// for reads of earlier versions. A placeholder write never changes an existing node's
+/// type or label.
+const MERGE_NODE: &str = "
+MERGE (n:Hypothesis {id: $id})
+ON CREATE SET n.type = $type, n.label = $label, n.hypothetical = $hypothetical,
+              n.created_version = $version, n.changed_version = $version,
+              n.confirmed_version = CASE WHEN $hypothetical THEN null ELSE $version END,
+              n.provenance_keys = [], n.provenance_events = [], n._created = true
+ON MATCH SET n._created = false
+WITH n, n._created AS created,
+     n.type = 'PLACEHOLDER' AND $type <> 'PLACEHOLDER' AS refining
+WITH n, created, refining,
+     refining OR $type = 'PLACEHOLDER' OR (n.type = $type AND n.label = $label) AS compatible
+REMOVE n._created
+SET n.placeholder_label = CASE WHEN refining THEN n.label ELSE n.placeholder_label END,
+    n.refined_version = CASE WHEN refining THEN $version ELSE n.refined_version END,
+    n.type = CASE WHEN refining THEN $type ELSE n.type END,
+    n.label = CASE WHEN refining THEN $label ELSE n.label END
+WITH n, created, refining, compatible,
+     CASE WHEN compatible
+       THEN [p IN $provenance WHERE NOT p.key IN n.provenance_keys] ELSE [] END AS fresh,
+     compatible AND n.hypothetical AND NOT $hypothetical AS confirming
+SET n.hypothetical = n.hypothetical AND NOT confirming,
+    n.confirmed_version = CASE WHEN confirming THEN $version ELSE n.confirmed_version END,
+    n.changed_version = CASE WHEN refining OR confirming OR size(fresh) > 0
+      THEN $version ELSE n.changed_version END,
+    n.provenance_keys = n.provenance_keys + [p IN fresh | p.key],
+    n.provenance_events = n.provenance_events + [p IN fresh | p.event]
+RETURN created, compatible, refining, confirming,
+       created OR refining OR confirming OR size(fresh) > 0 AS changed,
+       n.type AS existing_type, n.label AS existing_label, n.hypothetical AS hypothetical,
+       coalesce(n.created_version, 0) AS existing_created_version,
+       n.provenance_events AS existing_provenance
+";
+
+/// Confirms an existing node and appends the provenance unless its `(source, trigger)`
+/// is already recorded. The confirmation version is kept from the first confirmation.
+const CONFIRM_NODE: &str = "
+OPTIONAL MATCH (n:Hypothesis {id: $id})
+WITH n, n IS NOT NULL AND n.hypothetical AS confirming,
+     n IS NOT NULL AND NOT $prov_key IN n.provenance_keys AS fresh
+FOREACH (_ IN CASE WHEN n IS NULL THEN [] ELSE [1] END |
+  SET n.hypothetical = false,
+      n.confirmed_version = CASE WHEN confirming THEN $version ELSE n.confirmed_version END,
+      n.changed_version = CASE WHEN confirming OR fresh THEN $version ELSE n.changed_version END,
+      n.provenance_keys = n.provenance_keys + CASE WHEN fresh THEN [$prov_key] ELSE [] END,
+      n.provenance_events = n.provenance_events + CASE WHEN fresh THEN [$prov_event] ELSE [] END)
+RETURN n IS NOT NULL AS found, confirming, fresh
+";
+
+/// Which endpoints of an edge exist as nodes, checked after the delta's own nodes
+/// have been merged in the same transaction.
+const EDGE_ENDPOINTS: &str = "
+OPTIONAL MATCH (a:Hypothesis {id: $source})
+OPTIONAL MATCH (b:Hypothesis {id: $target})
+RETURN a IS NOT NULL AS source_exists, b IS NOT NULL AS target_exists
+";
+
+/// Edges are stored as `HypothesisEdge` nodes for constraint support; the `CAUSAL`
+/// relationship is maintained alongside for traversal when both endpoints exist.
+const MERGE_EDGE: &str = "
+MERGE (e:HypothesisEdge {source: $source, target: $target, type: $type})
+ON CREATE SET e.created_version = $version, e.changed_version = $version,
+              e.provenance_keys = [], e.provenance_events = [], e._created = true
+ON MATCH SET e._created = false
+WITH e, e._created AS created
+REMOVE e._created
+WITH e, created, [p IN $provenance WHERE NOT p.key IN e.provenance_keys] AS fresh
+SET e.changed_version = CASE WHEN size(fresh) > 0 THEN $version ELSE e.changed_version END,
+    e.provenance_keys = e.provenance_keys + [p IN fresh | p.key],
+    e.provenance_events = e.provenance_events + [p IN fresh | p.event]
+WITH e, created, fresh
+OPTIONAL MATCH (a:Hypothesis {id: $source})
+OPTIONAL MATCH (b:Hypothesis {id: $target})
+FOREACH (_ IN CASE WHEN a IS NOT NULL AND b IS NOT NULL THEN [1] ELSE [] END |
+  MERGE (a)-[:CAUSAL {type: $type}]->(b))
+RETURN created, created OR size(fresh) > 0 AS changed, e.provenance_events AS provenance
+";
+
+/// Pins `universe_anchor` to the current main-graph version unless the incident
+/// follows the head, in which case the anchor is resolved at read time.
+const CREATE_INCIDENT: &str = "
+MERGE (g:MainGraph {id: 'main'})
+ON CREATE SET g.version = 0
+MERGE (i:Incident {incident_id: $incident_id})
+ON CREATE SET i.created_at = timestamp(), i.follows_main_graph = $follow_main_graph,
+              i.universe_anchor = g.version, i._created = true
+ON MATCH SET i._created = false
+WITH g, i, i._created AS created
+REMOVE i._created
+RETURN created,
+       CASE WHEN i.follows_main_graph THEN g.version ELSE i.universe_anchor END AS universe_anchor
+";
+
+const GET_INCIDENT: &str = "
+MATCH (i:Incident {incident_id: $incident_id})
+OPTIONAL MATCH (g:MainGraph {id: 'main'})
+RETURN i.created_at AS created_at,
+       coalesce(i.follows_main_graph, false) AS follows_main_graph,
+       CASE WHEN i.follows_main_graph THEN coalesce(g.version, 0)
+            ELSE coalesce(i.universe_anchor, 0) END AS universe_anchor
+";
+
+/// Tombstones keep a grow-only provenance list, appended to on every write like a
+/// node's. Tombstones written before that hold a single `provenance_key` and
+/// `provenance_event`, which are folded into the lists on their next write. The
+/// rationale list grows the same way from `$rationale`, which is empty or holds the
+/// request's rationale.
+const MERGE_NODE_TOMBSTONE: &str = "
+MERGE (t:NodeTombstone {incident_id: $incident_id, node_id: $node_id})
+ON CREATE SET t.unmatched = NOT EXISTS { MATCH (:Hypothesis {id: $node_id}) },
+              t._created = true
+ON MATCH SET t._created = false
+WITH t, t._created AS created
+REMOVE t._created
+WITH t, created,
+     coalesce(t.provenance_keys, [k IN [t.provenance_key] WHERE k IS NOT NULL]) AS keys,
+     coalesce(t.provenance_events, [e IN [t.provenance_event] WHERE e IS NOT NULL]) AS events
+SET t.provenance_keys = keys + CASE WHEN $prov_key IN keys THEN [] ELSE [$prov_key] END,
+    t.provenance_events = events + CASE WHEN $prov_key IN keys THEN [] ELSE [$prov_event] END
+REMOVE t.provenance_key, t.provenance_event
+WITH t, created,
+     [r IN $rationale WHERE NOT r.key IN coalesce(t.rationale_keys, [])] AS fresh
+SET t.rationale_keys = coalesce(t.rationale_keys, []) + [r IN fresh | r.key],
+    t.rationale_events = coalesce(t.rationale_events, []) + [r IN fresh | r.event]
+RETURN created, t.unmatched AS unmatched
+";
+
+const MERGE_EDGE_TOMBSTONE: &str = "
+MERGE (t:EdgeTombstone {incident_id: $incident_id,
+                        source: $source, target: $target, type: $type})
+ON CREATE SET t.unmatched = NOT EXISTS {
+                MATCH (:HypothesisEdge {source: $source, target: $target, type: $type})
+              },
+              t._created = true
+ON MATCH SET t._created = false
+WITH t, t._created AS created
+REMOVE t._created
+WITH t, created,
+     coalesce(t.provenance_keys, [k IN [t.provenance_key] WHERE k IS NOT NULL]) AS keys,
+     coalesce(t.provenance_events, [e IN [t.provenance_event] WHERE e IS NOT NULL]) AS events
+SET t.provenance_keys = keys + CASE WHEN $prov_key IN keys THEN [] ELSE [$prov_key] END,
+    t.provenance_events = events + CASE WHEN $prov_key IN keys THEN [] ELSE [$prov_event] END
+REMOVE t.provenance_key, t.provenance_event
+WITH t, created,
+     [r IN $rationale WHERE NOT r.key IN coalesce(t.rationale_keys, [])] AS fresh
+SET t.rationale_keys = coalesce(t.rationale_keys, []) + [r IN fresh | r.key],
+    t.rationale_events = coalesce(t.rationale_events, []) + [r IN fresh | r.event]
+RETURN created, t.unmatched AS unmatched
+";
+
+const NODE_TOMBSTONES: &str = "
+MATCH (t:NodeTombstone {incident_id: $incident_id})
+RETURN t.node_id AS node_id,
+       coalesce(t.provenance_events,
+                [e IN [t.provenance_event] WHERE e IS NOT NULL]) AS provenance,
+       coalesce(t.rationale_events, []) AS rationale
+ORDER BY node_id
+";
+
+const EDGE_TOMBSTONES: &str = "
+MATCH (t:EdgeTombstone {incident_id: $incident_id})
+RETURN t.source AS source, t.target AS target, t.type AS type,
+       coalesce(t.provenance_events,
+                [e IN [t.provenance_event] WHERE e IS NOT NULL]) AS provenance,
+       coalesce(t.rationale_events, []) AS rationale
+ORDER BY source, target, type
+";
+
+/// Graph reads are resolved at `$as_of`: later nodes and edges are skipped, a node
+/// confirmed after `$as_of` reads back as hypothetical and a placeholder refined after
+/// it reads back as the placeholder. Provenance entries carry their own version and
+/// are filtered on decode.
+///
+/// Each read returns one page: elements after the `$after_*` position that pass the
+/// filter, up to `$limit`. Empty `$node_types`, `$edge_types` and `$sources` lists
+/// match everything; the source filter matches on provenance keys (`source|trigger`).
+const ALL_NODES: &str = "
+MATCH (n:Hypothesis)
+WHERE coalesce(n.created_version, 0) <= $as_of
+AND n.id > $after_id
+AND (size($sources) = 0
+     OR any(k IN n.provenance_keys WHERE any(s IN $sources WHERE k STARTS WITH s + '|')))
+WITH n,
+     CASE WHEN n.refined_version > $as_of THEN 'PLACEHOLDER' ELSE n.type END AS type,
+     CASE WHEN n.refined_version > $as_of THEN n.placeholder_label ELSE n.label END AS label,
+     n.hypothetical OR coalesce(n.confirmed_version, 0) > $as_of AS hypothetical
+WHERE (size($node_types) = 0 OR type IN $node_types)
+AND (NOT $filter_hypothetical OR hypothetical = $hypothetical)
+RETURN n.id AS id, type, label, hypothetical, n.provenance_events AS provenance
+ORDER BY id
+LIMIT $limit
+";
+
+const ALL_EDGES: &str = "
+MATCH (e:HypothesisEdge)
+WHERE coalesce(e.created_version, 0) <= $as_of
+AND (e.source > $after_source
+     OR (e.source = $after_source
+         AND (e.target > $after_target
+              OR (e.target = $after_target AND e.type > $after_type))))
+AND (size($edge_types) = 0 OR e.type IN $edge_types)
+AND (size($sources) = 0
+     OR any(k IN e.provenance_keys WHERE any(s IN $sources WHERE k STARTS WITH s + '|')))
+RETURN e.source AS source, e.target AS target, e.type AS type,
+       e.provenance_events AS provenance
+ORDER BY source, target, type
+LIMIT $limit
+";
+
+const LIVE_NODES: &str = "
+MATCH (n:Hypothesis)
+WHERE coalesce(n.created_version, 0) <= $as_of
+AND n.id > $after_id
+AND (size($sources) = 0
+     OR any(k IN n.provenance_keys WHERE any(s IN $sources WHERE k STARTS WITH s + '|')))
+AND NOT EXISTS {
+  MATCH (:NodeTombstone {incident_id: $incident_id, node_id: n.id})
+}
+WITH n,
+     CASE WHEN n.refined_version > $as_of THEN 'PLACEHOLDER' ELSE n.type END AS type,
+     CASE WHEN n.refined_version > $as_of THEN n.placeholder_label ELSE n.label END AS label,
+     n.hypothetical OR coalesce(n.confirmed_version, 0) > $as_of AS hypothetical
+WHERE (size($node_types) = 0 OR type IN $node_types)
+AND (NOT $filter_hypothetical OR hypothetical = $hypothetical)
+RETURN n.id AS id, type, label, hypothetical, n.provenance_events AS provenance
+ORDER BY id
+LIMIT $limit
+";
+
+const LIVE_EDGES: &str = "
+MATCH (e:HypothesisEdge)
+WHERE coalesce(e.created_version, 0) <= $as_of
+AND (e.source > $after_source
+     OR (e.source = $after_source
+         AND (e.target > $after_target
+              OR (e.target = $after_target AND e.type > $after_type))))
+AND (size($edge_types) = 0 OR e.type IN $edge_types)
+AND (size($sources) = 0
+     OR any(k IN e.provenance_keys WHERE any(s IN $sources WHERE k STARTS WITH s + '|')))
+AND NOT EXISTS {
+  MATCH (:EdgeTombstone {incident_id: $incident_id,
+                         source: e.source, target: e.target, type: e.type})
+}
+AND NOT EXISTS {
+  MATCH (:NodeTombstone {incident_id: $incident_id, node_id: e.source})
+}
+AND NOT EXISTS {
+  MATCH (:NodeTombstone {incident_id: $incident_id, node_id: e.target})
+}
+RETURN e.source AS source, e.target AS target, e.type AS type,
+       e.provenance_events AS provenance
+ORDER BY source, target, type
+LIMIT $limit
+";
+
+/// Neighborhood reads look up the live nodes among `$ids` and the live edges either
+/// touching them (`ADJACENT_EDGES`, in the `$outgoing`/`$incoming` directions) or
+/// between them (`INDUCED_EDGES`). Edges touching a tombstoned node are left to the
+/// node lookup, which never admits that node.
+const NEIGHBORHOOD_NODES: &str = "
+MATCH (n:Hypothesis)
+WHERE n.id IN $ids
+AND coalesce(n.created_version, 0) <= $as_of
+AND NOT EXISTS {
+  MATCH (:NodeTombstone {incident_id: $incident_id, node_id: n.id})
+}
+RETURN n.id AS id,
+       CASE WHEN n.refined_version > $as_of THEN 'PLACEHOLDER' ELSE n.type END AS type,
+       CASE WHEN n.refined_version > $as_of THEN n.placeholder_label ELSE n.label END AS label,
+       n.hypothetical OR coalesce(n.confirmed_version, 0) > $as_of AS hypothetical,
+       n.provenance_events AS provenance
+";
+
+const ADJACENT_EDGES: &str = "
+MATCH (e:HypothesisEdge)
+WHERE (($outgoing AND e.source IN $ids) OR ($incoming AND e.target IN $ids))
+AND coalesce(e.created_version, 0) <= $as_of
+AND (size($edge_types) = 0 OR e.type IN $edge_types)
+AND NOT EXISTS {
+  MATCH (:EdgeTombstone {incident_id: $incident_id,
+                         source: e.source, target: e.target, type: e.type})
+}
+RETURN e.source AS source, e.target AS target, e.type AS type,
+       e.provenance_events AS provenance
+";
+
+const INDUCED_EDGES: &str = "
+MATCH (e:HypothesisEdge)
+WHERE e.source IN $ids AND e.target IN $ids
+AND coalesce(e.created_version, 0) <= $as_of
+AND (size($edge_types) = 0 OR e.type IN $edge_types)
+AND NOT EXISTS {
+  MATCH (:EdgeTombstone {incident_id: $incident_id,
+                         source: e.source, target: e.target, type: e.type})
+}
+RETURN e.source AS source, e.target AS target, e.type AS type,
+       e.provenance_events AS provenance
+ORDER BY source, target, type
+";
+
+/// Incident metrics count the universe at `$as_of` per kind. A node is eliminated if
+/// the incident tombstoned it, an edge if it or either endpoint was tombstoned.
+/// Candidates are live hypothetical nodes other than placeholders.
+const NODE_METRICS: &str = "
+MATCH (n:Hypothesis)
+WHERE coalesce(n.created_version, 0) <= $as_of
+WITH n, EXISTS {
+  MATCH (:NodeTombstone {incident_id: $incident_id, node_id: n.id})
+} AS eliminated,
+     CASE WHEN n.refined_version > $as_of THEN 'PLACEHOLDER' ELSE n.type END AS kind
+WITH n, eliminated, kind,
+     NOT eliminated AND kind <> 'PLACEHOLDER'
+       AND (n.hypothetical OR coalesce(n.confirmed_version, 0) > $as_of) AS candidate
+RETURN kind,
+       count(CASE WHEN NOT eliminated THEN 1 END) AS live,
+       count(CASE WHEN eliminated THEN 1 END) AS eliminated,
+       collect(CASE WHEN candidate THEN n.provenance_events END) AS candidate_provenance
+";
+
+const EDGE_METRICS: &str = "
+MATCH (e:HypothesisEdge)
+WHERE coalesce(e.created_version, 0) <= $as_of
+WITH e, EXISTS {
+  MATCH (:EdgeTombstone {incident_id: $incident_id,
+                         source: e.source, target: e.target, type: e.type})
+} OR EXISTS {
+  MATCH (:NodeTombstone {incident_id: $incident_id, node_id: e.source})
+} OR EXISTS {
+  MATCH (:NodeTombstone {incident_id: $incident_id, node_id: e.target})
+} AS eliminated
+RETURN e.type AS kind,
+       count(CASE WHEN NOT eliminated THEN 1 END) AS live,
+       count(CASE WHEN eliminated THEN 1 END) AS eliminated
+";
+
+/// Tombstones of the incident that name nothing in the universe at `$as_of`.
+const UNMATCHED_TOMBSTONES: &str = "
+OPTIONAL MATCH (t:NodeTombstone {incident_id: $incident_id})
+WHERE NOT EXISTS {
+  MATCH (n:Hypothesis {id: t.node_id})
+  WHERE coalesce(n.created_version, 0) <= $as_of
+}
+WITH count(t) AS nodes
+OPTIONAL MATCH (t:EdgeTombstone {incident_id: $incident_id})
+WHERE NOT EXISTS {
+  MATCH (e:HypothesisEdge {source: t.source, target: t.target, type: t.type})
+  WHERE coalesce(e.created_version, 0) <= $as_of
+}
+RETURN nodes, count(t) AS edges
+";
+
+const NODE_TYPES: &str = "
+MATCH (n:Hypothesis)
+WHERE n.id IN $ids
+RETURN n.id AS id, n.type AS type
+";
+
+/// Edges with an endpoint in `$ids`, looked up through the identity constraint by
+/// source and the `edge_target` index by target.
+const EDGES_TOUCHING: &str = "
+UNWIND $ids AS id
+CALL {
+  WITH id
+  MATCH (e:HypothesisEdge {source: id})
+  RETURN e
+  UNION
+  WITH id
+  MATCH (e:HypothesisEdge {target: id})
+  RETURN e
+}
+RETURN DISTINCT e.source AS source, e.target AS target, e.type AS type
+";
+
+/// The nodes changed after `$after`, each with its full history, for rebuilding the
+/// change feed. `changed_version` is the last version that created, confirmed or
+/// appended provenance to a node; provenance versions live inside the serialized
+/// events, so the history is split into batches in Tee. `confirmed_version` is -1
+/// while the node is hypothetical, and `refined_version` unless a real write replaced
+/// a placeholder.
+const NODE_HISTORY: &str = "
+MATCH (n:Hypothesis)
+WHERE n.changed_version > $after AND coalesce(n.created_version, 0) <= $through
+RETURN n.id AS id, n.type AS type, n.label AS label, n.hypothetical AS hypothetical,
+       n.provenance_events AS provenance,
+       coalesce(n.created_version, 0) AS created_version,
+       coalesce(n.confirmed_version, -1) AS confirmed_version,
+       coalesce(n.refined_version, -1) AS refined_version,
+       n.placeholder_label AS placeholder_label
+ORDER BY id
+";
+
+/// The edges changed after `$after`, as [`NODE_HISTORY`].
+const EDGE_HISTORY: &str = "
+MATCH (e:HypothesisEdge)
+WHERE e.changed_version > $after AND coalesce(e.created_version, 0) <= $through
+RETURN e.source AS source, e.target AS target, e.type AS type,
+       e.provenance_events AS provenance,
+       coalesce(e.created_version, 0) AS created_version
+ORDER BY source, target, type
+";
+
+fn backend(err: impl std::fmt::Display) -> StoreError {
+    StoreError::Backend(err.to_string())
+}
+
+/// Dedup identity of a provenance entry: `source|trigger` (timestamp excluded).
+fn provenance_key(prov: &Provenance) -> String {
+    format!("{}|{}", prov.source, prov.trigger)
+}
+
+/// Full serialized provenance entry, including the informational timestamp.
+fn provenance_event(prov: &Provenance) -> Result<String, StoreError> {
+    serde_json::to_string(prov).map_err(backend)
+}
+
+/// Builds the `$provenance` parameter: a list of `{key, event}` maps, deduplicated
+/// by `(source, trigger)` so the Cypher list comprehension never appends twice.
+/// Each event is stamped with the main-graph `version` of the merge recording it.
+fn provenance_param(
+    provenance: Vec<proto::Provenance>,
+    version: u64,
+) -> Result<Vec<HashMap<String, String>>, StoreError> {
+    let entries: BTreeSet<Provenance> = provenance
+        .into_iter()
+        .map(|p| Provenance::from(p).with_version(version))
+        .collect();
+    entries
+        .iter()
+        .map(|p| {
+            Ok(HashMap::from([
+                ("key".to_string(), provenance_key(p)),
+                ("event".to_string(), provenance_event(p)?),
+            ]))
+        })
+        .collect()
+}
+
+/// Builds the `$rationale` parameter of a tombstone write: the request's rationale,
+/// if any, as a `{key, event}` map keyed like its writer's provenance.
+fn rationale_param(
+    rationale: Option<&Rationale>,
+) -> Result<Vec<HashMap<String, String>>, StoreError> {
+    rationale
+        .map(|r| {
+            Ok(HashMap::from([
+                ("key".to_string(), format!("{}|{}", r.source, r.trigger)),
+                (
+                    "event".to_string(),
+                    serde_json::to_string(r).map_err(backend)?,
+                ),
+            ]))
+        })
+        .into_iter()
+        .collect()
+}
+
+fn decode_rationale(events: Vec<String>) -> Result<Vec<proto::EliminationRationale>, StoreError> {
+    events
+        .iter()
+        .map(|event| {
+            let rationale: Rationale = serde_json::from_str(event).map_err(backend)?;
+            Ok(proto::EliminationRationale::from(&rationale))
+        })
+        .collect()
+}
+
+/// Decodes stored provenance events, keeping those recorded at or before `as_of`.
+fn decode_provenance(
+    events: Vec<String>,
+    as_of: u64,
+) -> Result<Vec<proto::Provenance>, StoreError> {
+    let mut decoded = Vec::with_capacity(events.len());
+    for event in &events {
+        let prov: Provenance = serde_json::from_str(event).map_err(backend)?;
+        if prov.version <= as_of {
+            decoded.push(proto::Provenance::from(&prov));
+        }
+    }
+    Ok(decoded)
+}
+
+/// `timestamp()` in Cypher is milliseconds since the epoch.
+fn millis_to_timestamp(millis: i64) -> prost_types::Timestamp {
+    prost_types::Timestamp {
+        seconds: millis.div_euclid(1000),
+        nanos: (millis.rem_euclid(1000) * 1_000_000) as i32,
+    }
+}
+
+fn row_to_node(row: &Row, as_of: u64) -> Result<proto::Node, StoreError> {
+    let node_type = NodeType::from_name(&row.get::<String>("type").map_err(backend)?);
+    Ok(proto::Node {
+        id: row.get("id").map_err(backend)?,
+        r#type: i32::from(&node_type),
+        kind: node_type.to_string(),
+        label: row.get("label").map_err(backend)?,
+        hypothetical: row.get("hypothetical").map_err(backend)?,
+        provenance: decode_provenance(row.get("provenance").map_err(backend)?, as_of)?,
+    })
+}
+
+fn row_to_edge(row: &Row, as_of: u64) -> Result<proto::Edge, StoreError> {
+    let edge_type = EdgeType::from_name(&row.get::<String>("type").map_err(backend)?);
+    Ok(proto::Edge {
+        source: row.get("source").map_err(backend)?,
+        target: row.get("target").map_err(backend)?,
+        r#type: i32::from(&edge_type),
+        kind: edge_type.to_string(),
+        provenance: decode_provenance(row.get("provenance").map_err(backend)?, as_of)?,
+    })
+}
+
+/// The node a `MERGE_NODE` row describes, as it stands after the merge.
+fn merged_node(row: &Row, id: &str) -> Result<proto::Node, StoreError> {
+    let node_type = NodeType::from_name(&row.get::<String>("existing_type").map_err(backend)?);
+    Ok(proto::Node {
+        id: id.to_string(),
+        r#type: i32::from(&node_type),
+        kind: node_type.to_string(),
+        label: row.get("existing_label").map_err(backend)?,
+        hypothetical: row.get("hypothetical").map_err(backend)?,
+        provenance: decode_provenance(row.get("existing_provenance").map_err(backend)?, u64::MAX)?,
+    })
+}
+
+fn row_to_edge_entry(row: &Row) -> Result<proto::EdgeTombstoneEntry, StoreError> {
+    let edge_type = EdgeType::from_name(&row.get::<String>("type").map_err(backend)?);
+    Ok(proto::EdgeTombstoneEntry {
+        source: row.get("source").map_err(backend)?,
+        target: row.get("target").map_err(backend)?,
+        r#type: i32::from(&edge_type),
+        kind: edge_type.to_string(),
+    })
+}
+
+/// Runs a query inside `txn` and returns its single result row.
+async fn fetch_one(txn: &mut Txn, q: Query) -> Result<Row, StoreError> {
+    let mut stream = txn.execute(q).await.map_err(backend)?;
+    let row = stream
+        .next(txn.handle())
+        .await
+        .map_err(backend)?
+        .ok_or_else(|| StoreError::Backend("query returned no rows".into()))?;
+    while stream.next(txn.handle()).await.map_err(backend)?.is_some() {}
+    Ok(row)
+}
+
+/// Runs a query inside `txn` and collects every result row.
+async fn fetch_all(txn: &mut Txn, q: Query) -> Result<Vec<Row>, StoreError> {
+    let mut stream = txn.execute(q).await.map_err(backend)?;
+    let mut rows = Vec::new();
+    while let Some(row) = stream.next(txn.handle()).await.map_err(backend)? {
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// Neo4j implementation of the [`Store`] trait.
+///
+/// Follows the schema and Cypher documented in the README. Every RPC runs inside a
+/// single Neo4j transaction; constraints and `MERGE` provide the serialization
+/// guarantees, so the store itself holds no state beyond the connection pool and the
+/// event channel. Events are published for writes made through this instance only.
+#[derive(Clone)]
+pub struct Neo4jStore {
+    graph: Graph,
+    events: broadcast::Sender<StoreEvent>,
+}
+
+impl Neo4jStore {
+    /// Connects to Neo4j and ensures the schema constraints exist.
+    pub async fn connect(config: &Neo4jConfig) -> Result<Self, StoreError> {
+        let mut builder = ConfigBuilder::default()
+            .uri(&config.uri)
+            .user(&config.user)
+            .password(&config.password)
+            .max_connections(config.max_connections);
+        if let Some(database) = &config.database {
+            builder = builder.db(database.as_str());
+        }
+        let graph = Graph::connect(builder.build().map_err(backend)?)
+            .await
+            .map_err(backend)?;
+        Self::from_graph(graph).await
+    }
+
+    /// Wraps an existing connection pool and ensures the schema constraints exist.
+    pub async fn from_graph(graph: Graph) -> Result<Self, StoreError> {
+        let store = Self {
+            graph,
+            events: broadcast::channel(EVENT_BUFFER).0,
+        };
+        store.ensure_constraints().await?;
+        Ok(store)
+    }
+
+    async fn ensure_constraints(&self) -> Result<(), StoreError> {
+        for constraint in CONSTRAINTS {
+            self.graph.run(query(constraint)).await.map_err(backend)?;
+        }
+        self.graph
+            .run(query(BACKFILL_CHANGED_VERSION))
+            .await
+            .map_err(backend)
+    }
+
+    async fn start_txn(&self) -> Result<Txn, StoreError> {
+        self.graph.start_txn().await.map_err(backend)
+    }
+
+    /// Commits on success, rolls back on failure.
+    async fn finish<T>(txn: Txn, result: Result<T, StoreError>) -> Result<T, StoreError> {
+        match result {
+            Ok(value) => {
+                txn.commit().await.map_err(backend)?;
+                Ok(value)
+            }
+            Err(err) => {
+                if let Err(rollback_err) = txn.rollback().await {
+                    tracing::warn!("neo4j rollback failed: {rollback_err}");
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Sends the events of a committed write to subscribers.
+    fn publish(&self, events: Vec<StoreEvent>) {
+        for event in events {
+            // No receivers is not an error: nobody is watching
+            let _ = self.events.send(event);
+        }
+    }
+
+    async fn require_incident(txn: &mut Txn, incident_id: &str) -> Result<Row, StoreError> {
+        let mut rows =
+            fetch_all(txn, query(GET_INCIDENT).param("incident_id", incident_id)).await?;
+        rows.pop()
+            .ok_or_else(|| StoreError::IncidentNotFound(incident_id.to_string()))
+    }
+
+    async fn tombstone_set(
+        txn: &mut Txn,
+        incident_id: &str,
+    ) -> Result<proto::TombstoneSet, StoreError> {
+        let nodes: Vec<proto::NodeTombstone> = fetch_all(
+            txn,
+            query(NODE_TOMBSTONES).param("incident_id", incident_id),
+        )
+        .await?
+        .iter()
+        .map(|row| {
+            Ok(proto::NodeTombstone {
+                node_id: row.get("node_id").map_err(backend)?,
+                provenance: decode_provenance(row.get("provenance").map_err(backend)?, u64::MAX)?,
+                rationale: decode_rationale(row.get("rationale").map_err(backend)?)?,
+            })
+        })
+        .collect::<Result<_, StoreError>>()?;
+        let edges: Vec<proto::EdgeTombstone> = fetch_all(
+            txn,
+            query(EDGE_TOMBSTONES).param("incident_id", incident_id),
+        )
+        .await?
+        .iter()
+        .map(|row| {
+            Ok(proto::EdgeTombstone {
+                entry: Some(row_to_edge_entry(row)?),
+                provenance: decode_provenance(row.get("provenance").map_err(backend)?, u64::MAX)?,
+                rationale: decode_rationale(row.get("rationale").map_err(backend)?)?,
+            })
+        })
+        .collect::<Result<_, StoreError>>()?;
+        Ok(proto::TombstoneSet {
+            node_ids: nodes.iter().map(|t| t.node_id.clone()).collect(),
+            edge_entries: edges.iter().filter_map(|t| t.entry.clone()).collect(),
+            nodes,
+            edges,
+        })
+    }
+
+    /// Reads one page with the node and edge queries, binding the version, filter and
+    /// page position. Each query fetches one row more than the page has room for, to
+    /// tell whether the page ends before the graph does.
+    async fn read_page(
+        txn: &mut Txn,
+        nodes_query: Query,
+        edges_query: Query,
+        graph_query: &GraphQuery,
+        version: u64,
+    ) -> Result<proto::CausalGraph, StoreError> {
+        let filter = &graph_query.filter;
+        let sources: Vec<String> = filter.provenance_sources.iter().cloned().collect();
+        let as_of = version as i64;
+        let mut page = PageBuilder::new(graph_query, version);
+
+        let after_id = match graph_query.after() {
+            None => Some(""),
+            Some(PagePosition::Node(id)) => Some(id.as_str()),
+            Some(PagePosition::Edge { .. }) => None,
+        };
+        if let Some(after_id) = after_id {
+            let limit = page.remaining().saturating_add(1);
+            let rows = fetch_all(
+                txn,
+                nodes_query
+                    .param("as_of", as_of)
+                    .param("after_id", after_id)
+                    .param(
+                        "node_types",
+                        filter
+                            .node_types
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>(),
+                    )
+                    .param("sources", sources.clone())
+                    .param("filter_hypothetical", filter.hypothetical.is_some())
+                    .param("hypothetical", filter.hypothetical.unwrap_or_default())
+                    .param("limit", i64::try_from(limit).unwrap_or(i64::MAX)),
+            )
+            .await?;
+            let more = rows.len() == limit;
+            let mut last = None;
+            for row in &rows {
+                let node = row_to_node(row, version)?;
+                last = Some(PagePosition::Node(node.id.clone()));
+                if !page.node(node) {
+                    break;
+                }
+            }
+            // Rows the filter rejected after the query can leave the page short
+            if let Some(last) = last.filter(|_| more) {
+                page.end_at(last);
+            }
+        }
+
+        if !page.is_closed() {
+            let (after_source, after_target, after_type) = match graph_query.after() {
+                Some(PagePosition::Edge {
+                    source,
+                    target,
+                    edge_type,
+                }) => (source.as_str(), target.as_str(), edge_type.as_str()),
+                _ => ("", "", ""),
+            };
+            let limit = page.remaining().saturating_add(1);
+            let rows = fetch_all(
+                txn,
+                edges_query
+                    .param("as_of", as_of)
+                    .param("after_source", after_source)
+                    .param("after_target", after_target)
+                    .param("after_type", after_type)
+                    .param(
+                        "edge_types",
+                        filter
+                            .edge_types
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>(),
+                    )
+                    .param("sources", sources)
+                    .param("limit", i64::try_from(limit).unwrap_or(i64::MAX)),
+            )
+            .await?;
+            let more = rows.len() == limit;
+            let mut last = None;
+            for row in &rows {
+                let edge = row_to_edge(row, version)?;
+                last = Some(PagePosition::Edge {
+                    source: edge.source.clone(),
+                    target: edge.target.clone(),
+                    edge_type: edge.kind.clone(),
+                });
+                if !page.edge(edge) {
+                    break;
+                }
+            }
+            if let Some(last) = last.filter(|_| more) {
+                page.end_at(last);
+            }
+        }
+        Ok(page.finish())
+    }
+
+    /// Walks the neighborhood `graph_query` asks for in `incident_id`'s live view at
+    /// `version`, with one node and one edge lookup per hop.
+    async fn read_neighborhood(
+        txn: &mut Txn,
+        incident_id: &str,
+        graph_query: &NeighborhoodQuery,
+        version: u64,
+    ) -> Result<proto::NeighborhoodResult, StoreError> {
+        let as_of = version as i64;
+        let edge_types: Vec<String> = graph_query
+            .edge_types
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        let edges_query = |cypher: &str, ids: Vec<String>| {
+            query(cypher)
+                .param("incident_id", incident_id)
+                .param("as_of", as_of)
+                .param("edge_types", edge_types.clone())
+                .param("ids", ids)
+        };
+        let read_nodes = |ids: Vec<String>| {
+            query(NEIGHBORHOOD_NODES)
+                .param("incident_id", incident_id)
+                .param("as_of", as_of)
+                .param("ids", ids)
+        };
+
+        let mut traversal = Traversal::new(graph_query);
+        let seeds = graph_query.node_ids.iter().cloned().collect();
+        let rows = fetch_all(txn, read_nodes(seeds)).await?;
+        traversal.admit(
+            rows.iter()
+                .map(|row| row_to_node(row, version))
+                .collect::<Result<Vec<_>, _>>()?,
+        );
+        while let Some(frontier) = traversal.frontier() {
+            let direction = graph_query.direction;
+            let rows = fetch_all(
+                txn,
+                edges_query(ADJACENT_EDGES, frontier.iter().cloned().collect())
+                    .param("outgoing", direction.follows_outgoing())
+                    .param("incoming", direction.follows_incoming()),
+            )
+            .await?;
+            let edges = rows
+                .iter()
+                .map(|row| row_to_edge(row, version))
+                .collect::<Result<Vec<_>, _>>()?;
+            let next = traversal.step(&edges);
+            let rows = fetch_all(txn, read_nodes(next.into_iter().collect())).await?;
+            traversal.admit(
+                rows.iter()
+                    .map(|row| row_to_node(row, version))
+                    .collect::<Result<Vec<_>, _>>()?,
+            );
+        }
+
+        let reached = traversal.node_ids().cloned().collect();
+        let rows = fetch_all(txn, edges_query(INDUCED_EDGES, reached)).await?;
+        let edges = rows
+            .iter()
+            .map(|row| row_to_edge(row, version))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(traversal.finish(version, edges))
+    }
+
+    /// Counts `incident_id`'s universe at `version` with aggregate queries, so no
+    /// element leaves the database.
+    async fn read_metrics(
+        txn: &mut Txn,
+        incident_id: &str,
+        version: u64,
+    ) -> Result<proto::IncidentMetrics, StoreError> {
+        let counts = |cypher: &str| {
+            query(cypher)
+                .param("incident_id", incident_id)
+                .param("as_of", version as i64)
+        };
+        let count = |row: &Row, column: &str| -> Result<u64, StoreError> {
+            Ok(row.get::<i64>(column).map_err(backend)? as u64)
+        };
+
+        let mut metrics = MetricsBuilder::default();
+        for row in fetch_all(txn, counts(NODE_METRICS)).await? {
+            let kind: String = row.get("kind").map_err(backend)?;
+            let candidates: Vec<Vec<String>> = row.get("candidate_provenance").map_err(backend)?;
+            let support = candidates
+                .into_iter()
+                .map(|events| {
+                    let provenance = decode_provenance(events, version)?;
+                    let sources: BTreeSet<_> = provenance.into_iter().map(|p| p.source).collect();
+                    Ok(sources.len() as u64)
+                })
+                .collect::<Result<Vec<_>, StoreError>>()?;
+            metrics.nodes(
+                &kind,
+                count(&row, "live")?,
+                count(&row, "eliminated")?,
+                support,
+            );
+        }
+        for row in fetch_all(txn, counts(EDGE_METRICS)).await? {
+            let kind: String = row.get("kind").map_err(backend)?;
+            metrics.edges(&kind, count(&row, "live")?, count(&row, "eliminated")?);
+        }
+        let row = fetch_one(txn, counts(UNMATCHED_TOMBSTONES)).await?;
+        metrics.unmatched(count(&row, "nodes")?, count(&row, "edges")?);
+        Ok(metrics.finish(incident_id, version))
+    }
+
+    async fn current_version(txn: &mut Txn) -> Result<u64, StoreError> {
+        let row = fetch_one(txn, query(GET_VERSION)).await?;
+        Ok(row.get::<i64>("version").map_err(backend)? as u64)
+    }
+
+    /// The main-graph version an incident's live view is resolved against.
+    fn anchor_of(incident: &Row) -> Result<u64, StoreError> {
+        Ok(incident.get::<i64>("universe_anchor").map_err(backend)? as u64)
+    }
+
+    /// Stored types of those `ids` that exist in the main graph with a type.
+    async fn node_types_in(
+        txn: &mut Txn,
+        ids: Vec<String>,
+    ) -> Result<BTreeMap<String, NodeType>, StoreError> {
+        fetch_all(txn, query(NODE_TYPES).param("ids", ids))
+            .await?
+            .iter()
+            .map(|row| {
+                let id: String = row.get("id").map_err(backend)?;
+                let node_type: String = row.get("type").map_err(backend)?;
+                Ok((id, NodeType::from_name(&node_type)))
+            })
+            .collect()
+    }
+
+    /// Stored edges with an endpoint in `ids`.
+    async fn edges_touching_in(
+        txn: &mut Txn,
+        ids: Vec<String>,
+    ) -> Result<Vec<EdgeKey>, StoreError> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        fetch_all(txn, query(EDGES_TOUCHING).param("ids", ids))
+            .await?
+            .iter()
+            .map(|row| {
+                let edge_type: String = row.get("type").map_err(backend)?;
+                Ok(EdgeKey::new(
+                    row.get::<String>("source").map_err(backend)?,
+                    row.get::<String>("target").map_err(backend)?,
+                    EdgeType::from_name(&edge_type),
+                ))
+            })
+            .collect()
+    }
+
+    /// Merges `delta` inside `txn`, collecting the changes it makes into `events`. With
+    /// a `schema`, the delta is checked once the version lock is held.
+    async fn merge_hypothesis_in(
+        txn: &mut Txn,
+        delta: proto::HypothesisDelta,
+        schema: Option<&CausalSchema>,
+        events: &mut Vec<StoreEvent>,
+    ) -> Result<proto::HypothesisMergeResult, StoreError> {
+        let atomic = delta.atomic;
+        let policy = delta.dangling_edge_policy();
+        let version_row = fetch_one(txn, query(LOCK_VERSION)).await?;
+        if let Some(schema) = schema {
+            let mut stored = Self::node_types_in(txn, schema_node_ids(&delta)).await?;
+            let first_typed = validation::first_typed_nodes(&delta, &stored);
+            let stored_edges = Self::edges_touching_in(txn, first_typed).await?;
+            let endpoints = unknown_endpoints(&stored_edges, &stored);
+            stored.extend(Self::node_types_in(txn, endpoints).await?);
+            validation::validate_edge_schema(&delta, schema, &stored, &stored_edges)?;
+        }
+        let current_version = version_row.get::<i64>("version").map_err(backend)?;
+        let next_version = current_version + 1;
+        let mut changed = false;
+        let mut created_ids = Vec::new();
+        let mut merged_ids = Vec::new();
+        let mut conflicts = Vec::new();
+
+        for node in delta.nodes {
+            let node_type = node_type_of(node.r#type, &node.kind).map_err(backend)?;
+            let row = fetch_one(
+                txn,
+                query(MERGE_NODE)
+                    .param("id", node.id.as_str())
+                    .param("type", node_type.to_string())
+                    .param("label", node.label.as_str())
+                    .param("hypothetical", node.hypothetical)
+                    .param("version", next_version)
+                    .param(
+                        "provenance",
+                        provenance_param(node.provenance, next_version as u64)?,
+                    ),
+            )
+            .await?;
+
+            changed |= row.get::<bool>("changed").map_err(backend)?;
+            let created: bool = row.get("created").map_err(backend)?;
+            let compatible: bool = row.get("compatible").map_err(backend)?;
+            if created || row.get::<bool>("refining").map_err(backend)? {
+                events.push(StoreEvent::NodeAdded {
+                    version: next_version as u64,
+                    node: merged_node(&row, &node.id)?,
+                });
+            } else if row.get::<bool>("confirming").map_err(backend)? {
+                events.push(StoreEvent::NodeConfirmed {
+                    version: next_version as u64,
+                    node_id: node.id.clone(),
+                });
+            }
+            if created {
+                created_ids.push(node.id);
+            } else if compatible {
+                merged_ids.push(node.id);
+            } else {
+                let existing_type: String = row.get("existing_type").map_err(backend)?;
+                let existing_label: String = row.get("existing_label").map_err(backend)?;
+                let created_version: i64 = row.get("existing_created_version").map_err(backend)?;
+                let first_writer = decode_provenance(
+                    row.get("existing_provenance").map_err(backend)?,
+                    created_version as u64,
+                )?;
+                let proposed_type = node_type.to_string();
+                let fields = [
+                    ("type", existing_type, proposed_type),
+                    ("label", existing_label, node.label),
+                ];
+                for (field, existing_value, proposed_value) in fields {
+                    if existing_value != proposed_value {
+                        conflicts.push(proto::MergeConflict {
+                            id: node.id.clone(),
+                            field: field.to_string(),
+                            existing_value,
+                            proposed_value,
+                            first_writer_provenance: first_writer.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        let mut dangling_edge_ids = Vec::new();
+        for edge in delta.edges {
+            let edge_type = edge_type_of(edge.r#type, &edge.kind).map_err(backend)?;
+            let id = edge_id(&edge.source, &edge.target, &edge_type);
+            let endpoints = fetch_one(
+                txn,
+                query(EDGE_ENDPOINTS)
+                    .param("source", edge.source.as_str())
+                    .param("target", edge.target.as_str()),
+            )
+            .await?;
+            let mut missing = Vec::new();
+            if !endpoints.get::<bool>("source_exists").map_err(backend)? {
+                missing.push(edge.source.as_str());
+            }
+            if !endpoints.get::<bool>("target_exists").map_err(backend)? {
+                missing.push(edge.target.as_str());
+            }
+            if !missing.is_empty() {
+                match policy {
+                    proto::DanglingEdgePolicy::Reject => {
+                        dangling_edge_ids.push(id);
+                        continue;
+                    }
+                    proto::DanglingEdgePolicy::Placeholder => {
+                        for endpoint in missing {
+                            let row = fetch_one(
+                                txn,
+                                query(MERGE_NODE)
+                                    .param("id", endpoint)
+                                    .param("type", NodeType::Placeholder.to_string())
+                                    .param("label", endpoint)
+                                    .param("hypothetical", true)
+                                    .param("version", next_version)
+                                    .param(
+                                        "provenance",
+                                        provenance_param(
+                                            edge.provenance.clone(),
+                                            next_version as u64,
+                                        )?,
+                                    ),
+                            )
+                            .await?;
+                            events.push(StoreEvent::NodeAdded {
+                                version: next_version as u64,
+                                node: merged_node(&row, endpoint)?,
+                            });
+                            changed = true;
+                            created_ids.push(endpoint.to_string());
+                        }
+                    }
+                    proto::DanglingEdgePolicy::Unspecified | proto::DanglingEdgePolicy::Report => {
+                        dangling_edge_ids.push(id.clone())
+                    }
+                }
+            }
+
+            let row = fetch_one(
+                txn,
+                query(MERGE_EDGE)
+                    .param("source", edge.source.as_str())
+                    .param("target", edge.target.as_str())
+                    .param("type", edge_type.to_string())
+                    .param("version", next_version)
+                    .param(
+                        "provenance",
+                        provenance_param(edge.provenance, next_version as u64)?,
+                    ),
+            )
+            .await?;
+
+            changed |= row.get::<bool>("changed").map_err(backend)?;
+            if row.get::<bool>("created").map_err(backend)? {
+                events.push(StoreEvent::EdgeAdded {
+                    version: next_version as u64,
+                    edge: proto::Edge {
+                        r#type: i32::from(&edge_type),
+                        kind: edge_type.to_string(),
+                        provenance: decode_provenance(
+                            row.get("provenance").map_err(backend)?,
+                            u64::MAX,
+                        )?,
+                        source: edge.source,
+                        target: edge.target,
+                    },
+                });
+                created_ids.push(id);
+            } else {
+                merged_ids.push(id);
+            }
+        }
+
+        let result = proto::HypothesisMergeResult {
+            created_ids,
+            merged_ids,
+            conflicts,
+            version: current_version as u64,
+            dangling_edge_ids,
+        };
+        if aborts_atomic(atomic, policy, &result) {
+            // The caller rolls the transaction back; report the rejections alone.
+            return Ok(proto::HypothesisMergeResult {
+                created_ids: Vec::new(),
+                merged_ids: Vec::new(),
+                ..result
+            });
+        }
+
+        let version = if changed {
+            txn.run(query(SET_VERSION).param("version", next_version))
+                .await
+                .map_err(backend)?;
+            next_version
+        } else {
+            current_version
+        };
+
+        Ok(proto::HypothesisMergeResult {
+            version: version as u64,
+            ..result
+        })
+    }
+
+    async fn confirm_nodes_in(
+        txn: &mut Txn,
+        request: proto::ConfirmNodesRequest,
+        events: &mut Vec<StoreEvent>,
+    ) -> Result<proto::ConfirmNodesResult, StoreError> {
+        let version_row = fetch_one(txn, query(LOCK_VERSION)).await?;
+        let current_version = version_row.get::<i64>("version").map_err(backend)?;
+        let next_version = current_version + 1;
+        let prov = Provenance::from(request.provenance.unwrap_or_default())
+            .with_version(next_version as u64);
+        let prov_key = provenance_key(&prov);
+        let prov_event = provenance_event(&prov)?;
+
+        let mut changed = false;
+        let mut result = proto::ConfirmNodesResult::default();
+        for node_id in request.node_ids {
+            let row = fetch_one(
+                txn,
+                query(CONFIRM_NODE)
+                    .param("id", node_id.as_str())
+                    .param("version", next_version)
+                    .param("prov_key", prov_key.as_str())
+                    .param("prov_event", prov_event.as_str()),
+            )
+            .await?;
+            if !row.get::<bool>("found").map_err(backend)? {
+                result.unknown_ids.push(node_id);
+                continue;
+            }
+            let confirming: bool = row.get("confirming").map_err(backend)?;
+            changed |= confirming || row.get::<bool>("fresh").map_err(backend)?;
+            if confirming {
+                events.push(StoreEvent::NodeConfirmed {
+                    version: next_version as u64,
+                    node_id: node_id.clone(),
+                });
+                result.confirmed_ids.push(node_id);
+            } else {
+                result.already_confirmed_ids.push(node_id);
+            }
+        }
+
+        let version = if changed {
+            txn.run(query(SET_VERSION).param("version", next_version))
+                .await
+                .map_err(backend)?;
+            next_version
+        } else {
+            current_version
+        };
+        result.version = version as u64;
+        Ok(result)
+    }
+
+    async fn merge_node_tombstones_in(
+        txn: &mut Txn,
+        request: proto::NodeTombstoneRequest,
+        events: &mut Vec<StoreEvent>,
+    ) -> Result<proto::TombstoneMergeResult, StoreError> {
+        Self::require_incident(txn, &request.incident_id).await?;
+        let version = Self::current_version(txn).await?;
+        let prov = Provenance::from(request.provenance.unwrap_or_default()).with_version(version);
+        let prov_key = provenance_key(&prov);
+        let prov_event = provenance_event(&prov)?;
+        let rationale = request.rationale.map(|r| Rationale::from(r).by(&prov));
+        let rationale = rationale_param(rationale.as_ref())?;
+
+        let mut result = proto::TombstoneMergeResult::default();
+        for node_id in request.node_ids {
+            let row = fetch_one(
+                txn,
+                query(MERGE_NODE_TOMBSTONE)
+                    .param("incident_id", request.incident_id.as_str())
+                    .param("node_id", node_id.as_str())
+                    .param("prov_key", prov_key.as_str())
+                    .param("prov_event", prov_event.as_str())
+                    .param("rationale", rationale.clone()),
+            )
+            .await?;
+            if classify_tombstone(&row, node_id.clone(), &mut result)? {
+                events.push(StoreEvent::NodeTombstoned {
+                    incident_id: request.incident_id.clone(),
+                    node_id,
+                });
+            }
+        }
+        Ok(result)
+    }
+
+    async fn merge_edge_tombstones_in(
+        txn: &mut Txn,
+        request: proto::EdgeTombstoneRequest,
+        events: &mut Vec<StoreEvent>,
+    ) -> Result<proto::TombstoneMergeResult, StoreError> {
+        Self::require_incident(txn, &request.incident_id).await?;
+        let version = Self::current_version(txn).await?;
+        let prov = Provenance::from(request.provenance.unwrap_or_default()).with_version(version);
+        let prov_key = provenance_key(&prov);
+        let prov_event = provenance_event(&prov)?;
+        let rationale = request.rationale.map(|r| Rationale::from(r).by(&prov));
+        let rationale = rationale_param(rationale.as_ref())?;
+
+        let mut result = proto::TombstoneMergeResult::default();
+        for entry in request.entries {
+            let edge_type = edge_type_of(entry.r#type, &entry.kind).map_err(backend)?;
+            let row = fetch_one(
+                txn,
+                query(MERGE_EDGE_TOMBSTONE)
+                    .param("incident_id", request.incident_id.as_str())
+                    .param("source", entry.source.as_str())
+                    .param("target", entry.target.as_str())
+                    .param("type", edge_type.to_string())
+                    .param("prov_key", prov_key.as_str())
+                    .param("prov_event", prov_event.as_str())
+                    .param("rationale", rationale.clone()),
+            )
+            .await?;
+            let id = edge_id(&entry.source, &entry.target, &edge_type);
+            if classify_tombstone(&row, id, &mut result)? {
+                events.push(StoreEvent::EdgeTombstoned {
+                    incident_id: request.incident_id.clone(),
+                    entry: proto::EdgeTombstoneEntry {
+                        r#type: i32::from(&edge_type),
+                        kind: edge_type.to_string(),
+                        ..entry
+                    },
+                });
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Maps the `ON CREATE` / `ON MATCH` outcome of a tombstone MERGE onto the result:
+/// created+matched → applied, created+unmatched → unmatched, matched → already tombstoned.
+/// Returns whether the tombstone is new.
+fn classify_tombstone(
+    row: &Row,
+    id: String,
+    result: &mut proto::TombstoneMergeResult,
+) -> Result<bool, StoreError> {
+    let created: bool = row.get("created").map_err(backend)?;
+    let unmatched: bool = row.get("unmatched").map_err(backend)?;
+    match (created, unmatched) {
+        (false, _) => result.already_tombstoned_ids.push(id),
+        (true, true) => result.unmatched_ids.push(id),
+        (true, false) => result.applied_ids.push(id),
+    }
+    Ok(created)
+}
+
+impl Store for Neo4jStore {
+    async fn merge_hypothesis(
+        &self,
+        delta: proto::HypothesisDelta,
+        schema: Option<&CausalSchema>,
+    ) -> Result<proto::HypothesisMergeResult, StoreError> {
+        let (atomic, policy) = (delta.atomic, delta.dangling_edge_policy());
+        let mut txn = self.start_txn().await?;
+        let mut events = Vec::new();
+        match Self::merge_hypothesis_in(&mut txn, delta, schema, &mut events).await {
+            // A rejected atomic delta must leave no trace
+            Ok(result) if aborts_atomic(atomic, policy, &result) => {
+                txn.rollback().await.map_err(backend)?;
+                Ok(result)
+            }
+            result => {
+                let result = Self::finish(txn, result).await?;
+                self.publish(events);
+                Ok(result)
+            }
+        }
+    }
+
+    async fn confirm_nodes(
+        &self,
+        request: proto::ConfirmNodesRequest,
+    ) -> Result<proto::ConfirmNodesResult, StoreError> {
+        let mut txn = self.start_txn().await?;
+        let mut events = Vec::new();
+        let result = Self::confirm_nodes_in(&mut txn, request, &mut events).await;
+        let result = Self::finish(txn, result).await?;
+        self.publish(events);
+        Ok(result)
+    }
+
+    async fn create_incident(
+        &self,
+        request: proto::CreateIncidentRequest,
+    ) -> Result<proto::CreateIncidentResult, StoreError> {
+        let mut txn = self.start_txn().await?;
+        let result = async {
+            let row = fetch_one(
+                &mut txn,
+                query(CREATE_INCIDENT)
+                    .param("incident_id", request.incident_id.as_str())
+                    .param("follow_main_graph", request.follow_main_graph),
+            )
+            .await?;
+            Ok(proto::CreateIncidentResult {
+                created: row.get("created").map_err(backend)?,
+                universe_anchor: Self::anchor_of(&row)?,
+                incident_id: request.incident_id,
+            })
+        }
+        .await;
+        Self::finish(txn, result).await
+    }
+
+    async fn get_incident_context(
+        &self,
+        incident_id: &str,
+    ) -> Result<proto::IncidentContext, StoreError> {
+        let mut txn = self.start_txn().await?;
+        let result = async {
+            let incident = Self::require_incident(&mut txn, incident_id).await?;
+            let created_at: i64 = incident.get("created_at").map_err(backend)?;
+            let tombstones = Self::tombstone_set(&mut txn, incident_id).await?;
+            Ok(proto::IncidentContext {
+                incident_id: incident_id.to_string(),
+                created_at: Some(millis_to_timestamp(created_at)),
+                tombstones: Some(tombstones),
+                universe_anchor: Self::anchor_of(&incident)?,
+                elimination_set_id: elimination_set_id(incident_id),
+                follows_main_graph: incident.get("follows_main_graph").map_err(backend)?,
+            })
+        }
+        .await;
+        Self::finish(txn, result).await
+    }
+
+    async fn get_incident_metrics(
+        &self,
+        incident_id: &str,
+        as_of_version: Option<u64>,
+    ) -> Result<proto::IncidentMetrics, StoreError> {
+        let mut txn = self.start_txn().await?;
+        let result = async {
+            let incident = Self::require_incident(&mut txn, incident_id).await?;
+            let version = resolve_as_of(as_of_version, Self::anchor_of(&incident)?)?;
+            Self::read_metrics(&mut txn, incident_id, version).await
+        }
+        .await;
+        Self::finish(txn, result).await
+    }
+
+    async fn merge_node_tombstones(
+        &self,
+        request: proto::NodeTombstoneRequest,
+    ) -> Result<proto::TombstoneMergeResult, StoreError> {
+        let mut txn = self.start_txn().await?;
+        let mut events = Vec::new();
+        let result = Self::merge_node_tombstones_in(&mut txn, request, &mut events).await;
+        let result = Self::finish(txn, result).await?;
+        self.publish(events);
+        Ok(result)
+    }
+
+    async fn merge_edge_tombstones(
+        &self,
+        request: proto::EdgeTombstoneRequest,
+    ) -> Result<proto::TombstoneMergeResult, StoreError> {
+        let mut txn = self.start_txn().await?;
+        let mut events = Vec::new();
+        let result = Self::merge_edge_tombstones_in(&mut txn, request, &mut events).await;
+        let result = Self::finish(txn, result).await?;
+        self.publish(events);
+        Ok(result)
+    }
+
+    async fn get_live_view(
+        &self,
+        incident_id: &str,
+        graph_query: &GraphQuery,
+    ) -> Result<proto::CausalGraph, StoreError> {
+        let mut txn = self.start_txn().await?;
+        let result = async {
+            let incident = Self::require_incident(&mut txn, incident_id).await?;
+            let version = graph_query.resolve_version(Self::anchor_of(&incident)?)?;
+            Self::read_page(
+                &mut txn,
+                query(LIVE_NODES).param("incident_id", incident_id),
+                query(LIVE_EDGES).param("incident_id", incident_id),
+                graph_query,
+                version,
+            )
+            .await
+        }
+        .await;
+        Self::finish(txn, result).await
+    }
+
+    async fn get_neighborhood(
+        &self,
+        incident_id: &str,
+        graph_query: &NeighborhoodQuery,
+    ) -> Result<proto::NeighborhoodResult, StoreError> {
+        let mut txn = self.start_txn().await?;
+        let result = async {
+            let incident = Self::require_incident(&mut txn, incident_id).await?;
+            let version = resolve_as_of(graph_query.as_of_version, Self::anchor_of(&incident)?)?;
+            Self::read_neighborhood(&mut txn, incident_id, graph_query, version).await
+        }
+        .await;
+        Self::finish(txn, result).await
+    }
+
+    async fn get_tombstones(&self, incident_id: &str) -> Result<proto::TombstoneSet, StoreError> {
+        let mut txn = self.start_txn().await?;
+        let result = async {
+            Self::require_incident(&mut txn, incident_id).await?;
+            Self::tombstone_set(&mut txn, incident_id).await
+        }
+        .await;
+        Self::finish(txn, result).await
+    }
+
+    async fn get_main_graph(
+        &self,
+        graph_query: &GraphQuery,
+    ) -> Result<proto::CausalGraph, StoreError> {
+        let mut txn = self.start_txn().await?;
+        let result = async {
+            let head = Self::current_version(&mut txn).await?;
+            let version = graph_query.resolve_version(head)?;
+            Self::read_page(
+                &mut txn,
+                query(ALL_NODES),
+                query(ALL_EDGES),
+                graph_query,
+                version,
+            )
+            .await
+        }
+        .await;
+        Self::finish(txn, result).await
+    }
+
+    async fn get_main_graph_changes(
+        &self,
+        after_cursor: u64,
+        max_versions: u64,
+    ) -> Result<Vec<proto::MainGraphChange>, StoreError> {
+        let mut txn = self.start_txn().await?;
+        let result = async {
+            let head = Self::current_version(&mut txn).await?;
+            resolve_as_of(Some(after_cursor), head)?;
+
+            let through = after_cursor.saturating_add(max_versions).min(head);
+            let mut log = ChangeLog::between(after_cursor, through);
+            let history = |cypher: &str| {
+                query(cypher)
+                    .param("after", after_cursor as i64)
+                    .param("through", through as i64)
+            };
+            for row in fetch_all(&mut txn, history(NODE_HISTORY)).await? {
+                let created: i64 = row.get("created_version").map_err(backend)?;
+                let confirmed: i64 = row.get("confirmed_version").map_err(backend)?;
+                let refined: i64 = row.get("refined_version").map_err(backend)?;
+                let refined = match u64::try_from(refined) {
+                    Ok(version) => Some(Refinement {
+                        version,
+                        placeholder_label: row.get("placeholder_label").map_err(backend)?,
+                    }),
+                    Err(_) => None,
+                };
+                log.node(
+                    row_to_node(&row, u64::MAX)?,
+                    created as u64,
+                    u64::try_from(confirmed).ok(),
+                    refined,
+                );
+            }
+            for row in fetch_all(&mut txn, history(EDGE_HISTORY)).await? {
+                let created: i64 = row.get("created_version").map_err(backend)?;
+                log.edge(row_to_edge(&row, u64::MAX)?, created as u64);
+            }
+            Ok(log.into_changes())
+        }
+        .await;
+        Self::finish(txn, result).await
+    }
+
+    async fn graph_size(&self) -> Result<GraphSize, StoreError> {
+        let mut txn = self.start_txn().await?;
+        let result = async {
+            let row = fetch_one(&mut txn, query(GRAPH_SIZE)).await?;
+            let count = |column: &str| -> Result<u64, StoreError> {
+                Ok(row.get::<i64>(column).map_err(backend)? as u64)
+            };
+            Ok(GraphSize {
+                version: count("version")?,
+                nodes: count("nodes")?,
+                edges: count("edges")?,
+                incidents: count("incidents")?,
+                node_tombstones: count("node_tombstones")?,
+                edge_tombstones: count("edge_tombstones")?,
+            })
+        }
+        .await;
+        Self::finish(txn, result).await
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<StoreEvent> {
+        self.events.subscribe()
+    }
+
+    async fn check_health(&self) -> Result<(), StoreError> {
+        self.graph.run(query("RETURN 1")).await.map_err(backend)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use super::*;
+    use crate::store::neighborhood::Direction;
+    use crate::store::query::{GraphFilter, PageToken};
+
+    // --- Encoding helpers (no database required) ---
+
+    #[test]
+    fn provenance_key_is_source_and_trigger() {
+        let prov = Provenance::new("agent-1", "alert").with_timestamp(100, 5);
+        assert_eq!(provenance_key(&prov), "agent-1|alert");
+    }
+
+    #[test]
+    fn provenance_event_roundtrip() {
+        let prov = Provenance::new("agent-1", "alert").with_timestamp(100, 5);
+        let event = provenance_event(&prov).unwrap();
+        let decoded = decode_provenance(vec![event], 0).unwrap();
+        assert_eq!(decoded[0].source, "agent-1");
+        assert_eq!(decoded[0].trigger, "alert");
+        assert_eq!(decoded[0].timestamp.unwrap().seconds, 100);
+        assert_eq!(decoded[0].timestamp.unwrap().nanos, 5);
+    }
+
+    #[test]
+    fn provenance_param_dedups_by_identity() {
+        let entries = vec![
+            proto::Provenance {
+                source: "a".into(),
+                trigger: "t".into(),
+                timestamp: Some(prost_types::Timestamp {
+                    seconds: 1,
+                    nanos: 0,
+                }),
+                version: 0,
+            },
+            proto::Provenance {
+                source: "a".into(),
+                trigger: "t".into(),
+                timestamp: Some(prost_types::Timestamp {
+                    seconds: 2,
+                    nanos: 0,
+                }),
+                version: 0,
+            },
+        ];
+        let param = provenance_param(entries, 3).unwrap();
+        assert_eq!(param.len(), 1);
+        assert_eq!(param[0]["key"], "a|t");
+    }
+
+    #[test]
+    fn decode_provenance_filters_later_versions() {
+        let events = vec![
+            provenance_event(&Provenance::new("a", "t1").with_version(1)).unwrap(),
+            provenance_event(&Provenance::new("b", "t2").with_version(4)).unwrap(),
+        ];
+        let decoded = decode_provenance(events.clone(), 2).unwrap();
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0].version, 1);
+        assert_eq!(decode_provenance(events, 4).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn millis_convert_to_timestamp() {
+        let ts = millis_to_timestamp(1_700_000_000_123);
+        assert_eq!(ts.seconds, 1_700_000_000);
+        assert_eq!(ts.nanos, 123_000_000);
+    }
+
+    // --- Against a live Neo4j ---
+    //
+    // Run with a local container, e.g.
+    //   docker run -p 7687:7687 -e NEO4J_AUTH=neo4j/password neo4j:5
+    //   TEE_NEO4J_TEST_URI=127.0.0.1:7687 cargo test -- --ignored
+    //
+    // Tests share the database, so every test namespaces its ids with a unique prefix.
+
+    async fn test_store() -> Neo4jStore {
+        let uri =
+            std::env::var("TEE_NEO4J_TEST_URI").expect("set TEE_NEO4J_TEST_URI to run Neo4j tests");
+        let user = std::env::var("TEE_NEO4J_TEST_USER").unwrap_or_else(|_| "neo4j".into());
+        let password =
+            std::env::var("TEE_NEO4J_TEST_PASSWORD").unwrap_or_else(|_| "password".into());
+        Neo4jStore::connect(&Neo4jConfig {
+            uri,
+            user,
+            password,
+            database: None,
+            max_connections: Neo4jConfig::DEFAULT_MAX_CONNECTIONS,
+        })
+        .await
+        .unwrap()
+    }
+
+    fn unique(prefix: &str) -> String {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        format!("{prefix}-{nanos}")
+    }
+
+    fn prov() -> proto::Provenance {
+        proto::Provenance {
+            source: "agent-1".into(),
+            trigger: "alert".into(),
+            timestamp: None,
+            version: 0,
+        }
+    }
+
+    fn make_node(id: &str, node_type: proto::NodeType, label: &str) -> proto::Node {
+        proto::Node {
+            id: id.into(),
+            r#type: node_type as i32,
+            label: label.into(),
+            hypothetical: true,
+            provenance: vec![prov()],
+            kind: String::new(),
+        }
+    }
+
+    fn make_edge(source: &str, target: &str) -> proto::Edge {
+        proto::Edge {
+            source: source.into(),
+            target: target.into(),
+            r#type: proto::EdgeType::DependsOn as i32,
+            provenance: vec![prov()],
+            kind: String::new(),
+        }
+    }
+
+    fn create_request(incident_id: &str, follow_main_graph: bool) -> proto::CreateIncidentRequest {
+        proto::CreateIncidentRequest {
+            incident_id: incident_id.into(),
+            follow_main_graph,
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a running Neo4j (TEE_NEO4J_TEST_URI)"]
+    async fn merge_is_idempotent_and_detects_conflicts() {
+        let store = test_store().await;
+        let id = unique("n");
+        let delta = proto::HypothesisDelta {
+            nodes: vec![make_node(&id, proto::NodeType::Service, "svc")],
+            edges: vec![],
+            atomic: false,
+            dangling_edge_policy: proto::DanglingEdgePolicy::Report as i32,
+        };
+
+        let first = store.merge_hypothesis(delta.clone(), None).await.unwrap();
+        assert_eq!(first.created_ids, vec![id.clone()]);
+
+        let second = store.merge_hypothesis(delta, None).await.unwrap();
+        assert_eq!(second.merged_ids, vec![id.clone()]);
+
+        let conflicting = proto::HypothesisDelta {
+            nodes: vec![make_node(&id, proto::NodeType::Infrastructure, "svc")],
+            edges: vec![],
+            atomic: false,
+            dangling_edge_policy: proto::DanglingEdgePolicy::Report as i32,
+        };
+        let third = store.merge_hypothesis(conflicting, None).await.unwrap();
+        assert_eq!(third.conflicts.len(), 1);
+        assert_eq!(third.conflicts[0].field, "type");
+        assert_eq!(third.conflicts[0].existing_value, "SERVICE");
+        assert_eq!(third.conflicts[0].proposed_value, "INFRASTRUCTURE");
+        assert_eq!(third.conflicts[0].first_writer_provenance.len(), 1);
+
+        let both = proto::HypothesisDelta {
+            nodes: vec![make_node(&id, proto::NodeType::Infrastructure, "renamed")],
+            edges: vec![],
+            atomic: false,
+            dangling_edge_policy: proto::DanglingEdgePolicy::Report as i32,
+        };
+        let fourth = store.merge_hypothesis(both, None).await.unwrap();
+        let fields: Vec<_> = fourth.conflicts.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, vec!["type", "label"]);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a running Neo4j (TEE_NEO4J_TEST_URI)"]
+    async fn tombstones_classify_and_filter_live_view() {
+        let store = test_store().await;
+        let incident = unique("inc");
+        let a = unique("a");
+        let b = unique("b");
+
+        store
+            .merge_hypothesis(
+                proto::HypothesisDelta {
+                    nodes: vec![
+                        make_node(&a, proto::NodeType::Service, "a"),
+                        make_node(&b, proto::NodeType::Service, "b"),
+                    ],
+                    edges: vec![make_edge(&a, &b)],
+                    atomic: false,
+                    dangling_edge_policy: proto::DanglingEdgePolicy::Report as i32,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(
+            store
+                .create_incident(create_request(&incident, false))
+                .await
+                .unwrap()
+                .created
+        );
+        assert!(
+            !store
+                .create_incident(create_request(&incident, false))
+                .await
+                .unwrap()
+                .created
+        );
+
+        let request = proto::NodeTombstoneRequest {
+            incident_id: incident.clone(),
+            node_ids: vec![a.clone(), unique("ghost")],
+            provenance: Some(prov()),
+            rationale: None,
+        };
+        let result = store.merge_node_tombstones(request.clone()).await.unwrap();
+        assert_eq!(result.applied_ids, vec![a.clone()]);
+        assert_eq!(result.unmatched_ids.len(), 1);
+
+        let again = store.merge_node_tombstones(request.clone()).await.unwrap();
+        assert_eq!(again.already_tombstoned_ids.len(), 2);
+
+        let mut reviewer = prov();
+        reviewer.source = "reviewer".into();
+        store
+            .merge_node_tombstones(proto::NodeTombstoneRequest {
+                node_ids: vec![a.clone()],
+                provenance: Some(reviewer),
+                rationale: Some(proto::EliminationRationale {
+                    text: "no errors in the window".into(),
+                    evidence_uris: vec!["https://logs.example/q/1".into()],
+                    ..Default::default()
+                }),
+                ..request
+            })
+            .await
+            .unwrap();
+        let tombstones = store.get_tombstones(&incident).await.unwrap();
+        let eliminated = tombstones.nodes.iter().find(|t| t.node_id == a).unwrap();
+        assert_eq!(eliminated.provenance.len(), 2);
+        assert_eq!(eliminated.rationale.len(), 1);
+        assert_eq!(eliminated.rationale[0].source, "reviewer");
+
+        let view = store
+            .get_live_view(&incident, &GraphQuery::default())
+            .await
+            .unwrap();
+        assert!(view.nodes.iter().all(|n| n.id != a));
+        assert!(view.nodes.iter().any(|n| n.id == b));
+        assert!(view.edges.iter().all(|e| e.source != a));
+
+        let ctx = store.get_incident_context(&incident).await.unwrap();
+        assert!(ctx.created_at.is_some());
+        assert_eq!(ctx.tombstones.unwrap().node_ids.len(), 2);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a running Neo4j (TEE_NEO4J_TEST_URI)"]
+    async fn neighborhood_walks_the_live_view() {
+        let store = test_store().await;
+        let incident = unique("inc");
+        let [a, b, c] = [unique("a"), unique("b"), unique("c")];
+        store
+            .merge_hypothesis(
+                proto::HypothesisDelta {
+                    nodes: vec![
+                        make_node(&a, proto::NodeType::Service, "a"),
+                        make_node(&b, proto::NodeType::Service, "b"),
+                        make_node(&c, proto::NodeType::Service, "c"),
+                    ],
+                    edges: vec![make_edge(&a, &b), make_edge(&b, &c)],
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        store
+            .create_incident(create_request(&incident, true))
+            .await
+            .unwrap();
+
+        let query = NeighborhoodQuery {
+            node_ids: [b.clone()].into(),
+            hops: 1,
+            direction: Direction::Downstream,
+            ..NeighborhoodQuery::default()
+        };
+        let result = store.get_neighborhood(&incident, &query).await.unwrap();
+        let graph = result.graph.unwrap();
+        let ids: Vec<_> = graph.nodes.iter().map(|n| n.id.clone()).collect();
+        assert_eq!(ids, [b.clone(), c.clone()]);
+        assert_eq!(graph.edges.len(), 1);
+
+        store
+            .merge_node_tombstones(proto::NodeTombstoneRequest {
+                incident_id: incident.clone(),
+                node_ids: vec![c.clone()],
+                provenance: Some(prov()),
+                rationale: None,
+            })
+            .await
+            .unwrap();
+        let query = NeighborhoodQuery {
+            hops: 2,
+            direction: Direction::Both,
+            ..query
+        };
+        let result = store.get_neighborhood(&incident, &query).await.unwrap();
+        let graph = result.graph.unwrap();
+        let ids: Vec<_> = graph.nodes.iter().map(|n| n.id.clone()).collect();
+        assert_eq!(ids, [a, b]);
+        assert_eq!(graph.edges.len(), 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a running Neo4j (TEE_NEO4J_TEST_URI)"]
+    async fn metrics_count_the_incident_tombstones() {
+        let store = test_store().await;
+        let incident = unique("inc");
+        let [a, b] = [unique("a"), unique("b")];
+        store
+            .merge_hypothesis(
+                proto::HypothesisDelta {
+                    nodes: vec![
+                        make_node(&a, proto::NodeType::Service, "a"),
+                        make_node(&b, proto::NodeType::Service, "b"),
+                    ],
+                    edges: vec![make_edge(&a, &b)],
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        store
+            .create_incident(create_request(&incident, true))
+            .await
+            .unwrap();
+        store
+            .merge_node_tombstones(proto::NodeTombstoneRequest {
+                incident_id: incident.clone(),
+                node_ids: vec![a, unique("ghost")],
+                provenance: Some(prov()),
+                rationale: None,
+            })
+            .await
+            .unwrap();
+
+        // Other tests share the database, so only this incident's eliminations are exact
+        let metrics = store.get_incident_metrics(&incident, None).await.unwrap();
+        assert_eq!(metrics.eliminated_nodes, 1);
+        assert_eq!(metrics.eliminated_edges, 1);
+        assert_eq!(metrics.unmatched_node_tombstones, 1);
+        assert!(metrics.live_nodes >= 1);
+        assert!(metrics.candidate_count >= 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a running Neo4j (TEE_NEO4J_TEST_URI)"]
+    async fn anchored_incident_excludes_later_hypotheses() {
+        let store = test_store().await;
+        let pinned = unique("pinned");
+        let following = unique("following");
+        store
+            .create_incident(create_request(&pinned, false))
+            .await
+            .unwrap();
+        store
+            .create_incident(create_request(&following, true))
+            .await
+            .unwrap();
+
+        let late = unique("late");
+        store
+            .merge_hypothesis(
+                proto::HypothesisDelta {
+                    nodes: vec![make_node(&late, proto::NodeType::Service, "late")],
+                    edges: vec![],
+                    atomic: false,
+                    dangling_edge_policy: proto::DanglingEdgePolicy::Report as i32,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let pinned_view = store
+            .get_live_view(&pinned, &GraphQuery::default())
+            .await
+            .unwrap();
+        assert!(pinned_view.nodes.iter().all(|n| n.id != late));
+        let following_view = store
+            .get_live_view(&following, &GraphQuery::default())
+            .await
+            .unwrap();
+        assert!(following_view.nodes.iter().any(|n| n.id == late));
+
+        let ctx = store.get_incident_context(&pinned).await.unwrap();
+        assert_eq!(ctx.universe_anchor, pinned_view.version);
+        assert!(!ctx.follows_main_graph);
+        assert_eq!(ctx.elimination_set_id, elimination_set_id(&pinned));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a running Neo4j (TEE_NEO4J_TEST_URI)"]
+    async fn main_graph_as_of_reconstructs_past_version() {
+        let store = test_store().await;
+        let id = unique("node");
+        let first = store
+            .merge_hypothesis(
+                proto::HypothesisDelta {
+                    nodes: vec![make_node(&id, proto::NodeType::Service, "svc")],
+                    edges: vec![],
+                    atomic: false,
+                    dangling_edge_policy: proto::DanglingEdgePolicy::Report as i32,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let mut confirm = make_node(&id, proto::NodeType::Service, "svc");
+        confirm.hypothetical = false;
+        confirm.provenance[0].trigger = "confirmed".into();
+        let second = store
+            .merge_hypothesis(
+                proto::HypothesisDelta {
+                    nodes: vec![confirm],
+                    edges: vec![],
+                    atomic: false,
+                    dangling_edge_policy: proto::DanglingEdgePolicy::Report as i32,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(second.version > first.version);
+
+        let past = store
+            .get_main_graph(&GraphQuery::as_of(first.version))
+            .await
+            .unwrap();
+        let node = past.nodes.iter().find(|n| n.id == id).unwrap();
+        assert!(node.hypothetical);
+        assert_eq!(node.provenance.len(), 1);
+
+        let head = store.get_main_graph(&GraphQuery::default()).await.unwrap();
+        let node = head.nodes.iter().find(|n| n.id == id).unwrap();
+        assert!(!node.hypothetical);
+        assert_eq!(node.provenance.len(), 2);
+
+        assert!(matches!(
+            store
+                .get_main_graph(&GraphQuery::as_of(head.version + 1))
+                .await,
+            Err(StoreError::VersionUnavailable { .. })
+        ));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a running Neo4j (TEE_NEO4J_TEST_URI)"]
+    async fn graph_size_grows_with_writes() {
+        let store = test_store().await;
+        let before = store.graph_size().await.unwrap();
+        let merged = store
+            .merge_hypothesis(
+                proto::HypothesisDelta {
+                    nodes: vec![make_node(&unique("node"), proto::NodeType::Service, "svc")],
+                    edges: vec![],
+                    atomic: false,
+                    dangling_edge_policy: proto::DanglingEdgePolicy::Report as i32,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        // Other tests share the database, so only a lower bound holds
+        let after = store.graph_size().await.unwrap();
+        assert!(after.nodes > before.nodes);
+        assert!(after.version >= merged.version);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a running Neo4j (TEE_NEO4J_TEST_URI)"]
+    async fn confirm_nodes_flips_hypothetical_once() {
+        let store = test_store().await;
+        let id = unique("node");
+        let ghost = unique("ghost");
+        let merged = store
+            .merge_hypothesis(
+                proto::HypothesisDelta {
+                    nodes: vec![make_node(&id, proto::NodeType::Service, "svc")],
+                    edges: vec![],
+                    atomic: false,
+                    dangling_edge_policy: proto::DanglingEdgePolicy::Report as i32,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let mut reviewer = prov();
+        reviewer.source = "reviewer".into();
+        let request = proto::ConfirmNodesRequest {
+            node_ids: vec![id.clone(), ghost.clone()],
+            provenance: Some(reviewer),
+        };
+        let result = store.confirm_nodes(request.clone()).await.unwrap();
+        assert_eq!(result.confirmed_ids, vec![id.clone()]);
+        assert_eq!(result.unknown_ids, vec![ghost]);
+        assert!(result.version > merged.version);
+
+        let again = store.confirm_nodes(request).await.unwrap();
+        assert_eq!(again.already_confirmed_ids, vec![id.clone()]);
+
+        let head = store.get_main_graph(&GraphQuery::default()).await.unwrap();
+        let node = head.nodes.iter().find(|n| n.id == id).unwrap();
+        assert!(!node.hypothetical);
+        assert_eq!(node.provenance.len(), 2);
+        let past = store
+            .get_main_graph(&GraphQuery::as_of(merged.version))
+            .await
+            .unwrap();
+        assert!(past.nodes.iter().find(|n| n.id == id).unwrap().hypothetical);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a running Neo4j (TEE_NEO4J_TEST_URI)"]
+    async fn main_graph_pages_are_filtered_and_bounded() {
+        let store = test_store().await;
+        let source = unique("agent");
+        let mut prov = prov();
+        prov.source = source.clone();
+        let ids = [unique("a"), unique("b"), unique("c")];
+        let nodes = ids
+            .iter()
+            .map(|id| proto::Node {
+                provenance: vec![prov.clone()],
+                ..make_node(id, proto::NodeType::Service, "svc")
+            })
+            .collect();
+        store
+            .merge_hypothesis(
+                proto::HypothesisDelta {
+                    nodes,
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let mut query = GraphQuery {
+            filter: GraphFilter {
+                provenance_sources: [source].into(),
+                ..GraphFilter::default()
+            },
+            page_size: 2,
+            ..GraphQuery::default()
+        };
+        let mut seen = Vec::new();
+        loop {
+            let page = store.get_main_graph(&query).await.unwrap();
+            assert!(page.nodes.len() + page.edges.len() <= 2);
+            seen.extend(page.nodes.into_iter().map(|n| n.id));
+            if page.next_page_token.is_empty() {
+                break;
+            }
+            query.page_token = Some(PageToken::decode(&page.next_page_token).unwrap());
+        }
+        seen.sort();
+        assert_eq!(seen, ids);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a running Neo4j (TEE_NEO4J_TEST_URI)"]
+    async fn change_feed_resumes_after_cursor() {
+        let store = test_store().await;
+        let id = unique("node");
+        let first = store
+            .merge_hypothesis(
+                proto::HypothesisDelta {
+                    nodes: vec![make_node(&id, proto::NodeType::Service, "svc")],
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        let mut confirm = make_node(&id, proto::NodeType::Service, "svc");
+        confirm.hypothetical = false;
+        confirm.provenance[0].trigger = "confirmed".into();
+        let second = store
+            .merge_hypothesis(
+                proto::HypothesisDelta {
+                    nodes: vec![confirm],
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let changes = store
+            .get_main_graph_changes(first.version - 1, u64::MAX)
+            .await
+            .unwrap();
+        let created = &changes[0];
+        assert_eq!(created.cursor, first.version);
+        assert!(created
+            .created_nodes
+            .iter()
+            .any(|n| n.id == id && n.hypothetical));
+
+        let resumed = store
+            .get_main_graph_changes(first.version, u64::MAX)
+            .await
+            .unwrap();
+        let change = resumed.iter().find(|c| c.cursor == second.version).unwrap();
+        assert_eq!(change.confirmed_node_ids, vec![id.clone()]);
+        assert_eq!(change.provenance_appends.len(), 1);
+        assert!(resumed.iter().all(|c| c.cursor > first.version));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a running Neo4j (TEE_NEO4J_TEST_URI)"]
+    async fn atomic_merge_with_conflicts_rolls_back() {
+        let store = test_store().await;
+        let existing = unique("existing");
+        let fresh = unique("fresh");
+        store
+            .merge_hypothesis(
+                proto::HypothesisDelta {
+                    nodes: vec![make_node(&existing, proto::NodeType::Service, "svc")],
+                    edges: vec![],
+                    atomic: false,
+                    dangling_edge_policy: proto::DanglingEdgePolicy::Report as i32,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let result = store
+            .merge_hypothesis(
+                proto::HypothesisDelta {
+                    nodes: vec![
+                        make_node(&fresh, proto::NodeType::Service, "fresh"),
+                        make_node(&existing, proto::NodeType::Service, "renamed"),
+                    ],
+                    edges: vec![make_edge(&fresh, &existing)],
+                    atomic: true,
+                    dangling_edge_policy: proto::DanglingEdgePolicy::Report as i32,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.conflicts.len(), 1);
+        assert!(result.created_ids.is_empty());
+
+        let graph = store.get_main_graph(&GraphQuery::default()).await.unwrap();
+        assert!(graph.nodes.iter().all(|n| n.id != fresh));
+        assert!(graph.edges.iter().all(|e| e.source != fresh));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a running Neo4j (TEE_NEO4J_TEST_URI)"]
+    async fn dangling_edge_policies() {
+        let store = test_store().await;
+        let a = unique("a");
+        let missing = unique("missing");
+        let delta = |policy: proto::DanglingEdgePolicy| {
+            let mut delta = proto::HypothesisDelta {
+                nodes: vec![make_node(&a, proto::NodeType::Service, "svc")],
+                edges: vec![make_edge(&a, &missing)],
+                atomic: false,
+                dangling_edge_policy: 0,
+            };
+            delta.set_dangling_edge_policy(policy);
+            delta
+        };
+
+        let rejected = store
+            .merge_hypothesis(delta(proto::DanglingEdgePolicy::Reject), None)
+            .await
+            .unwrap();
+        assert_eq!(rejected.dangling_edge_ids.len(), 1);
+        assert_eq!(rejected.created_ids, vec![a.clone()]);
+
+        let placed = store
+            .merge_hypothesis(delta(proto::DanglingEdgePolicy::Placeholder), None)
+            .await
+            .unwrap();
+        assert!(placed.dangling_edge_ids.is_empty());
+        assert!(placed.created_ids.contains(&missing));
+
+        let refined = store
+            .merge_hypothesis(
+                proto::HypothesisDelta {
+                    nodes: vec![make_node(&missing, proto::NodeType::Infrastructure, "db")],
+                    edges: vec![],
+                    atomic: false,
+                    dangling_edge_policy: proto::DanglingEdgePolicy::Report as i32,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(refined.conflicts.is_empty());
+        assert_eq!(refined.merged_ids, vec![missing.clone()]);
+
+        // Reads before the real write still see the placeholder
+        let before = store
+            .get_main_graph(&GraphQuery::as_of(placed.version))
+            .await
+            .unwrap();
+        let node = before.nodes.iter().find(|n| n.id == missing).unwrap();
+        assert_eq!(node.r#type, proto::NodeType::Placeholder as i32);
+        assert_eq!(node.label, missing);
+        let after = store
+            .get_main_graph(&GraphQuery::as_of(refined.version))
+            .await
+            .unwrap();
+        let node = after.nodes.iter().find(|n| n.id == missing).unwrap();
+        assert_eq!(node.r#type, proto::NodeType::Infrastructure as i32);
+        assert_eq!(node.label, "db");
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a running Neo4j (TEE_NEO4J_TEST_URI)"]
+    async fn unknown_incident_is_not_found() {
+        let store = test_store().await;
+        let result = store.get_tombstones(&unique("nope")).await;
+        assert!(matches!(result, Err(StoreError::IncidentNotFound(_))));
+    }
+}
@@ -0,0 +1,419 @@
+//! Filtering and pagination of graph reads (`GetMainGraph`, `GetLiveView` and their
+//! streaming variants).
+//!
+//! A page holds up to `page_size` elements: nodes in id order, then edges in key
+//! order. Its `next_page_token` records the version the first page was read at and
+//! the last element returned, so following pages continue from the same version
+//! even while the graph grows.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::edge::EdgeKey;
+use crate::domain::edge_type::EdgeType;
+use crate::domain::node_type::NodeType;
+use crate::proto;
+use crate::proto_convert::{edge_type_of, node_type_of};
+
+use super::{resolve_as_of, StoreError};
+
+/// Which nodes and edges a graph read returns. Empty sets match everything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GraphFilter {
+    pub node_types: BTreeSet<NodeType>,
+    pub edge_types: BTreeSet<EdgeType>,
+    /// Keeps elements with at least one provenance entry from one of these sources.
+    pub provenance_sources: BTreeSet<String>,
+    /// Keeps only nodes whose `hypothetical` flag has this value. Edges are unaffected.
+    pub hypothetical: Option<bool>,
+}
+
+impl From<proto::GraphFilter> for GraphFilter {
+    fn from(filter: proto::GraphFilter) -> Self {
+        Self {
+            node_types: filter
+                .node_types
+                .iter()
+                .map(|name| NodeType::from_name(name))
+                .collect(),
+            edge_types: filter
+                .edge_types
+                .iter()
+                .map(|name| EdgeType::from_name(name))
+                .collect(),
+            provenance_sources: filter.provenance_sources.into_iter().collect(),
+            hypothetical: filter.hypothetical,
+        }
+    }
+}
+
+impl GraphFilter {
+    pub fn matches_node(&self, node: &proto::Node) -> bool {
+        let type_matches = self.node_types.is_empty()
+            || node_type_of(node.r#type, &node.kind).is_ok_and(|t| self.node_types.contains(&t));
+        type_matches
+            && self.hypothetical.is_none_or(|h| h == node.hypothetical)
+            && self.source_matches(&node.provenance)
+    }
+
+    pub fn matches_edge(&self, edge: &proto::Edge) -> bool {
+        let type_matches = self.edge_types.is_empty()
+            || edge_type_of(edge.r#type, &edge.kind).is_ok_and(|t| self.edge_types.contains(&t));
+        type_matches && self.source_matches(&edge.provenance)
+    }
+
+    fn source_matches(&self, provenance: &[proto::Provenance]) -> bool {
+        self.provenance_sources.is_empty()
+            || provenance
+                .iter()
+                .any(|p| self.provenance_sources.contains(&p.source))
+    }
+}
+
+/// The last element of a page, which the next page starts after.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PagePosition {
+    Node(String),
+    Edge {
+        source: String,
+        target: String,
+        edge_type: String,
+    },
+}
+
+impl PagePosition {
+    fn of_edge(edge: &proto::Edge) -> Self {
+        let edge_type = edge_type_of(edge.r#type, &edge.kind)
+            .map(|t| t.to_string())
+            .unwrap_or_else(|_| edge.kind.clone());
+        Self::Edge {
+            source: edge.source.clone(),
+            target: edge.target.clone(),
+            edge_type,
+        }
+    }
+
+    /// The edge key this position names, if it is an edge.
+    pub fn edge_key(&self) -> Option<EdgeKey> {
+        match self {
+            Self::Node(_) => None,
+            Self::Edge {
+                source,
+                target,
+                edge_type,
+            } => Some(EdgeKey::new(
+                source.clone(),
+                target.clone(),
+                EdgeType::from_name(edge_type),
+            )),
+        }
+    }
+}
+
+/// Decoded `page_token`: where the previous page ended and the version it was read at.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageToken {
+    pub version: u64,
+    pub after: PagePosition,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("invalid page token")]
+pub struct InvalidPageToken;
+
+impl PageToken {
+    /// Encodes the token as an opaque hex string.
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).expect("page tokens serialize");
+        json.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    pub fn decode(token: &str) -> Result<Self, InvalidPageToken> {
+        if !token.len().is_multiple_of(2) || !token.is_ascii() {
+            return Err(InvalidPageToken);
+        }
+        let bytes = (0..token.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&token[i..i + 2], 16))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| InvalidPageToken)?;
+        serde_json::from_slice(&bytes).map_err(|_| InvalidPageToken)
+    }
+}
+
+/// Parameters of a graph read. The default reads the whole graph at the default version.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GraphQuery {
+    /// Ignored when continuing from a `page_token`, which carries its own version.
+    pub as_of_version: Option<u64>,
+    pub filter: GraphFilter,
+    /// Maximum number of nodes and edges per page; `0` returns everything.
+    pub page_size: usize,
+    pub page_token: Option<PageToken>,
+}
+
+impl GraphQuery {
+    /// A whole-graph read at `version`.
+    pub fn as_of(version: u64) -> Self {
+        Self {
+            as_of_version: Some(version),
+            ..Self::default()
+        }
+    }
+
+    /// Builds a query from the fields shared by `MainGraphRequest` and `LiveViewRequest`.
+    pub fn from_request(
+        as_of_version: Option<u64>,
+        filter: Option<proto::GraphFilter>,
+        page_size: u32,
+        page_token: &str,
+    ) -> Result<Self, InvalidPageToken> {
+        let page_token = if page_token.is_empty() {
+            None
+        } else {
+            Some(PageToken::decode(page_token)?)
+        };
+        Ok(Self {
+            as_of_version,
+            filter: filter.map(GraphFilter::from).unwrap_or_default(),
+            page_size: page_size as usize,
+            page_token,
+        })
+    }
+
+    /// The version to read at, given the latest version the read may see.
+    pub(crate) fn resolve_version(&self, latest: u64) -> Result<u64, StoreError> {
+        match &self.page_token {
+            Some(token) => resolve_as_of(Some(token.version), latest),
+            None => resolve_as_of(self.as_of_version, latest),
+        }
+    }
+
+    /// Where this page starts: after this position, or at the beginning if `None`.
+    pub(crate) fn after(&self) -> Option<&PagePosition> {
+        self.page_token.as_ref().map(|token| &token.after)
+    }
+}
+
+/// Collects one page of a graph read. Stores offer elements in page order, unfiltered;
+/// the builder applies the filter and stops accepting once the page is full.
+#[derive(Debug)]
+pub(crate) struct PageBuilder<'a> {
+    filter: &'a GraphFilter,
+    limit: usize,
+    version: u64,
+    nodes: Vec<proto::Node>,
+    edges: Vec<proto::Edge>,
+    last: Option<PagePosition>,
+    next: Option<PagePosition>,
+}
+
+impl<'a> PageBuilder<'a> {
+    pub(crate) fn new(query: &'a GraphQuery, version: u64) -> Self {
+        Self {
+            filter: &query.filter,
+            limit: if query.page_size == 0 {
+                usize::MAX
+            } else {
+                query.page_size
+            },
+            version,
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            last: None,
+            next: None,
+        }
+    }
+
+    /// Room left on the page.
+    pub(crate) fn remaining(&self) -> usize {
+        self.limit - (self.nodes.len() + self.edges.len())
+    }
+
+    /// Whether the page has ended; no further elements are accepted.
+    pub(crate) fn is_closed(&self) -> bool {
+        self.next.is_some()
+    }
+
+    /// Offers the next node. Returns `false` once the page is full.
+    pub(crate) fn node(&mut self, node: proto::Node) -> bool {
+        if self.is_closed() {
+            return false;
+        }
+        if !self.filter.matches_node(&node) {
+            return true;
+        }
+        if self.remaining() == 0 {
+            self.next = self.last.take();
+            return false;
+        }
+        self.last = Some(PagePosition::Node(node.id.clone()));
+        self.nodes.push(node);
+        true
+    }
+
+    /// Offers the next edge. Returns `false` once the page is full.
+    pub(crate) fn edge(&mut self, edge: proto::Edge) -> bool {
+        if self.is_closed() {
+            return false;
+        }
+        if !self.filter.matches_edge(&edge) {
+            return true;
+        }
+        if self.remaining() == 0 {
+            self.next = self.last.take();
+            return false;
+        }
+        self.last = Some(PagePosition::of_edge(&edge));
+        self.edges.push(edge);
+        true
+    }
+
+    /// Ends the page after `position`, for a store that stopped reading there with
+    /// more elements left.
+    pub(crate) fn end_at(&mut self, position: PagePosition) {
+        if !self.is_closed() {
+            self.next = Some(position);
+        }
+    }
+
+    pub(crate) fn finish(self) -> proto::CausalGraph {
+        let next_page_token = self
+            .next
+            .map(|after| {
+                PageToken {
+                    version: self.version,
+                    after,
+                }
+                .encode()
+            })
+            .unwrap_or_default();
+        proto::CausalGraph {
+            nodes: self.nodes,
+            edges: self.edges,
+            version: self.version,
+            next_page_token,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str, hypothetical: bool, source: &str) -> proto::Node {
+        proto::Node {
+            id: id.into(),
+            r#type: proto::NodeType::Service as i32,
+            label: id.into(),
+            hypothetical,
+            provenance: vec![proto::Provenance {
+                source: source.into(),
+                ..Default::default()
+            }],
+            kind: "SERVICE".into(),
+        }
+    }
+
+    fn edge(source: &str, target: &str) -> proto::Edge {
+        proto::Edge {
+            source: source.into(),
+            target: target.into(),
+            r#type: proto::EdgeType::DependsOn as i32,
+            provenance: vec![],
+            kind: "DEPENDS_ON".into(),
+        }
+    }
+
+    #[test]
+    fn page_token_roundtrip() {
+        let token = PageToken {
+            version: 7,
+            after: PagePosition::Edge {
+                source: "a".into(),
+                target: "b".into(),
+                edge_type: "DEPENDS_ON".into(),
+            },
+        };
+        let encoded = token.encode();
+        assert!(encoded.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(PageToken::decode(&encoded).unwrap(), token);
+    }
+
+    #[test]
+    fn malformed_page_token_is_rejected() {
+        assert!(PageToken::decode("abc").is_err());
+        assert!(PageToken::decode("zz").is_err());
+        assert!(PageToken::decode("7b7d").is_err()); // "{}"
+        assert!(GraphQuery::from_request(None, None, 0, "nope").is_err());
+    }
+
+    #[test]
+    fn filter_matches_type_source_and_hypothetical() {
+        let filter = GraphFilter::from(proto::GraphFilter {
+            node_types: vec!["SERVICE".into()],
+            edge_types: vec!["PROPAGATES_TO".into()],
+            provenance_sources: vec!["agent-1".into()],
+            hypothetical: Some(true),
+        });
+        assert!(filter.matches_node(&node("a", true, "agent-1")));
+        assert!(!filter.matches_node(&node("a", false, "agent-1")));
+        assert!(!filter.matches_node(&node("a", true, "agent-2")));
+        assert!(!filter.matches_edge(&edge("a", "b")));
+        assert!(GraphFilter::default().matches_edge(&edge("a", "b")));
+    }
+
+    #[test]
+    fn builder_stops_at_page_size_and_records_last_element() {
+        let query = GraphQuery {
+            page_size: 2,
+            ..GraphQuery::default()
+        };
+        let mut page = PageBuilder::new(&query, 4);
+        assert!(page.node(node("a", true, "x")));
+        assert!(page.node(node("b", true, "x")));
+        assert!(!page.edge(edge("a", "b")));
+        let graph = page.finish();
+
+        assert_eq!(graph.nodes.len(), 2);
+        assert!(graph.edges.is_empty());
+        let token = PageToken::decode(&graph.next_page_token).unwrap();
+        assert_eq!(token.version, 4);
+        assert_eq!(token.after, PagePosition::Node("b".into()));
+    }
+
+    #[test]
+    fn filtered_elements_do_not_fill_the_page() {
+        let query = GraphQuery {
+            page_size: 1,
+            filter: GraphFilter {
+                hypothetical: Some(false),
+                ..GraphFilter::default()
+            },
+            ..GraphQuery::default()
+        };
+        let mut page = PageBuilder::new(&query, 1);
+        assert!(page.node(node("a", true, "x")));
+        assert!(page.node(node("b", false, "x")));
+        assert!(page.node(node("c", true, "x")));
+        let graph = page.finish();
+        assert_eq!(graph.nodes.len(), 1);
+        assert_eq!(graph.nodes[0].id, "b");
+        assert!(graph.next_page_token.is_empty());
+    }
+
+    #[test]
+    fn token_version_overrides_as_of() {
+        let query = GraphQuery {
+            as_of_version: Some(1),
+            page_token: Some(PageToken {
+                version: 3,
+                after: PagePosition::Node("a".into()),
+            }),
+            ..GraphQuery::default()
+        };
+        assert_eq!(query.resolve_version(5).unwrap(), 3);
+        assert!(query.resolve_version(2).is_err());
+    }
+}
@@ -0,0 +1,433 @@
+//! Write-ahead log and snapshots backing the durable mode of
+//! [`InMemoryStore`](super::memory::InMemoryStore).
+//!
+//! The log is a sequence of framed records:
+//!
+//! ```text
+//! [len: u32 LE][crc32: u32 LE][seq: u64 LE][payload: len bytes]
+//! ```
+//!
+//! where `payload` is a prost-encoded [`Record`] and the CRC covers `seq` and
+//! `payload`. Sequence numbers increase monotonically across snapshots.
+//!
+//! A snapshot is the serialized store state plus the sequence number of the last
+//! record it includes. It is written to a temporary file and renamed into place,
+//! after which the log is truncated. On open, records already covered by the
+//! snapshot are skipped, so a crash between the rename and the truncation is safe.
+//!
+//! A record that is cut short or fails its checksum is a torn tail when no intact
+//! record follows it: a crash mid-append is the only way that arises, so it is
+//! truncated away. If intact records do follow, the log is corrupt in the middle and
+//! [`Wal::open`] fails with [`WalError::Corrupt`] rather than drop acknowledged writes.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use prost::Message;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::proto;
+
+const LOG_FILE: &str = "wal.log";
+const SNAPSHOT_FILE: &str = "snapshot.json";
+const SNAPSHOT_TMP_FILE: &str = "snapshot.json.tmp";
+
+/// `len` + `crc32` + `seq`.
+const HEADER_LEN: usize = 4 + 4 + 8;
+
+#[derive(Debug, thiserror::Error)]
+pub enum WalError {
+    #[error("write-ahead log I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("snapshot is unreadable: {0}")]
+    Snapshot(#[from] serde_json::Error),
+    #[error(
+        "write-ahead log is corrupt at byte {offset}, but intact records follow from byte \
+         {resumes_at}; refusing to truncate them"
+    )]
+    Corrupt { offset: usize, resumes_at: usize },
+}
+
+/// One accepted mutation, as appended to the log.
+#[derive(Clone, PartialEq, Message)]
+pub struct Record {
+    #[prost(oneof = "Op", tags = "1, 2, 3, 4, 5")]
+    pub op: Option<Op>,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+pub enum Op {
+    #[prost(message, tag = "1")]
+    MergeHypothesis(proto::HypothesisDelta),
+    #[prost(message, tag = "2")]
+    CreateIncident(IncidentCreated),
+    #[prost(message, tag = "3")]
+    MergeNodeTombstones(proto::NodeTombstoneRequest),
+    #[prost(message, tag = "4")]
+    MergeEdgeTombstones(proto::EdgeTombstoneRequest),
+    #[prost(message, tag = "5")]
+    ConfirmNodes(proto::ConfirmNodesRequest),
+}
+
+/// `CreateIncident` with its server-assigned creation time, so replay reproduces it.
+#[derive(Clone, PartialEq, Message)]
+pub struct IncidentCreated {
+    #[prost(string, tag = "1")]
+    pub incident_id: String,
+    #[prost(bool, tag = "2")]
+    pub follow_main_graph: bool,
+    #[prost(message, optional, tag = "3")]
+    pub created_at: Option<prost_types::Timestamp>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot<S> {
+    /// Sequence number of the last log record folded into `state`.
+    seq: u64,
+    state: S,
+}
+
+/// What [`Wal::open`] recovered from disk.
+pub struct Recovered<S> {
+    pub wal: Wal,
+    /// The latest snapshot, if one has been written.
+    pub snapshot: Option<S>,
+    /// Log records written after the snapshot, in order.
+    pub records: Vec<Op>,
+}
+
+/// An open write-ahead log directory.
+#[derive(Debug)]
+pub struct Wal {
+    dir: PathBuf,
+    log: File,
+    /// Length of the log's valid prefix; a failed append is rolled back to it.
+    log_len: u64,
+    next_seq: u64,
+    records_since_snapshot: u64,
+    snapshot_every: u64,
+}
+
+impl Wal {
+    /// Opens (creating if needed) the log in `dir`, truncating any torn tail. Fails
+    /// with [`WalError::Corrupt`] if a bad record is followed by intact ones.
+    /// A snapshot is due after every `snapshot_every` appended records; `0` disables
+    /// periodic snapshots.
+    pub fn open<S: DeserializeOwned>(
+        dir: impl AsRef<Path>,
+        snapshot_every: u64,
+    ) -> Result<Recovered<S>, WalError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        let snapshot: Option<Snapshot<S>> = match fs::read(dir.join(SNAPSHOT_FILE)) {
+            Ok(bytes) => Some(serde_json::from_slice(&bytes)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => return Err(err.into()),
+        };
+        let snapshot_seq = snapshot.as_ref().map_or(0, |s| s.seq);
+
+        let mut log = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(dir.join(LOG_FILE))?;
+        let mut bytes = Vec::new();
+        log.read_to_end(&mut bytes)?;
+
+        let (frames, valid_len) = decode_frames(&bytes);
+        if valid_len < bytes.len() {
+            let last_seq = frames.last().map_or(snapshot_seq, |(seq, _)| *seq);
+            if let Some(resumes_at) = find_frame_after(&bytes, valid_len, last_seq) {
+                return Err(WalError::Corrupt {
+                    offset: valid_len,
+                    resumes_at,
+                });
+            }
+            tracing::warn!(
+                "truncating {} bytes of torn write-ahead log tail",
+                bytes.len() - valid_len
+            );
+            log.set_len(valid_len as u64)?;
+            log.sync_data()?;
+        }
+
+        let last_seq = frames.last().map_or(snapshot_seq, |(seq, _)| *seq);
+        let records: Vec<Op> = frames
+            .into_iter()
+            .filter(|(seq, _)| *seq > snapshot_seq)
+            .map(|(_, op)| op)
+            .collect();
+
+        Ok(Recovered {
+            wal: Wal {
+                dir,
+                log,
+                log_len: valid_len as u64,
+                next_seq: last_seq.max(snapshot_seq) + 1,
+                records_since_snapshot: records.len() as u64,
+                snapshot_every,
+            },
+            snapshot: snapshot.map(|s| s.state),
+            records,
+        })
+    }
+
+    /// Appends `op` and syncs it to disk.
+    pub fn append(&mut self, op: Op) -> Result<(), WalError> {
+        let payload = Record { op: Some(op) }.encode_to_vec();
+        let seq = self.next_seq;
+
+        let mut frame = Vec::with_capacity(HEADER_LEN + payload.len());
+        frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        frame.extend_from_slice(&checksum(seq, &payload).to_le_bytes());
+        frame.extend_from_slice(&seq.to_le_bytes());
+        frame.extend_from_slice(&payload);
+
+        if let Err(err) = self
+            .log
+            .write_all(&frame)
+            .and_then(|_| self.log.sync_data())
+        {
+            // Leave no partial frame behind for later appends to land after.
+            let _ = self.log.set_len(self.log_len);
+            return Err(err.into());
+        }
+        self.log_len += frame.len() as u64;
+        self.next_seq += 1;
+        self.records_since_snapshot += 1;
+        Ok(())
+    }
+
+    /// True once `snapshot_every` records have been appended since the last snapshot.
+    pub fn snapshot_due(&self) -> bool {
+        self.snapshot_every > 0 && self.records_since_snapshot >= self.snapshot_every
+    }
+
+    /// Writes `state` as the new snapshot and truncates the log.
+    pub fn snapshot<S: Serialize>(&mut self, state: &S) -> Result<(), WalError> {
+        let snapshot = Snapshot {
+            seq: self.next_seq - 1,
+            state,
+        };
+        let tmp = self.dir.join(SNAPSHOT_TMP_FILE);
+        let mut file = File::create(&tmp)?;
+        serde_json::to_writer(&mut file, &snapshot)?;
+        file.sync_all()?;
+        fs::rename(&tmp, self.dir.join(SNAPSHOT_FILE))?;
+        sync_dir(&self.dir)?;
+
+        self.log.set_len(0)?;
+        self.log.sync_data()?;
+        self.log_len = 0;
+        self.records_since_snapshot = 0;
+        Ok(())
+    }
+}
+
+fn checksum(seq: u64, payload: &[u8]) -> u32 {
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(&seq.to_le_bytes());
+    hasher.update(payload);
+    hasher.finalize()
+}
+
+/// Decodes the frame starting at `offset`, returning its `(seq, op)` and the offset
+/// just past it, or `None` if it is incomplete or corrupt.
+fn decode_frame(bytes: &[u8], offset: usize) -> Option<((u64, Op), usize)> {
+    let header = bytes.get(offset..offset.checked_add(HEADER_LEN)?)?;
+    let len = u32::from_le_bytes(header[0..4].try_into().unwrap()) as usize;
+    let crc = u32::from_le_bytes(header[4..8].try_into().unwrap());
+    let seq = u64::from_le_bytes(header[8..16].try_into().unwrap());
+
+    let start = offset + HEADER_LEN;
+    let payload = bytes.get(start..start.checked_add(len)?)?;
+    if checksum(seq, payload) != crc {
+        return None;
+    }
+    let op = Record::decode(payload).ok()?.op?;
+    Some(((seq, op), start + len))
+}
+
+/// Decodes frames until the first incomplete or corrupt one. Returns the decoded
+/// `(seq, op)` pairs and the length of the valid prefix.
+fn decode_frames(bytes: &[u8]) -> (Vec<(u64, Op)>, usize) {
+    let mut frames = Vec::new();
+    let mut offset = 0;
+    while let Some((frame, end)) = decode_frame(bytes, offset) {
+        frames.push(frame);
+        offset = end;
+    }
+    (frames, offset)
+}
+
+/// The offset of the first intact frame after the bad one at `offset`, if any. Only
+/// frames sequenced after `last_seq` count, so stray bytes in a torn tail that happen
+/// to look like an old frame don't fail recovery.
+fn find_frame_after(bytes: &[u8], offset: usize, last_seq: u64) -> Option<usize> {
+    (offset + 1..bytes.len())
+        .find(|&at| decode_frame(bytes, at).is_some_and(|((seq, _), _)| seq > last_seq))
+}
+
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create(incident_id: &str) -> Op {
+        Op::CreateIncident(IncidentCreated {
+            incident_id: incident_id.into(),
+            follow_main_graph: false,
+            created_at: None,
+        })
+    }
+
+    fn incident_ids(records: &[Op]) -> Vec<&str> {
+        records
+            .iter()
+            .map(|op| match op {
+                Op::CreateIncident(created) => created.incident_id.as_str(),
+                other => panic!("unexpected record {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn records_survive_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut wal = Wal::open::<Vec<String>>(dir.path(), 0).unwrap().wal;
+        wal.append(create("inc-1")).unwrap();
+        wal.append(create("inc-2")).unwrap();
+        drop(wal);
+
+        let recovered = Wal::open::<Vec<String>>(dir.path(), 0).unwrap();
+        assert!(recovered.snapshot.is_none());
+        assert_eq!(incident_ids(&recovered.records), vec!["inc-1", "inc-2"]);
+    }
+
+    #[test]
+    fn torn_tail_is_truncated() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut wal = Wal::open::<Vec<String>>(dir.path(), 0).unwrap().wal;
+        wal.append(create("inc-1")).unwrap();
+        wal.append(create("inc-2")).unwrap();
+        drop(wal);
+
+        // Cut the last record in half
+        let path = dir.path().join(LOG_FILE);
+        let len = fs::metadata(&path).unwrap().len();
+        OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(len - 5)
+            .unwrap();
+
+        let mut recovered = Wal::open::<Vec<String>>(dir.path(), 0).unwrap();
+        assert_eq!(incident_ids(&recovered.records), vec!["inc-1"]);
+
+        // Appending after recovery continues from the valid prefix
+        recovered.wal.append(create("inc-3")).unwrap();
+        drop(recovered);
+        let recovered = Wal::open::<Vec<String>>(dir.path(), 0).unwrap();
+        assert_eq!(incident_ids(&recovered.records), vec!["inc-1", "inc-3"]);
+    }
+
+    #[test]
+    fn corrupt_last_record_is_truncated() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut wal = Wal::open::<Vec<String>>(dir.path(), 0).unwrap().wal;
+        wal.append(create("inc-1")).unwrap();
+        wal.append(create("inc-2")).unwrap();
+        drop(wal);
+
+        let path = dir.path().join(LOG_FILE);
+        let mut bytes = fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        fs::write(&path, bytes).unwrap();
+
+        let recovered = Wal::open::<Vec<String>>(dir.path(), 0).unwrap();
+        assert_eq!(incident_ids(&recovered.records), vec!["inc-1"]);
+    }
+
+    #[test]
+    fn corrupt_record_followed_by_intact_ones_fails_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut wal = Wal::open::<Vec<String>>(dir.path(), 0).unwrap().wal;
+        for id in ["inc-1", "inc-2", "inc-3"] {
+            wal.append(create(id)).unwrap();
+        }
+        drop(wal);
+
+        let path = dir.path().join(LOG_FILE);
+        let intact = fs::read(&path).unwrap();
+        let frame_len = intact.len() / 3;
+        // A flipped payload bit, then a length that runs past the end of the log
+        for (at, mask) in [(2 * frame_len - 1, 0x01), (frame_len, 0xff)] {
+            let mut bytes = intact.clone();
+            bytes[at] ^= mask;
+            fs::write(&path, &bytes).unwrap();
+
+            let Err(err) = Wal::open::<Vec<String>>(dir.path(), 0) else {
+                panic!("a corrupt record before intact ones must fail open");
+            };
+            assert!(matches!(
+                err,
+                WalError::Corrupt { offset, resumes_at }
+                    if offset == frame_len && resumes_at == 2 * frame_len
+            ));
+            assert_eq!(fs::read(&path).unwrap(), bytes, "nothing is truncated");
+        }
+    }
+
+    #[test]
+    fn snapshot_compacts_the_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut wal = Wal::open::<Vec<String>>(dir.path(), 2).unwrap().wal;
+        wal.append(create("inc-1")).unwrap();
+        assert!(!wal.snapshot_due());
+        wal.append(create("inc-2")).unwrap();
+        assert!(wal.snapshot_due());
+
+        wal.snapshot(&vec!["inc-1".to_string(), "inc-2".to_string()])
+            .unwrap();
+        assert!(!wal.snapshot_due());
+        assert_eq!(fs::metadata(dir.path().join(LOG_FILE)).unwrap().len(), 0);
+        wal.append(create("inc-3")).unwrap();
+        drop(wal);
+
+        let recovered = Wal::open::<Vec<String>>(dir.path(), 2).unwrap();
+        assert_eq!(recovered.snapshot.unwrap(), vec!["inc-1", "inc-2"]);
+        assert_eq!(incident_ids(&recovered.records), vec!["inc-3"]);
+    }
+
+    #[test]
+    fn records_covered_by_snapshot_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut wal = Wal::open::<Vec<String>>(dir.path(), 0).unwrap().wal;
+        wal.append(create("inc-1")).unwrap();
+        let log_before = fs::read(dir.path().join(LOG_FILE)).unwrap();
+        wal.snapshot(&vec!["inc-1".to_string()]).unwrap();
+        drop(wal);
+
+        // Simulate a crash between the snapshot rename and the log truncation
+        fs::write(dir.path().join(LOG_FILE), log_before).unwrap();
+
+        let recovered = Wal::open::<Vec<String>>(dir.path(), 0).unwrap();
+        assert_eq!(recovered.snapshot.unwrap(), vec!["inc-1"]);
+        assert!(recovered.records.is_empty());
+        assert_eq!(recovered.wal.next_seq, 2);
+    }
+}
@@ -0,0 +1,206 @@
+//! TLS for the gRPC endpoint, and the identity of clients that present a certificate.
+//!
+//! With a client CA bundle configured the server verifies client certificates against
+//! it, and [`attach_client_identity`] stamps the principal a verified certificate names
+//! on each request. Handlers find it among the request's extensions as a
+//! [`ClientIdentity`], to check against the `Provenance.source` a client writes under.
+
+use std::path::{Path, PathBuf};
+
+use tonic::transport::{Certificate, Identity, ServerTlsConfig};
+use tonic::{Request, Status};
+use x509_parser::prelude::{FromDer, GeneralName, X509Certificate};
+
+use crate::config::TlsConfig;
+
+#[derive(Debug, thiserror::Error)]
+#[error("failed to read {path}: {source}")]
+pub struct TlsError {
+    path: PathBuf,
+    source: std::io::Error,
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, TlsError> {
+    std::fs::read(path).map_err(|source| TlsError {
+        path: path.to_owned(),
+        source,
+    })
+}
+
+/// The server's TLS settings: its certificate and key and, with a client CA bundle,
+/// the verification of client certificates.
+pub fn server_config(tls: &TlsConfig) -> Result<ServerTlsConfig, TlsError> {
+    let identity = Identity::from_pem(read(&tls.cert_file)?, read(&tls.key_file)?);
+    let mut config = ServerTlsConfig::new().identity(identity);
+    if let Some(client_ca_file) = &tls.client_ca_file {
+        config = config
+            .client_ca_root(Certificate::from_pem(read(client_ca_file)?))
+            .client_auth_optional(tls.client_auth_optional);
+    }
+    Ok(config)
+}
+
+/// The principal a client's verified certificate names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientIdentity {
+    pub principal: String,
+}
+
+impl ClientIdentity {
+    /// The certificate's subject common name or, lacking one, its first DNS or URI
+    /// subject alternative name.
+    pub fn from_certificate(der: &[u8]) -> Option<Self> {
+        let (_, cert) = X509Certificate::from_der(der).ok()?;
+        let common_name = cert
+            .subject()
+            .iter_common_name()
+            .find_map(|cn| cn.as_str().ok());
+        let principal = match common_name {
+            Some(name) => name.to_string(),
+            None => cert
+                .subject_alternative_name()
+                .ok()??
+                .value
+                .general_names
+                .iter()
+                .find_map(|name| match name {
+                    GeneralName::DNSName(name) | GeneralName::URI(name) => Some(name.to_string()),
+                    _ => None,
+                })?,
+        };
+        Some(Self { principal })
+    }
+
+    /// The identity the client's certificate names, if it presented one.
+    pub fn of_peer<T>(request: &Request<T>) -> Option<Self> {
+        let certs = request.peer_certs()?;
+        Self::from_certificate(certs.first()?)
+    }
+}
+
+/// Interceptor recording the [`ClientIdentity`] of each request's peer, if it has one.
+pub fn attach_client_identity(mut request: Request<()>) -> Result<Request<()>, Status> {
+    if let Some(identity) = ClientIdentity::of_peer(&request) {
+        request.extensions_mut().insert(identity);
+    }
+    Ok(request)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::{Channel, ClientTlsConfig, Server};
+
+    use super::*;
+    use crate::proto::tee_client::TeeClient;
+    use crate::proto::tee_server::TeeServer;
+    use crate::proto::TombstoneRequest;
+    use crate::service::TeeService;
+    use crate::store::memory::InMemoryStore;
+
+    // testdata/tls holds a test CA, a server certificate for localhost, and client
+    // certificates for agent-1 (by common name) and agent-2 (by URI SAN only).
+    const CA: &str = include_str!("../testdata/tls/ca.pem");
+
+    fn testdata(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("testdata/tls")
+            .join(name)
+    }
+
+    fn der(name: &str) -> Vec<u8> {
+        let pem = std::fs::read(testdata(name)).unwrap();
+        let (_, pem) = x509_parser::pem::parse_x509_pem(&pem).unwrap();
+        pem.contents
+    }
+
+    #[test]
+    fn identity_is_common_name_else_first_san() {
+        let agent_1 = ClientIdentity::from_certificate(&der("agent-1.pem")).unwrap();
+        assert_eq!(agent_1.principal, "agent-1");
+        let agent_2 = ClientIdentity::from_certificate(&der("agent-2.pem")).unwrap();
+        assert_eq!(agent_2.principal, "spiffe://tee/agent-2");
+        assert_eq!(ClientIdentity::from_certificate(b"not a certificate"), None);
+    }
+
+    #[tokio::test]
+    async fn mutual_tls_exposes_the_client_identity() {
+        let tls = TlsConfig {
+            cert_file: testdata("server.pem"),
+            key_file: testdata("server.key"),
+            client_ca_file: Some(testdata("ca.pem")),
+            client_auth_optional: false,
+        };
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        let service = TeeServer::with_interceptor(
+            TeeService::new(Arc::new(InMemoryStore::new())),
+            move |request: Request<()>| {
+                let request = attach_client_identity(request)?;
+                let identity = request.extensions().get::<ClientIdentity>().cloned();
+                recorder.lock().unwrap().push(identity);
+                Ok(request)
+            },
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            Server::builder()
+                .tls_config(server_config(&tls).unwrap())
+                .unwrap()
+                .add_service(service)
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+
+        let client_tls = |cert: Option<&str>| {
+            let config = ClientTlsConfig::new()
+                .ca_certificate(Certificate::from_pem(CA))
+                .domain_name("localhost");
+            match cert {
+                Some(name) => config.identity(Identity::from_pem(
+                    std::fs::read(testdata(&format!("{name}.pem"))).unwrap(),
+                    std::fs::read(testdata(&format!("{name}.key"))).unwrap(),
+                )),
+                None => config,
+            }
+        };
+        let request = || TombstoneRequest {
+            incident_id: "inc-1".into(),
+        };
+
+        let channel = Channel::from_shared(format!("https://{addr}"))
+            .unwrap()
+            .tls_config(client_tls(Some("agent-1")))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        // The incident doesn't exist; the call only has to reach the service
+        let status = TeeClient::new(channel)
+            .get_tombstones(request())
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+        let expected = ClientIdentity {
+            principal: "agent-1".into(),
+        };
+        assert_eq!(*seen.lock().unwrap(), [Some(expected)]);
+
+        // Without a certificate the handshake fails before any request is served
+        let anonymous = Channel::from_shared(format!("https://{addr}"))
+            .unwrap()
+            .tls_config(client_tls(None))
+            .unwrap()
+            .connect()
+            .await;
+        if let Ok(channel) = anonymous {
+            assert!(TeeClient::new(channel)
+                .get_tombstones(request())
+                .await
+                .is_err());
+        }
+        assert_eq!(seen.lock().unwrap().len(), 1);
+    }
+}
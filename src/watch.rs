@@ -0,0 +1,251 @@
+//! Turns the store's change events into the incremental updates of one incident's
+//! live view, for the `WatchLiveView` stream.
+//!
+//! A watch starts from a snapshot: the incident's tombstones and the version of the
+//! live view sent first. Main-graph changes at or before that version are already in
+//! the snapshot and are dropped, as is everything the incident has tombstoned. An
+//! incident pinned to its universe anchor never sees main-graph changes at all, only
+//! its own tombstones.
+
+use std::collections::BTreeSet;
+
+use crate::domain::edge::EdgeKey;
+use crate::proto;
+use crate::proto::live_view_event::Event;
+use crate::proto_convert::edge_type_of;
+use crate::store::StoreEvent;
+
+/// Filtering state for one incident's `WatchLiveView` stream.
+#[derive(Debug)]
+pub struct LiveViewWatch {
+    incident_id: String,
+    follows_main_graph: bool,
+    /// Version of the snapshot the watch started from.
+    snapshot_version: u64,
+    /// Version of the view after the last event passed on.
+    version: u64,
+    node_tombstones: BTreeSet<String>,
+    edge_tombstones: BTreeSet<EdgeKey>,
+}
+
+impl LiveViewWatch {
+    /// Starts a watch from the incident's context and the version of the live view
+    /// sent as the snapshot. The context must be read before the live view, so a
+    /// tombstone landing in between is reported rather than lost.
+    pub fn new(context: &proto::IncidentContext, snapshot_version: u64) -> Self {
+        let tombstones = context.tombstones.clone().unwrap_or_default();
+        Self {
+            incident_id: context.incident_id.clone(),
+            follows_main_graph: context.follows_main_graph,
+            snapshot_version,
+            version: snapshot_version,
+            node_tombstones: tombstones.node_ids.into_iter().collect(),
+            edge_tombstones: tombstones
+                .edge_entries
+                .iter()
+                .filter_map(|e| edge_key(&e.source, &e.target, e.r#type, &e.kind))
+                .collect(),
+        }
+    }
+
+    /// The first message of the stream.
+    pub fn snapshot(&self, graph: proto::CausalGraph) -> proto::LiveViewEvent {
+        proto::LiveViewEvent {
+            version: graph.version,
+            event: Some(Event::Snapshot(graph)),
+        }
+    }
+
+    /// The update `event` makes to the live view, if any.
+    pub fn apply(&mut self, event: StoreEvent) -> Option<proto::LiveViewEvent> {
+        let event = match event {
+            StoreEvent::NodeAdded { version, node } => {
+                self.advance(version)?;
+                if self.node_tombstones.contains(&node.id) {
+                    return None;
+                }
+                Event::NodeAdded(node)
+            }
+            StoreEvent::EdgeAdded { version, edge } => {
+                self.advance(version)?;
+                let hidden = self.node_tombstones.contains(&edge.source)
+                    || self.node_tombstones.contains(&edge.target)
+                    || edge_key(&edge.source, &edge.target, edge.r#type, &edge.kind)
+                        .is_some_and(|key| self.edge_tombstones.contains(&key));
+                if hidden {
+                    return None;
+                }
+                Event::EdgeAdded(edge)
+            }
+            StoreEvent::NodeConfirmed { version, node_id } => {
+                self.advance(version)?;
+                if self.node_tombstones.contains(&node_id) {
+                    return None;
+                }
+                Event::NodeConfirmed(node_id)
+            }
+            StoreEvent::NodeTombstoned {
+                incident_id,
+                node_id,
+            } => {
+                if incident_id != self.incident_id || !self.node_tombstones.insert(node_id.clone())
+                {
+                    return None;
+                }
+                Event::NodeTombstoned(node_id)
+            }
+            StoreEvent::EdgeTombstoned { incident_id, entry } => {
+                if incident_id != self.incident_id {
+                    return None;
+                }
+                let key = edge_key(&entry.source, &entry.target, entry.r#type, &entry.kind)?;
+                if !self.edge_tombstones.insert(key) {
+                    return None;
+                }
+                Event::EdgeTombstoned(entry)
+            }
+        };
+        Some(proto::LiveViewEvent {
+            version: self.version,
+            event: Some(event),
+        })
+    }
+
+    /// Moves the view to a main-graph change at `version`, or `None` if the change is
+    /// not part of this view: already in the snapshot, or past a pinned anchor.
+    fn advance(&mut self, version: u64) -> Option<()> {
+        if !self.follows_main_graph || version <= self.snapshot_version {
+            return None;
+        }
+        self.version = self.version.max(version);
+        Some(())
+    }
+}
+
+/// Identity of an edge or tombstone entry; `None` for a kind that cannot be resolved.
+fn edge_key(source: &str, target: &str, r#type: i32, kind: &str) -> Option<EdgeKey> {
+    let edge_type = edge_type_of(r#type, kind).ok()?;
+    Some(EdgeKey::new(
+        source.to_string(),
+        target.to_string(),
+        edge_type,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(follows_main_graph: bool, tombstoned_node: &str) -> proto::IncidentContext {
+        proto::IncidentContext {
+            incident_id: "inc-1".to_string(),
+            tombstones: Some(proto::TombstoneSet {
+                node_ids: vec![tombstoned_node.to_string()],
+                edge_entries: vec![proto::EdgeTombstoneEntry {
+                    source: "a".to_string(),
+                    target: "b".to_string(),
+                    r#type: proto::EdgeType::DependsOn as i32,
+                    kind: String::new(),
+                }],
+                ..Default::default()
+            }),
+            follows_main_graph,
+            ..Default::default()
+        }
+    }
+
+    fn node_added(id: &str, version: u64) -> StoreEvent {
+        StoreEvent::NodeAdded {
+            version,
+            node: proto::Node {
+                id: id.to_string(),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn edge_added(source: &str, target: &str, version: u64) -> StoreEvent {
+        StoreEvent::EdgeAdded {
+            version,
+            edge: proto::Edge {
+                source: source.to_string(),
+                target: target.to_string(),
+                r#type: proto::EdgeType::DependsOn as i32,
+                kind: "DEPENDS_ON".to_string(),
+                provenance: vec![],
+            },
+        }
+    }
+
+    fn node_tombstoned(incident_id: &str, node_id: &str) -> StoreEvent {
+        StoreEvent::NodeTombstoned {
+            incident_id: incident_id.to_string(),
+            node_id: node_id.to_string(),
+        }
+    }
+
+    #[test]
+    fn passes_changes_after_the_snapshot() {
+        let mut watch = LiveViewWatch::new(&context(true, "x"), 3);
+
+        assert!(watch.apply(node_added("n", 3)).is_none());
+        let event = watch.apply(node_added("n", 4)).unwrap();
+        assert_eq!(event.version, 4);
+        assert!(matches!(event.event, Some(Event::NodeAdded(ref n)) if n.id == "n"));
+
+        let event = watch
+            .apply(StoreEvent::NodeConfirmed {
+                version: 5,
+                node_id: "n".to_string(),
+            })
+            .unwrap();
+        assert_eq!(event.event, Some(Event::NodeConfirmed("n".to_string())));
+        assert_eq!(event.version, 5);
+    }
+
+    #[test]
+    fn pinned_incident_ignores_main_graph_changes() {
+        let mut watch = LiveViewWatch::new(&context(false, "x"), 3);
+
+        assert!(watch.apply(node_added("n", 4)).is_none());
+        assert!(watch.apply(edge_added("n", "m", 4)).is_none());
+
+        let event = watch.apply(node_tombstoned("inc-1", "n")).unwrap();
+        assert_eq!(event.event, Some(Event::NodeTombstoned("n".to_string())));
+        assert_eq!(event.version, 3);
+    }
+
+    #[test]
+    fn hides_tombstoned_nodes_and_edges() {
+        let mut watch = LiveViewWatch::new(&context(true, "x"), 0);
+
+        assert!(watch.apply(node_added("x", 1)).is_none());
+        assert!(watch.apply(edge_added("x", "y", 1)).is_none());
+        assert!(watch.apply(edge_added("a", "b", 1)).is_none());
+        assert!(watch.apply(edge_added("b", "a", 1)).is_some());
+    }
+
+    #[test]
+    fn reports_each_tombstone_of_this_incident_once() {
+        let mut watch = LiveViewWatch::new(&context(true, "x"), 0);
+
+        assert!(watch.apply(node_tombstoned("inc-2", "n")).is_none());
+        assert!(watch.apply(node_tombstoned("inc-1", "x")).is_none());
+        assert!(watch.apply(node_tombstoned("inc-1", "n")).is_some());
+        assert!(watch.apply(node_tombstoned("inc-1", "n")).is_none());
+        // A node tombstoned after the snapshot also hides its later edges
+        assert!(watch.apply(edge_added("n", "m", 1)).is_none());
+
+        let entry = proto::EdgeTombstoneEntry {
+            source: "a".to_string(),
+            target: "b".to_string(),
+            r#type: proto::EdgeType::DependsOn as i32,
+            kind: "DEPENDS_ON".to_string(),
+        };
+        let event = StoreEvent::EdgeTombstoned {
+            incident_id: "inc-1".to_string(),
+            entry,
+        };
+        assert!(watch.apply(event).is_none());
+    }
+}